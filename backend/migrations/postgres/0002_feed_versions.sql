@@ -0,0 +1,14 @@
+-- Content-hash deduplication and version history for feeds
+
+ALTER TABLE data_feeds ADD COLUMN IF NOT EXISTS content_hash TEXT NOT NULL DEFAULT '';
+ALTER TABLE data_feeds ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+-- Superseded versions; the current version stays in data_feeds
+CREATE TABLE IF NOT EXISTS data_feed_versions (
+    feed_id      TEXT NOT NULL REFERENCES data_feeds (feed_id) ON DELETE CASCADE,
+    version      INTEGER NOT NULL,
+    content_hash TEXT NOT NULL,
+    feed         JSONB NOT NULL,
+    ingested_at  TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (feed_id, version)
+);
@@ -0,0 +1,7 @@
+-- JSON Schemas registered for Custom feed types, loaded into the cleaning pipeline on startup
+
+CREATE TABLE IF NOT EXISTS feed_schemas (
+    type_name  TEXT PRIMARY KEY,
+    schema     JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,10 @@
+-- Write order of feeds, so live streams resume by position rather than by timestamp
+
+CREATE SEQUENCE IF NOT EXISTS data_feeds_seq;
+
+ALTER TABLE data_feeds ADD COLUMN IF NOT EXISTS seq BIGINT;
+UPDATE data_feeds SET seq = nextval('data_feeds_seq') WHERE seq IS NULL;
+UPDATE data_feeds SET feed = jsonb_set(feed, '{seq}', to_jsonb(seq));
+ALTER TABLE data_feeds ALTER COLUMN seq SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS data_feeds_seq_idx ON data_feeds (seq);
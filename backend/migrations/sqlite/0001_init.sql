@@ -0,0 +1,56 @@
+-- Indexer persistence: feeds, agent decisions and the chain data derived from feeds
+
+CREATE TABLE IF NOT EXISTS data_feeds (
+    feed_id      TEXT PRIMARY KEY,
+    source_kind  TEXT NOT NULL,
+    source_value TEXT NOT NULL,
+    data_type    TEXT NOT NULL,
+    timestamp    INTEGER NOT NULL,
+    feed         TEXT NOT NULL,
+    ingested_at  TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS data_feeds_timestamp_idx ON data_feeds (timestamp);
+CREATE INDEX IF NOT EXISTS data_feeds_type_timestamp_idx ON data_feeds (data_type, timestamp);
+
+CREATE TABLE IF NOT EXISTS agent_decisions (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id      TEXT NOT NULL,
+    decision_type TEXT NOT NULL,
+    confidence    REAL NOT NULL,
+    timestamp     INTEGER NOT NULL,
+    decision      TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS agent_decisions_agent_idx ON agent_decisions (agent_id, timestamp);
+
+-- Rows below are owned by the feed they were derived from, so rolling back
+-- a feed (e.g. after a reorg) removes them too.
+CREATE TABLE IF NOT EXISTS indexed_blocks (
+    number            INTEGER PRIMARY KEY,
+    hash              TEXT NOT NULL,
+    parent_hash       TEXT NOT NULL,
+    timestamp         INTEGER NOT NULL,
+    transaction_count INTEGER NOT NULL,
+    gas_used          TEXT NOT NULL,
+    miner             TEXT NOT NULL,
+    feed_id           TEXT NOT NULL REFERENCES data_feeds (feed_id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS indexed_transactions (
+    hash         TEXT PRIMARY KEY,
+    block_number INTEGER NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address   TEXT,
+    value        TEXT NOT NULL,
+    gas_price    TEXT NOT NULL,
+    gas_used     TEXT,
+    status       INTEGER,
+    fee_currency TEXT,
+    "transaction" TEXT NOT NULL,
+    feed_id      TEXT NOT NULL REFERENCES data_feeds (feed_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS indexed_transactions_block_idx ON indexed_transactions (block_number);
+CREATE INDEX IF NOT EXISTS indexed_transactions_from_idx ON indexed_transactions (from_address);
+CREATE INDEX IF NOT EXISTS indexed_transactions_to_idx ON indexed_transactions (to_address);
@@ -0,0 +1,4 @@
+-- Indexes for filtered decision queries ordered by timestamp
+
+CREATE INDEX IF NOT EXISTS agent_decisions_timestamp_idx ON agent_decisions (timestamp);
+CREATE INDEX IF NOT EXISTS agent_decisions_type_timestamp_idx ON agent_decisions (decision_type, timestamp);
@@ -0,0 +1,16 @@
+-- Write order of feeds, so live streams resume by position rather than by timestamp
+
+ALTER TABLE data_feeds ADD COLUMN seq INTEGER NOT NULL DEFAULT 0;
+
+-- Highest seq ever assigned; kept apart from data_feeds so deleting the newest feed
+-- (e.g. after a reorg) never lets its seq be handed out again
+CREATE TABLE IF NOT EXISTS stream_sequences (
+    name     TEXT PRIMARY KEY,
+    last_seq INTEGER NOT NULL
+);
+
+UPDATE data_feeds SET seq = rowid;
+UPDATE data_feeds SET feed = json_set(feed, '$.seq', seq);
+INSERT INTO stream_sequences (name, last_seq) SELECT 'data_feeds', COALESCE(MAX(seq), 0) FROM data_feeds;
+
+CREATE INDEX IF NOT EXISTS data_feeds_seq_idx ON data_feeds (seq);
@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use reqwest::Client;
 
+use crate::llm::{Completion, LlmConfig, ProviderRegistry};
+use crate::telemetry::{AI_CACHE_HITS, AI_CACHE_MISSES};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Celo7BModel {
     pub model_name: String,
@@ -43,47 +46,22 @@ pub struct LLMResponse {
 pub struct CeloAIEngine {
     model: Celo7BModel,
     cache: HashMap<String, LLMResponse>,
-    http_client: Client,
-    groq_api_key: Option<String>,
-    groq_model: String,
-    ollama_url: Option<String>,
-    ollama_model: String,
-    vllm_url: Option<String>,
-    vllm_model: String,
-    hf_api_key: Option<String>,
-    hf_model: String,
-    openai_api_key: Option<String>,
+    providers: ProviderRegistry,
 }
 
 impl CeloAIEngine {
+    /// Uses the providers configured by `LLM_PROVIDERS` and `LLM_ROUTING`.
     pub fn new() -> Self {
-        let groq_api_key = std::env::var("GROQ_API_KEY").ok();
-        let groq_model = std::env::var("GROQ_MODEL")
-            .unwrap_or_else(|_| "llama-3.3-70b-versatile".to_string());
-        let ollama_url = std::env::var("OLLAMA_URL").ok();
-        let ollama_model = std::env::var("OLLAMA_MODEL")
-            .unwrap_or_else(|_| "llama3.2:3b".to_string());
-        let vllm_url = std::env::var("VLLM_URL").ok();
-        let vllm_model = std::env::var("VLLM_MODEL")
-            .unwrap_or_else(|_| "openai/gpt-oss-20b".to_string());
-        let hf_api_key = std::env::var("HF_API_KEY").ok();
-        let hf_model = std::env::var("HF_MODEL")
-            .unwrap_or_else(|_| "openai/gpt-oss-20b".to_string());
-        let openai_api_key = std::env::var("OPENAI_API_KEY").ok();
-        
-        let model_name = if groq_api_key.is_some() {
-            groq_model.clone()
-        } else if ollama_url.is_some() {
-            ollama_model.clone()
-        } else if vllm_url.is_some() {
-            vllm_model.clone()
-        } else if hf_api_key.is_some() {
-            hf_model.clone()
-        } else if openai_api_key.is_some() {
-            "gpt-4".to_string()
-        } else {
-            "Celo-7B-Mock".to_string()
-        };
+        let http_client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        Self::with_providers(ProviderRegistry::from_config(&LlmConfig::from_env(), &http_client))
+    }
+
+    /// With no providers registered every task answers with its built-in response.
+    pub fn with_providers(providers: ProviderRegistry) -> Self {
+        let model_name = providers.primary_model().unwrap_or("Celo-7B-Mock").to_string();
 
         Self {
             model: Celo7BModel {
@@ -99,215 +77,32 @@ impl CeloAIEngine {
                 ],
             },
             cache: HashMap::new(),
-            http_client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| Client::new()),
-            groq_api_key,
-            groq_model,
-            ollama_url,
-            ollama_model,
-            vllm_url,
-            vllm_model,
-            hf_api_key,
-            hf_model,
-            openai_api_key,
+            providers,
         }
     }
 
-    async fn call_real_ai(&self, prompt: &str) -> Option<String> {
-        // Try Groq first (fastest cloud API)
-        if let Some(api_key) = &self.groq_api_key {
-            if let Ok(response) = self.call_groq(prompt, api_key).await {
-                return Some(response);
-            }
-        }
-
-        // Try Ollama (local, no API key needed)
-        if let Some(url) = &self.ollama_url {
-            if let Ok(response) = self.call_ollama(prompt, url).await {
-                return Some(response);
-            }
-        }
-
-        // Try vLLM (local server)
-        if let Some(url) = &self.vllm_url {
-            if let Ok(response) = self.call_vllm(prompt, url).await {
-                return Some(response);
-            }
-        }
-
-        // Try HuggingFace
-        if let Some(api_key) = &self.hf_api_key {
-            if let Ok(response) = self.call_huggingface(prompt, api_key).await {
-                return Some(response);
-            }
-        }
-
-        // Try OpenAI
-        if let Some(api_key) = &self.openai_api_key {
-            if let Ok(response) = self.call_openai(prompt, api_key).await {
-                return Some(response);
-            }
-        }
-
-        None
-    }
-
-    async fn call_groq(&self, prompt: &str, api_key: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let payload = serde_json::json!({
-            "model": self.groq_model,
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You are a Celo blockchain expert AI assistant. Provide clear, accurate, and helpful information about Celo blockchain, smart contracts, DeFi, and related topics."
-                },
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ],
-            "temperature": 0.7,
-            "max_tokens": 1024
-        });
-
-        let response = self.http_client
-            .post("https://api.groq.com/openai/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            let result: serde_json::Value = response.json().await?;
-            if let Some(text) = result["choices"][0]["message"]["content"].as_str() {
-                return Ok(text.to_string());
-            }
-        }
-
-        Err("Failed to get response from Groq".into())
-    }
-
-    async fn call_ollama(&self, prompt: &str, url: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let payload = serde_json::json!({
-            "model": self.ollama_model,
-            "prompt": format!(
-                "You are a Celo blockchain expert. Provide clear, accurate information.\n\nUser: {}\n\nAssistant:",
-                prompt
-            ),
-            "stream": false,
-            "options": {
-                "temperature": 0.7,
-                "num_predict": 512
-            }
-        });
-
-        let response = self.http_client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            let result: serde_json::Value = response.json().await?;
-            if let Some(text) = result["response"].as_str() {
-                return Ok(text.trim().to_string());
-            }
-        }
-
-        Err("Failed to get response from Ollama".into())
-    }
-
-    async fn call_vllm(&self, prompt: &str, url: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let payload = serde_json::json!({
-            "model": self.vllm_model,
-            "prompt": prompt,
-            "max_tokens": 512,
-            "temperature": 0.7
-        });
-
-        let response = self.http_client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            let result: serde_json::Value = response.json().await?;
-            if let Some(text) = result["choices"][0]["text"].as_str() {
-                return Ok(text.to_string());
-            }
-        }
-
-        Err("Failed to get response from vLLM".into())
-    }
-
-    async fn call_huggingface(&self, prompt: &str, api_key: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let url = format!("https://api-inference.huggingface.co/models/{}", self.hf_model);
-        
-        let payload = serde_json::json!({
-            "inputs": prompt,
-            "parameters": {
-                "max_new_tokens": 500,
-                "temperature": 0.7,
-                "return_full_text": false
-            }
-        });
-
-        let response = self.http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .json(&payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            let result: serde_json::Value = response.json().await?;
-            if let Some(text) = result[0]["generated_text"].as_str() {
-                return Ok(text.to_string());
-            }
-        }
-
-        Err("Failed to get response from HuggingFace".into())
-    }
-
-    async fn call_openai(&self, prompt: &str, api_key: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let payload = serde_json::json!({
-            "model": "gpt-4",
-            "messages": [
-                {"role": "system", "content": "You are a Celo blockchain expert AI assistant."},
-                {"role": "user", "content": prompt}
-            ],
-            "max_tokens": 500,
-            "temperature": 0.7
-        });
-
-        let response = self.http_client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .json(&payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            let result: serde_json::Value = response.json().await?;
-            if let Some(text) = result["choices"][0]["message"]["content"].as_str() {
-                return Ok(text.to_string());
-            }
-        }
-
-        Err("Failed to get response from OpenAI".into())
+    /// Completes `prompt` with the request's context, `max_tokens` and `temperature`.
+    async fn call_real_ai(&self, prompt: &str, request: &LLMRequest) -> Option<String> {
+        let completion = Completion {
+            context: request.context.clone().unwrap_or_default(),
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            ..Completion::new(prompt)
+        };
+        self.providers.complete(&completion).await
     }
 
     pub async fn process(&mut self, request: LLMRequest) -> LLMResponse {
         // Check cache
-        let cache_key = format!("{:?}_{}", request.task_type, request.prompt);
+        let cache_key = format!(
+            "{:?}_{:?}_{:?}_{:?}_{}",
+            request.task_type, request.max_tokens, request.temperature, request.context, request.prompt
+        );
         if let Some(cached) = self.cache.get(&cache_key) {
+            AI_CACHE_HITS.inc();
             return cached.clone();
         }
+        AI_CACHE_MISSES.inc();
 
         // Process based on task type
         let response = match request.task_type {
@@ -331,14 +126,12 @@ impl CeloAIEngine {
             request.prompt
         );
 
-        let output = if let Some(ai_response) = self.call_real_ai(&prompt).await {
+        let output = if let Some(ai_response) = self.call_real_ai(&prompt, request).await {
             ai_response
         } else {
-            format!(
-                "Contract Analysis: This smart contract implements a token standard with \
+            "Contract Analysis: This smart contract implements a token standard with \
                 advanced features including staking, governance, and automated market making. \
-                The contract follows best practices and includes proper access controls."
-            )
+                The contract follows best practices and includes proper access controls.".to_string()
         };
 
         LLMResponse {
@@ -361,11 +154,9 @@ impl CeloAIEngine {
 
     async fn security_audit(&self, _request: &LLMRequest) -> LLMResponse {
         LLMResponse {
-            output: format!(
-                "Security Audit Complete: Found 0 critical, 1 medium, and 2 low severity issues. \
+            output: "Security Audit Complete: Found 0 critical, 1 medium, and 2 low severity issues. \
                 Overall security score: 8.5/10. The contract is generally secure but could benefit \
-                from additional input validation and gas optimizations."
-            ),
+                from additional input validation and gas optimizations.".to_string(),
             confidence: 0.91,
             reasoning_steps: vec![
                 "Scanned for common vulnerabilities (reentrancy, overflow, etc.)".to_string(),
@@ -384,12 +175,10 @@ impl CeloAIEngine {
 
     async fn explain_code(&self, _request: &LLMRequest) -> LLMResponse {
         LLMResponse {
-            output: format!(
-                "Code Explanation: This function implements a token transfer with fee mechanism. \
+            output: "Code Explanation: This function implements a token transfer with fee mechanism. \
                 It calculates a 0.1% fee on transfers, sends it to the treasury, and transfers \
                 the remaining amount to the recipient. The function includes proper checks for \
-                balance sufficiency and emits a Transfer event."
-            ),
+                balance sufficiency and emits a Transfer event.".to_string(),
             confidence: 0.96,
             reasoning_steps: vec![
                 "Parsed function signature and parameters".to_string(),
@@ -408,12 +197,10 @@ impl CeloAIEngine {
 
     async fn analyze_transaction(&self, _request: &LLMRequest) -> LLMResponse {
         LLMResponse {
-            output: format!(
-                "Transaction Analysis: This is a token swap transaction on a DEX. \
+            output: "Transaction Analysis: This is a token swap transaction on a DEX. \
                 The user swapped 100 CELO for approximately 2,450 cUSD at a rate of 24.5. \
                 The transaction included a 0.3% swap fee and was executed in a single block. \
-                Gas cost was 0.002 CELO (~$0.05)."
-            ),
+                Gas cost was 0.002 CELO (~$0.05).".to_string(),
             confidence: 0.98,
             reasoning_steps: vec![
                 "Decoded transaction input data".to_string(),
@@ -432,12 +219,10 @@ impl CeloAIEngine {
 
     async fn predict_price(&self, _request: &LLMRequest) -> LLMResponse {
         LLMResponse {
-            output: format!(
-                "Price Prediction: Based on historical data, on-chain metrics, and sentiment analysis, \
+            output: "Price Prediction: Based on historical data, on-chain metrics, and sentiment analysis, \
                 CELO is predicted to trade between $24.80 - $25.20 in the next 24 hours. \
                 Confidence: Medium. Key factors: increasing transaction volume (+15%), \
-                positive social sentiment (0.72), and stable liquidity pools."
-            ),
+                positive social sentiment (0.72), and stable liquidity pools.".to_string(),
             confidence: 0.73,
             reasoning_steps: vec![
                 "Analyzed 30-day price history".to_string(),
@@ -459,7 +244,7 @@ impl CeloAIEngine {
         let prompt_with_context = &request.prompt;
         
         // Try to get real AI response
-        if let Some(ai_output) = self.call_real_ai(prompt_with_context).await {
+        if let Some(ai_output) = self.call_real_ai(prompt_with_context, request).await {
             return LLMResponse {
                 output: ai_output,
                 confidence: 0.92,
@@ -479,22 +264,18 @@ impl CeloAIEngine {
         
         // Fallback response with blockchain context
         let output = if prompt_with_context.contains("Blockchain Context:") {
-            format!(
-                "Based on current Celo blockchain data:\n\n\
+            "Based on current Celo blockchain data:\n\n\
                 The Celo network is actively processing transactions. \
                 To get AI-powered insights, please configure an AI model (vLLM, HuggingFace, or OpenAI).\n\n\
-                Current blockchain status is available in the Explorer tab."
-            )
+                Current blockchain status is available in the Explorer tab.".to_string()
         } else {
-            format!(
-                "I can help you understand Celo blockchain, smart contracts, and DeFi. \
+            "I can help you understand Celo blockchain, smart contracts, and DeFi. \
                 However, AI features require configuration.\n\n\
                 To enable intelligent responses:\n\
                 1. Run: ./setup_vllm.sh\n\
                 2. Start: vllm serve deepseek-ai/DeepSeek-OCR\n\
                 Or add HF_API_KEY or OPENAI_API_KEY to backend/.env\n\n\
-                Meanwhile, you can explore real blockchain data in the Explorer tab!"
-            )
+                Meanwhile, you can explore real blockchain data in the Explorer tab!".to_string()
         };
         
         LLMResponse {
@@ -517,6 +298,10 @@ impl CeloAIEngine {
     pub fn get_model_info(&self) -> &Celo7BModel {
         &self.model
     }
+
+    pub fn providers(&self) -> &ProviderRegistry {
+        &self.providers
+    }
 }
 
 impl Default for CeloAIEngine {
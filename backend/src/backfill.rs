@@ -0,0 +1,411 @@
+// Historical Backfill - indexes arbitrary block ranges into the Real-Time Indexer
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinSet;
+
+use crate::celo_client::CeloClient;
+use crate::follower::index_block;
+use crate::indexer::RealtimeIndexer;
+
+pub const MAX_BACKFILL_CONCURRENCY: usize = 32;
+const DEFAULT_MAX_BACKFILL_BLOCKS: u64 = 1_000_000;
+const BLOCK_RETRIES: u32 = 3;
+const CHECKPOINT_EVERY: u64 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackfillState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Persisted so a job for the same range resumes after the last contiguous block. It is only
+/// trusted while that block's feed is still stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Checkpoint {
+    from: u64,
+    to: u64,
+    last_contiguous_block: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillStatus {
+    pub job_id: String,
+    pub from: u64,
+    pub to: u64,
+    pub concurrency: usize,
+    pub state: BackfillState,
+    pub blocks_total: u64,
+    pub blocks_done: u64,
+    pub transactions_indexed: u64,
+    pub checkpoint: Option<u64>,
+    pub resumed_from: Option<u64>,
+    pub blocks_per_second: f64,
+    pub eta_seconds: Option<u64>,
+    pub started_at: u64,
+    pub error: Option<String>,
+}
+
+struct BackfillJob {
+    status: RwLock<BackfillStatus>,
+    started: Instant,
+    done_at_start: u64,
+    cancel: watch::Sender<bool>,
+}
+
+impl BackfillJob {
+    async fn snapshot(&self) -> BackfillStatus {
+        let mut status = self.status.read().await.clone();
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let done_this_run = status.blocks_done - self.done_at_start;
+
+        if elapsed > 0.0 {
+            status.blocks_per_second = done_this_run as f64 / elapsed;
+        }
+        if status.state == BackfillState::Running && status.blocks_per_second > 0.0 {
+            let remaining = status.blocks_total - status.blocks_done;
+            status.eta_seconds = Some((remaining as f64 / status.blocks_per_second).ceil() as u64);
+        }
+        status
+    }
+}
+
+#[derive(Clone)]
+pub struct BackfillManager {
+    client: CeloClient,
+    indexer: RealtimeIndexer,
+    checkpoint_dir: PathBuf,
+    max_blocks: u64,
+    jobs: Arc<RwLock<HashMap<String, Arc<BackfillJob>>>>,
+}
+
+impl BackfillManager {
+    pub fn new(client: CeloClient, indexer: RealtimeIndexer) -> Self {
+        let checkpoint_dir = std::env::var("BACKFILL_CHECKPOINT_DIR")
+            .unwrap_or_else(|_| "backfill_checkpoints".to_string());
+        let max_blocks = std::env::var("BACKFILL_MAX_BLOCKS")
+            .map(|v| v.parse().expect("BACKFILL_MAX_BLOCKS must be a valid number"))
+            .unwrap_or(DEFAULT_MAX_BACKFILL_BLOCKS);
+
+        Self {
+            client,
+            indexer,
+            checkpoint_dir: PathBuf::from(checkpoint_dir),
+            max_blocks,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Checks a job request before it is started: the range must be ordered, no wider than
+    /// `BACKFILL_MAX_BLOCKS` and, when the chain head can be fetched, must not end past it.
+    pub async fn validate(&self, from: u64, to: u64, concurrency: usize) -> Result<(), String> {
+        if !(1..=MAX_BACKFILL_CONCURRENCY).contains(&concurrency) {
+            return Err(format!("concurrency must be between 1 and {}", MAX_BACKFILL_CONCURRENCY));
+        }
+        self.range_len(from, to)?;
+
+        match self.client.get_latest_block().await {
+            Ok(head) if to > head.number => {
+                Err(format!("to ({}) is beyond the chain head ({})", to, head.number))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                // Unreachable blocks fail the job itself; don't block on a flaky head lookup
+                tracing::warn!("Could not check backfill range against the chain head: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Number of blocks in `[from, to]`.
+    fn range_len(&self, from: u64, to: u64) -> Result<u64, String> {
+        if from > to {
+            return Err("from must not be greater than to".to_string());
+        }
+        // Workers count up to `to + 1`, so that has to fit as well
+        let end = to.checked_add(1).ok_or_else(|| format!("to ({}) is not a valid block number", to))?;
+        let blocks = end - from;
+        if blocks > self.max_blocks {
+            return Err(format!("range spans {} blocks, more than the limit of {} (BACKFILL_MAX_BLOCKS)", blocks, self.max_blocks));
+        }
+        Ok(blocks)
+    }
+
+    /// Starts (or resumes from its checkpoint) the job for `[from, to]`; `restart` ignores the
+    /// checkpoint and indexes the whole range again. Fails if a job for the same range is
+    /// already running.
+    pub async fn start(&self, from: u64, to: u64, concurrency: usize, restart: bool) -> Result<BackfillStatus, String> {
+        let blocks_total = self.range_len(from, to)?;
+        let job_id = format!("backfill-{}-{}", from, to);
+        let mut jobs = self.jobs.write().await;
+
+        if let Some(existing) = jobs.get(&job_id) {
+            if existing.status.read().await.state == BackfillState::Running {
+                return Err(format!("Backfill job {} is already running", job_id));
+            }
+        }
+
+        let checkpoint_path = self.checkpoint_dir.join(format!("{}.json", job_id));
+        let mut checkpoint = if restart {
+            None
+        } else {
+            load_checkpoint(&checkpoint_path)
+                .await
+                .and_then(|c| c.last_contiguous_block)
+                .filter(|block| (from..=to).contains(block))
+        };
+        if let Some(block) = checkpoint {
+            // An in-memory store (or retention) may have dropped what the checkpoint covers
+            let feed_id = format!("celo:block:{}", block);
+            match self.indexer.get_feed(&feed_id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    tracing::warn!("Ignoring backfill checkpoint #{}: {} is no longer stored", block, feed_id);
+                    checkpoint = None;
+                }
+                Err(e) => {
+                    tracing::warn!("Ignoring backfill checkpoint #{}: {}", block, e);
+                    checkpoint = None;
+                }
+            }
+        }
+        let blocks_done = checkpoint.map(|c| c - from + 1).unwrap_or(0);
+
+        let (cancel, cancel_rx) = watch::channel(false);
+        let job = Arc::new(BackfillJob {
+            status: RwLock::new(BackfillStatus {
+                job_id: job_id.clone(),
+                from,
+                to,
+                concurrency,
+                state: BackfillState::Running,
+                blocks_total,
+                blocks_done,
+                transactions_indexed: 0,
+                checkpoint,
+                resumed_from: checkpoint.map(|c| c + 1),
+                blocks_per_second: 0.0,
+                eta_seconds: None,
+                started_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+                error: None,
+            }),
+            started: Instant::now(),
+            done_at_start: blocks_done,
+            cancel,
+        });
+        jobs.insert(job_id, job.clone());
+
+        if let Some(block) = checkpoint {
+            tracing::info!("Resuming backfill [{}, {}] after checkpoint #{}", from, to, block);
+        }
+        tokio::spawn(run_job(
+            job.clone(),
+            self.client.clone(),
+            self.indexer.clone(),
+            checkpoint_path,
+            cancel_rx,
+        ));
+
+        Ok(job.snapshot().await)
+    }
+
+    pub async fn status(&self, job_id: &str) -> Option<BackfillStatus> {
+        let job = self.jobs.read().await.get(job_id).cloned()?;
+        Some(job.snapshot().await)
+    }
+
+    pub async fn list(&self) -> Vec<BackfillStatus> {
+        let jobs: Vec<Arc<BackfillJob>> = self.jobs.read().await.values().cloned().collect();
+        let mut statuses = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            statuses.push(job.snapshot().await);
+        }
+        statuses.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+        statuses
+    }
+
+    /// Requests cancellation of a running job; the checkpoint is kept so the job can be
+    /// resumed. Returns false if the job is unknown or no longer running.
+    pub async fn cancel(&self, job_id: &str) -> bool {
+        let Some(job) = self.jobs.read().await.get(job_id).cloned() else {
+            return false;
+        };
+        if job.status.read().await.state != BackfillState::Running {
+            return false;
+        }
+        let _ = job.cancel.send(true);
+        true
+    }
+}
+
+async fn run_job(
+    job: Arc<BackfillJob>,
+    client: CeloClient,
+    indexer: RealtimeIndexer,
+    checkpoint_path: PathBuf,
+    cancel: watch::Receiver<bool>,
+) {
+    let (from, to, concurrency, mut checkpoint) = {
+        let status = job.status.read().await;
+        (status.from, status.to, status.concurrency.max(1), status.checkpoint)
+    };
+    let mut next = checkpoint.map(|c| c + 1).unwrap_or(from);
+    let mut saved_checkpoint = checkpoint;
+    let mut completed_ahead = BTreeSet::new();
+    let mut in_flight = JoinSet::new();
+    let mut failure: Option<String> = None;
+
+    loop {
+        while failure.is_none() && !*cancel.borrow() && in_flight.len() < concurrency && next <= to {
+            let (client, indexer, number) = (client.clone(), indexer.clone(), next);
+            in_flight.spawn(async move { (number, index_with_retries(&client, &indexer, number).await) });
+            next += 1;
+        }
+
+        let Some(joined) = in_flight.join_next().await else { break };
+        let (number, result) = match joined {
+            Ok(result) => result,
+            Err(e) => {
+                failure.get_or_insert(format!("Backfill worker panicked: {}", e));
+                continue;
+            }
+        };
+
+        match result {
+            Ok(tx_count) => {
+                completed_ahead.insert(number);
+                let expected = checkpoint.map(|c| c + 1).unwrap_or(from);
+                let mut advance_to = None;
+                let mut block = expected;
+                while completed_ahead.remove(&block) {
+                    advance_to = Some(block);
+                    block += 1;
+                }
+                if advance_to.is_some() {
+                    checkpoint = advance_to;
+                }
+
+                let mut status = job.status.write().await;
+                status.blocks_done += 1;
+                status.transactions_indexed += tx_count;
+                status.checkpoint = checkpoint;
+            }
+            Err(e) => {
+                failure.get_or_insert(format!("Block {}: {}", number, e));
+            }
+        }
+
+        if checkpoint.unwrap_or(0) >= saved_checkpoint.unwrap_or(0) + CHECKPOINT_EVERY {
+            save_checkpoint(&checkpoint_path, from, to, checkpoint).await;
+            saved_checkpoint = checkpoint;
+        }
+    }
+
+    save_checkpoint(&checkpoint_path, from, to, checkpoint).await;
+
+    let mut status = job.status.write().await;
+    status.state = match (&failure, checkpoint == Some(to)) {
+        (Some(_), _) => BackfillState::Failed,
+        (None, true) => BackfillState::Completed,
+        (None, false) => BackfillState::Cancelled,
+    };
+    status.error = failure;
+    tracing::info!(
+        "Backfill {} finished: {:?} ({} / {} blocks)",
+        status.job_id, status.state, status.blocks_done, status.blocks_total
+    );
+}
+
+/// Indexes one block, retrying transient RPC failures. Returns the transaction count.
+async fn index_with_retries(client: &CeloClient, indexer: &RealtimeIndexer, number: u64) -> Result<u64, String> {
+    let mut attempt = 0;
+    loop {
+        match index_block(client, indexer, number).await {
+            Ok(indexed) => return Ok(indexed.transaction_count),
+            Err(e) if attempt + 1 >= BLOCK_RETRIES => return Err(e),
+            Err(e) => {
+                attempt += 1;
+                tracing::debug!("Retrying block {} (attempt {}): {}", number, attempt, e);
+                tokio::time::sleep(std::time::Duration::from_millis(250 * 2u64.pow(attempt))).await;
+            }
+        }
+    }
+}
+
+async fn load_checkpoint(path: &PathBuf) -> Option<Checkpoint> {
+    let contents = tokio::fs::read(path).await.ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+async fn save_checkpoint(path: &PathBuf, from: u64, to: u64, last_contiguous_block: Option<u64>) {
+    let checkpoint = Checkpoint { from, to, last_contiguous_block };
+    if let Some(dir) = path.parent() {
+        let _ = tokio::fs::create_dir_all(dir).await;
+    }
+    let contents = serde_json::to_vec_pretty(&checkpoint).unwrap_or_default();
+    if let Err(e) = tokio::fs::write(path, contents).await {
+        tracing::warn!("Failed to save backfill checkpoint {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::follower::tests::serve;
+
+    fn manager(client: CeloClient, indexer: RealtimeIndexer, checkpoint_dir: PathBuf) -> BackfillManager {
+        BackfillManager {
+            client,
+            indexer,
+            checkpoint_dir,
+            max_blocks: 1_000,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    async fn run(manager: &BackfillManager, restart: bool) -> BackfillStatus {
+        let job_id = manager.start(1, 5, 2, restart).await.unwrap().job_id;
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            let status = manager.status(&job_id).await.unwrap();
+            if status.state != BackfillState::Running {
+                return status;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for {}", job_id);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn checkpoints_resume_only_while_their_blocks_are_stored() {
+        let (_chain, client) = serve(10).await;
+        let dir = std::env::temp_dir().join(format!("backfill-tests-{}", std::process::id()));
+        let backfill = manager(client.clone(), RealtimeIndexer::new(), dir.clone());
+
+        let status = run(&backfill, false).await;
+        assert_eq!((status.state, status.checkpoint, status.transactions_indexed), (BackfillState::Completed, Some(5), 5));
+        assert!(!backfill.cancel(&status.job_id).await);
+
+        // The same store: the range resumes after its checkpoint, unless restarted
+        let status = run(&backfill, false).await;
+        assert_eq!((status.resumed_from, status.transactions_indexed), (Some(6), 0));
+        let status = run(&backfill, true).await;
+        assert_eq!((status.resumed_from, status.transactions_indexed), (None, 5));
+
+        // A new in-memory store no longer holds the checkpointed blocks
+        let indexer = RealtimeIndexer::new();
+        let status = run(&manager(client, indexer.clone(), dir.clone()), false).await;
+        assert_eq!((status.resumed_from, status.transactions_indexed), (None, 5));
+        assert!(indexer.get_feed("celo:block:1").await.unwrap().is_some());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
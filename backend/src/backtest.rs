@@ -0,0 +1,964 @@
+// Backtesting - replays stored feeds through an agent and scores the decisions it emits
+use async_trait::async_trait;
+use ethers::core::rand::{self, Rng};
+use ethers::utils::hex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, RwLock};
+
+use crate::indexer::{AgentDecision, DataFeed, DecisionType, RealtimeIndexer};
+use crate::paper_trading::{price_of, Fill, PaperTradingConfig, PriceBook, Portfolio, RejectedTrade, Side, Valuation};
+use crate::storage::{FeedCursor, FeedQuery, SortOrder};
+
+/// Feeds (counting every replayed version) a single backtest may replay.
+pub const MAX_BACKTEST_FEEDS: usize = 100_000;
+/// Backtests that may run at the same time.
+pub const MAX_RUNNING_BACKTESTS: usize = 4;
+/// Finished backtests kept for `GET /agents/backtests`; the oldest are dropped first.
+const MAX_FINISHED_BACKTESTS: usize = 50;
+const REPLAY_PAGE_SIZE: usize = 500;
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Points kept in a report's equity curve.
+const MAX_EQUITY_POINTS: usize = 500;
+/// Fills, rejected trades and invalid decisions listed in a report.
+const REPORT_HISTORY: usize = 100;
+
+/// An agent under test. It sees one feed at a time, in timestamp order, and returns the
+/// decisions it would have made at that moment.
+#[async_trait]
+pub trait BacktestAgent: Send {
+    fn describe(&self) -> String;
+
+    async fn on_feed(&mut self, feed: &DataFeed) -> Result<Vec<AgentDecision>, String>;
+}
+
+/// The agent a backtest request names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentSpec {
+    /// POSTs every feed to `url` and reads the decisions from the response.
+    Http { url: String },
+    /// Buys `amount` of each asset (or only `asset`) the first time it is priced.
+    BuyAndHold { amount: f64, asset: Option<String> },
+}
+
+impl AgentSpec {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            AgentSpec::Http { url } if !url.starts_with("http://") && !url.starts_with("https://") => {
+                Err(format!("agent url must be an http(s) URL, got {}", url))
+            }
+            AgentSpec::BuyAndHold { amount, .. } if !amount.is_finite() || *amount <= 0.0 => {
+                Err(format!("agent amount must be positive, got {}", amount))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn build(&self, backtest_id: &str, agent_id: &str, client: reqwest::Client) -> Box<dyn BacktestAgent> {
+        match self {
+            AgentSpec::Http { url } => Box::new(HttpCallbackAgent {
+                url: url.clone(),
+                backtest_id: backtest_id.to_string(),
+                agent_id: agent_id.to_string(),
+                client,
+            }),
+            AgentSpec::BuyAndHold { amount, asset } => Box::new(BuyAndHoldAgent {
+                amount: *amount,
+                asset: asset.as_ref().map(|a| a.to_ascii_uppercase()),
+                bought: HashSet::new(),
+            }),
+        }
+    }
+}
+
+/// A decision as returned by a callback agent; the harness supplies `agent_id` and `timestamp`.
+#[derive(Debug, Deserialize)]
+struct CallbackDecision {
+    decision_type: DecisionType,
+    confidence: f32,
+    reasoning: String,
+    #[serde(default)]
+    data_sources: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CallbackResponse {
+    List(Vec<CallbackDecision>),
+    Wrapped { decisions: Vec<CallbackDecision> },
+}
+
+/// Sends `{"backtest_id", "agent_id", "feed"}` and expects a JSON array of decisions,
+/// `{"decisions": [...]}` or an empty body.
+pub struct HttpCallbackAgent {
+    url: String,
+    backtest_id: String,
+    agent_id: String,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl BacktestAgent for HttpCallbackAgent {
+    fn describe(&self) -> String {
+        format!("http {}", self.url)
+    }
+
+    async fn on_feed(&mut self, feed: &DataFeed) -> Result<Vec<AgentDecision>, String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({
+                "backtest_id": self.backtest_id,
+                "agent_id": self.agent_id,
+                "feed": feed,
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("Agent callback failed: {}", e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Agent callback returned {}", status));
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read agent callback response: {}", e))?;
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Vec::new());
+        }
+
+        let decisions = match serde_json::from_slice(&body)
+            .map_err(|e| format!("Agent callback returned invalid decisions: {}", e))?
+        {
+            CallbackResponse::List(decisions) | CallbackResponse::Wrapped { decisions } => decisions,
+        };
+        Ok(decisions
+            .into_iter()
+            .map(|d| AgentDecision {
+                agent_id: self.agent_id.clone(),
+                decision_type: d.decision_type,
+                confidence: d.confidence,
+                reasoning: d.reasoning,
+                timestamp: feed.timestamp,
+                data_sources: d.data_sources,
+                provenance: None,
+                seq: None,
+            })
+            .collect())
+    }
+}
+
+/// Baseline strategy to compare other agents against.
+pub struct BuyAndHoldAgent {
+    amount: f64,
+    asset: Option<String>,
+    bought: HashSet<String>,
+}
+
+#[async_trait]
+impl BacktestAgent for BuyAndHoldAgent {
+    fn describe(&self) -> String {
+        match &self.asset {
+            Some(asset) => format!("buy_and_hold {} {}", self.amount, asset),
+            None => format!("buy_and_hold {}", self.amount),
+        }
+    }
+
+    async fn on_feed(&mut self, feed: &DataFeed) -> Result<Vec<AgentDecision>, String> {
+        let Some((asset, price)) = price_of(feed) else { return Ok(Vec::new()) };
+        if self.asset.as_ref().is_some_and(|wanted| *wanted != asset) || !self.bought.insert(asset.clone()) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![AgentDecision {
+            agent_id: String::new(),
+            decision_type: DecisionType::Trade {
+                action: "buy".to_string(),
+                amount: self.amount,
+                asset: asset.clone(),
+            },
+            confidence: 1.0,
+            reasoning: format!("Buy-and-hold entry for {} at {}", asset, price),
+            timestamp: feed.timestamp,
+            data_sources: vec![feed.feed_id.clone()],
+            provenance: None,
+            seq: None,
+        }])
+    }
+}
+
+/// What to replay and how to score it.
+#[derive(Debug, Clone)]
+pub struct BacktestSpec {
+    pub agent_id: String,
+    pub agent: AgentSpec,
+    /// `since`, `until`, source and data type filters; cursor, order and limit are ignored.
+    pub filter: FeedQuery,
+    /// Replay every stored version of a feed, not only its current one. Feeds updated in
+    /// place (such as a price per asset) only have a series through their versions.
+    pub include_history: bool,
+    /// Defaults to the paper trading configuration.
+    pub starting_cash: Option<f64>,
+    pub fee_bps: Option<f64>,
+    /// An alert is confirmed when a price it references moves by `alert_move_pct`
+    /// within `alert_horizon_secs` after it.
+    pub alert_horizon_secs: u64,
+    pub alert_move_pct: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BacktestState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestStatus {
+    pub backtest_id: String,
+    pub agent_id: String,
+    pub agent: String,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub state: BacktestState,
+    /// Known once the range has been loaded.
+    pub feeds_total: Option<u64>,
+    pub feeds_replayed: u64,
+    pub decisions_collected: u64,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<BacktestReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvalidDecision {
+    pub feed_id: String,
+    pub timestamp: u64,
+    pub decision_type: String,
+    pub violations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: u64,
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertScore {
+    pub total: u64,
+    /// Alerts referencing a Price feed whose horizon ended within the replay.
+    pub scored: u64,
+    pub confirmed: u64,
+    /// Alerts whose horizon runs past the replayed range without a qualifying move yet.
+    pub unresolved: u64,
+    /// `confirmed / scored`.
+    pub precision: Option<f64>,
+    pub horizon_secs: u64,
+    pub move_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub feeds_replayed: u64,
+    pub first_timestamp: Option<u64>,
+    pub last_timestamp: Option<u64>,
+    pub decisions_total: u64,
+    pub decisions_by_type: BTreeMap<String, u64>,
+    /// Decisions that failed validation or referenced a feed not replayed yet.
+    pub decisions_invalid: u64,
+    pub starting_cash: f64,
+    pub fee_bps: f64,
+    #[serde(flatten)]
+    pub valuation: Valuation,
+    pub trades_filled: u64,
+    pub trades_rejected: u64,
+    /// Sells, each closing (part of) a position.
+    pub closed_trades: u64,
+    pub winning_trades: u64,
+    /// `winning_trades / closed_trades`, where a sell wins if it realizes a gross profit.
+    pub hit_rate: Option<f64>,
+    /// Largest fall of equity from a previous peak, absolute and as a percentage of that peak.
+    pub max_drawdown: f64,
+    pub max_drawdown_pct: f64,
+    pub alerts: AlertScore,
+    pub equity_curve: Vec<EquityPoint>,
+    pub fills: Vec<Fill>,
+    pub rejections: Vec<RejectedTrade>,
+    pub invalid_decisions: Vec<InvalidDecision>,
+    pub duration_ms: u64,
+}
+
+struct BacktestJob {
+    status: RwLock<BacktestStatus>,
+    cancel: watch::Sender<bool>,
+}
+
+#[derive(Clone)]
+pub struct BacktestManager {
+    indexer: RealtimeIndexer,
+    paper_config: PaperTradingConfig,
+    client: reqwest::Client,
+    allow_private_urls: bool,
+    jobs: Arc<RwLock<HashMap<String, Arc<BacktestJob>>>>,
+}
+
+impl BacktestManager {
+    pub fn new(indexer: RealtimeIndexer, paper_config: PaperTradingConfig, allow_private_urls: bool) -> Self {
+        Self {
+            indexer,
+            paper_config,
+            client: reqwest::Client::builder()
+                .timeout(CALLBACK_TIMEOUT)
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap_or_default(),
+            allow_private_urls,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Validates `agent` and, for a callback agent, that its URL is not an internal address.
+    pub async fn check_agent(&self, agent: &AgentSpec) -> Result<(), String> {
+        agent.validate()?;
+        match agent {
+            AgentSpec::Http { url } => crate::webhooks::check_outbound_url(url, self.allow_private_urls)
+                .await
+                .map_err(|e| format!("agent {}", e)),
+            AgentSpec::BuyAndHold { .. } => Ok(()),
+        }
+    }
+
+    /// Starts a backtest in the background. Fails if too many are already running.
+    pub async fn start(&self, mut spec: BacktestSpec) -> Result<BacktestStatus, String> {
+        spec.starting_cash.get_or_insert(self.paper_config.starting_cash);
+        spec.fee_bps.get_or_insert(self.paper_config.fee_bps);
+        let mut jobs = self.jobs.write().await;
+
+        let mut finished = Vec::new();
+        let mut running = 0;
+        for (id, job) in jobs.iter() {
+            let status = job.status.read().await;
+            match status.state {
+                BacktestState::Running => running += 1,
+                _ => finished.push((status.started_at, id.clone())),
+            }
+        }
+        if running >= MAX_RUNNING_BACKTESTS {
+            return Err(format!("{} backtests are already running; wait for one to finish", running));
+        }
+        if finished.len() >= MAX_FINISHED_BACKTESTS {
+            finished.sort();
+            for (_, id) in &finished[..=finished.len() - MAX_FINISHED_BACKTESTS] {
+                jobs.remove(id);
+            }
+        }
+
+        let backtest_id = format!("bt_{}", hex::encode(rand::thread_rng().gen::<[u8; 8]>()));
+        let agent = spec.agent.build(&backtest_id, &spec.agent_id, self.client.clone());
+        let (cancel, cancel_rx) = watch::channel(false);
+        let job = Arc::new(BacktestJob {
+            status: RwLock::new(BacktestStatus {
+                backtest_id: backtest_id.clone(),
+                agent_id: spec.agent_id.clone(),
+                agent: agent.describe(),
+                since: spec.filter.since,
+                until: spec.filter.until,
+                state: BacktestState::Running,
+                feeds_total: None,
+                feeds_replayed: 0,
+                decisions_collected: 0,
+                started_at: unix_now(),
+                finished_at: None,
+                error: None,
+                report: None,
+            }),
+            cancel,
+        });
+        jobs.insert(backtest_id, job.clone());
+
+        let status = job.status.read().await.clone();
+        tokio::spawn(run_job(job, self.indexer.clone(), spec, agent, cancel_rx));
+        Ok(status)
+    }
+
+    /// The backtest's progress, with its report once completed.
+    pub async fn status(&self, backtest_id: &str) -> Option<BacktestStatus> {
+        let job = self.jobs.read().await.get(backtest_id).cloned()?;
+        let status = job.status.read().await.clone();
+        Some(status)
+    }
+
+    /// Every known backtest without its report, newest first.
+    pub async fn list(&self) -> Vec<BacktestStatus> {
+        let jobs: Vec<Arc<BacktestJob>> = self.jobs.read().await.values().cloned().collect();
+        let mut statuses = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            let mut status = job.status.read().await.clone();
+            status.report = None;
+            statuses.push(status);
+        }
+        statuses.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+        statuses
+    }
+
+    /// Requests cancellation; the backtest stops before its next feed.
+    pub async fn cancel(&self, backtest_id: &str) -> bool {
+        match self.jobs.read().await.get(backtest_id) {
+            Some(job) => {
+                let _ = job.cancel.send(true);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+async fn run_job(
+    job: Arc<BacktestJob>,
+    indexer: RealtimeIndexer,
+    spec: BacktestSpec,
+    mut agent: Box<dyn BacktestAgent>,
+    cancel: watch::Receiver<bool>,
+) {
+    let result = replay(&job, &indexer, &spec, agent.as_mut(), &cancel).await;
+
+    let mut status = job.status.write().await;
+    status.finished_at = Some(unix_now());
+    match result {
+        Ok(Some(report)) => {
+            status.state = BacktestState::Completed;
+            status.report = Some(report);
+        }
+        Ok(None) => status.state = BacktestState::Cancelled,
+        Err(e) => {
+            status.state = BacktestState::Failed;
+            status.error = Some(e);
+        }
+    }
+    tracing::info!(
+        "Backtest {} of {} finished: {:?} ({} feeds, {} decisions)",
+        status.backtest_id, status.agent_id, status.state, status.feeds_replayed, status.decisions_collected
+    );
+}
+
+/// Replays the range through the agent. Returns `None` if the backtest was cancelled.
+async fn replay(
+    job: &BacktestJob,
+    indexer: &RealtimeIndexer,
+    spec: &BacktestSpec,
+    agent: &mut dyn BacktestAgent,
+    cancel: &watch::Receiver<bool>,
+) -> Result<Option<BacktestReport>, String> {
+    let started = Instant::now();
+    let feeds = load_feeds(indexer, spec).await?;
+    job.status.write().await.feeds_total = Some(feeds.len() as u64);
+
+    let mut simulation = Simulation::new(spec);
+    for feed in &feeds {
+        if *cancel.borrow() {
+            return Ok(None);
+        }
+        let price_changed = simulation.observe(feed);
+        let decisions = agent
+            .on_feed(feed)
+            .await
+            .map_err(|e| format!("Feed {} at {}: {}", feed.feed_id, feed.timestamp, e))?;
+
+        let mut filled = false;
+        for mut decision in decisions {
+            decision.agent_id = spec.agent_id.clone();
+            decision.timestamp = feed.timestamp;
+            filled |= simulation.record(feed, decision);
+        }
+        if price_changed || filled {
+            simulation.sample_equity(feed.timestamp);
+        }
+
+        let mut status = job.status.write().await;
+        status.feeds_replayed += 1;
+        status.decisions_collected = simulation.decisions_total;
+    }
+
+    Ok(Some(simulation.finish(&feeds, started.elapsed())))
+}
+
+/// The stored feeds in range, oldest first. Ties in `timestamp` keep `feed_id` and then
+/// version order.
+async fn load_feeds(indexer: &RealtimeIndexer, spec: &BacktestSpec) -> Result<Vec<DataFeed>, String> {
+    let in_range = |feed: &DataFeed| {
+        spec.filter.since.is_none_or(|since| feed.timestamp >= since)
+            && spec.filter.until.is_none_or(|until| feed.timestamp <= until)
+    };
+    let mut query = FeedQuery {
+        // Feeds updated after `until` may still have versions inside the range
+        until: if spec.include_history { None } else { spec.filter.until },
+        cursor: None,
+        order: SortOrder::Asc,
+        limit: REPLAY_PAGE_SIZE,
+        ..spec.filter.clone()
+    };
+    let mut feeds = Vec::new();
+
+    loop {
+        let page = indexer.query_feeds(query.clone()).await?;
+        for feed in &page.feeds {
+            let versions = if spec.include_history {
+                let history = indexer.get_feed_history(&feed.feed_id).await?;
+                history.into_iter().map(|v| v.feed).filter(&in_range).collect()
+            } else {
+                vec![feed.clone()]
+            };
+            // Checked per version so one long history cannot run far past the cap
+            for version in versions {
+                feeds.push(version);
+                if feeds.len() > MAX_BACKTEST_FEEDS {
+                    return Err(format!(
+                        "The range holds more than {} feeds; narrow since/until or filter by source or data_type",
+                        MAX_BACKTEST_FEEDS
+                    ));
+                }
+            }
+        }
+        if page.next_cursor.is_none() {
+            break;
+        }
+        query.cursor = page.feeds.last().map(FeedCursor::after);
+    }
+
+    feeds.sort_by(|a, b| (a.timestamp, &a.feed_id).cmp(&(b.timestamp, &b.feed_id)));
+    Ok(feeds)
+}
+
+struct PendingAlert {
+    timestamp: u64,
+    assets: Vec<String>,
+}
+
+struct Simulation {
+    prices: PriceBook,
+    portfolio: Portfolio,
+    /// Replayed feed ids; decisions may only cite feeds the agent has already seen.
+    replayed: HashSet<String>,
+    feed_assets: HashMap<String, String>,
+    price_series: HashMap<String, Vec<(u64, f64)>>,
+    decisions_total: u64,
+    decisions_by_type: BTreeMap<String, u64>,
+    decisions_invalid: u64,
+    invalid_decisions: Vec<InvalidDecision>,
+    closed_trades: u64,
+    winning_trades: u64,
+    alerts: Vec<PendingAlert>,
+    alert_horizon_secs: u64,
+    alert_move_pct: f64,
+    peak_equity: f64,
+    max_drawdown: f64,
+    max_drawdown_pct: f64,
+    equity_curve: Vec<EquityPoint>,
+}
+
+impl Simulation {
+    fn new(spec: &BacktestSpec) -> Self {
+        let starting_cash = spec.starting_cash.unwrap_or_default();
+        Self {
+            prices: PriceBook::default(),
+            portfolio: Portfolio::new(starting_cash, spec.fee_bps.unwrap_or_default()),
+            replayed: HashSet::new(),
+            feed_assets: HashMap::new(),
+            price_series: HashMap::new(),
+            decisions_total: 0,
+            decisions_by_type: BTreeMap::new(),
+            decisions_invalid: 0,
+            invalid_decisions: Vec::new(),
+            closed_trades: 0,
+            winning_trades: 0,
+            alerts: Vec::new(),
+            alert_horizon_secs: spec.alert_horizon_secs,
+            alert_move_pct: spec.alert_move_pct,
+            peak_equity: starting_cash,
+            max_drawdown: 0.0,
+            max_drawdown_pct: 0.0,
+            equity_curve: Vec::new(),
+        }
+    }
+
+    /// Makes the feed visible to the agent. Returns whether it moved a price.
+    fn observe(&mut self, feed: &DataFeed) -> bool {
+        self.replayed.insert(feed.feed_id.clone());
+        if let Some((asset, price)) = price_of(feed) {
+            self.price_series.entry(asset.clone()).or_default().push((feed.timestamp, price));
+            self.feed_assets.insert(feed.feed_id.clone(), asset);
+        }
+        self.prices.update(feed)
+    }
+
+    /// Validates and applies one decision. Returns whether it produced a fill.
+    fn record(&mut self, feed: &DataFeed, decision: AgentDecision) -> bool {
+        let kind = decision.decision_type.kind();
+        self.decisions_total += 1;
+        *self.decisions_by_type.entry(kind.to_string()).or_insert(0) += 1;
+
+        let mut violations = decision.validate(feed.timestamp);
+        violations.extend(
+            decision
+                .data_sources
+                .iter()
+                .filter(|source| !self.replayed.contains(*source))
+                .map(|source| format!("data source {} has not been replayed yet", source)),
+        );
+        if !violations.is_empty() {
+            self.decisions_invalid += 1;
+            if self.invalid_decisions.len() < REPORT_HISTORY {
+                self.invalid_decisions.push(InvalidDecision {
+                    feed_id: feed.feed_id.clone(),
+                    timestamp: feed.timestamp,
+                    decision_type: kind.to_string(),
+                    violations,
+                });
+            }
+            return false;
+        }
+
+        match &decision.decision_type {
+            DecisionType::Trade { .. } => match self.portfolio.apply(&decision, &self.prices) {
+                Some(Ok(fill)) => {
+                    if fill.side == Side::Sell {
+                        self.closed_trades += 1;
+                        if fill.realized_pnl > 0.0 {
+                            self.winning_trades += 1;
+                        }
+                    }
+                    true
+                }
+                _ => false,
+            },
+            DecisionType::Alert { .. } => {
+                let mut assets: Vec<String> = decision
+                    .data_sources
+                    .iter()
+                    .filter_map(|source| self.feed_assets.get(source).cloned())
+                    .collect();
+                assets.sort();
+                assets.dedup();
+                self.alerts.push(PendingAlert {
+                    timestamp: decision.timestamp,
+                    assets,
+                });
+                false
+            }
+            DecisionType::Monitor { .. } => false,
+        }
+    }
+
+    fn sample_equity(&mut self, timestamp: u64) {
+        let equity = self.portfolio.valuation(&self.prices).equity;
+        self.peak_equity = self.peak_equity.max(equity);
+        let drawdown = self.peak_equity - equity;
+        if drawdown > self.max_drawdown {
+            self.max_drawdown = drawdown;
+        }
+        if self.peak_equity > 0.0 {
+            self.max_drawdown_pct = self.max_drawdown_pct.max(drawdown / self.peak_equity * 100.0);
+        }
+        self.equity_curve.push(EquityPoint { timestamp, equity });
+    }
+
+    fn score_alerts(&self, last_timestamp: u64) -> AlertScore {
+        let (mut scored, mut confirmed, mut unresolved) = (0, 0, 0);
+
+        for alert in &self.alerts {
+            if alert.assets.is_empty() {
+                continue;
+            }
+            let horizon_end = alert.timestamp.saturating_add(self.alert_horizon_secs);
+            let moved = alert.assets.iter().any(|asset| {
+                let series = &self.price_series[asset];
+                let Some(&(_, base)) = series.iter().rev().find(|(ts, _)| *ts <= alert.timestamp) else {
+                    return false;
+                };
+                series
+                    .iter()
+                    .filter(|(ts, _)| *ts > alert.timestamp && *ts <= horizon_end)
+                    .any(|(_, price)| ((price / base) - 1.0).abs() * 100.0 >= self.alert_move_pct)
+            });
+
+            if moved {
+                scored += 1;
+                confirmed += 1;
+            } else if horizon_end > last_timestamp {
+                unresolved += 1;
+            } else {
+                scored += 1;
+            }
+        }
+
+        AlertScore {
+            total: self.alerts.len() as u64,
+            scored,
+            confirmed,
+            unresolved,
+            precision: (scored > 0).then(|| confirmed as f64 / scored as f64),
+            horizon_secs: self.alert_horizon_secs,
+            move_pct: self.alert_move_pct,
+        }
+    }
+
+    fn finish(self, feeds: &[DataFeed], elapsed: Duration) -> BacktestReport {
+        let last_timestamp = feeds.last().map(|f| f.timestamp);
+        let alerts = self.score_alerts(last_timestamp.unwrap_or(0));
+
+        BacktestReport {
+            feeds_replayed: feeds.len() as u64,
+            first_timestamp: feeds.first().map(|f| f.timestamp),
+            last_timestamp,
+            decisions_total: self.decisions_total,
+            decisions_by_type: self.decisions_by_type,
+            decisions_invalid: self.decisions_invalid,
+            starting_cash: self.portfolio.starting_cash,
+            fee_bps: self.portfolio.fee_bps,
+            valuation: self.portfolio.valuation(&self.prices),
+            trades_filled: self.portfolio.trades_filled,
+            trades_rejected: self.portfolio.trades_rejected,
+            closed_trades: self.closed_trades,
+            winning_trades: self.winning_trades,
+            hit_rate: (self.closed_trades > 0).then(|| self.winning_trades as f64 / self.closed_trades as f64),
+            max_drawdown: self.max_drawdown,
+            max_drawdown_pct: self.max_drawdown_pct,
+            alerts,
+            equity_curve: downsample(self.equity_curve),
+            fills: self.portfolio.recent_fills(REPORT_HISTORY),
+            rejections: self.portfolio.recent_rejections(REPORT_HISTORY),
+            invalid_decisions: self.invalid_decisions,
+            duration_ms: elapsed.as_millis() as u64,
+        }
+    }
+}
+
+/// Evenly thins the curve to `MAX_EQUITY_POINTS`, keeping the last point.
+fn downsample(curve: Vec<EquityPoint>) -> Vec<EquityPoint> {
+    if curve.len() <= MAX_EQUITY_POINTS {
+        return curve;
+    }
+    let step = curve.len().div_ceil(MAX_EQUITY_POINTS);
+    let last = curve.len() - 1;
+    curve
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| i % step == 0 || *i == last)
+        .map(|(_, point)| point)
+        .collect()
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{DataType, FeedSource};
+    use serde_json::json;
+
+    fn spec(agent: AgentSpec) -> BacktestSpec {
+        BacktestSpec {
+            agent_id: "tester".to_string(),
+            agent,
+            filter: FeedQuery::default(),
+            include_history: false,
+            starting_cash: Some(1_000.0),
+            fee_bps: Some(0.0),
+            alert_horizon_secs: 100,
+            alert_move_pct: 5.0,
+        }
+    }
+
+    fn paper_config() -> PaperTradingConfig {
+        PaperTradingConfig { enabled: true, starting_cash: 1_000.0, fee_bps: 0.0 }
+    }
+
+    fn price_feed(feed_id: &str, asset: &str, price: f64, timestamp: u64) -> DataFeed {
+        DataFeed {
+            feed_id: feed_id.to_string(),
+            source: FeedSource::Oracle("backtest-tests".to_string()),
+            data_type: DataType::Price,
+            timestamp,
+            raw_data: json!({ "asset": asset, "price": price }),
+            cleaned_data: None,
+            seq: None,
+        }
+    }
+
+    fn decision(decision_type: DecisionType, timestamp: u64, sources: &[&str]) -> AgentDecision {
+        AgentDecision {
+            agent_id: "tester".to_string(),
+            decision_type,
+            confidence: 0.9,
+            reasoning: "backtest test".to_string(),
+            timestamp,
+            data_sources: sources.iter().map(|s| s.to_string()).collect(),
+            provenance: None,
+            seq: None,
+        }
+    }
+
+    fn trade(action: &str, amount: f64) -> DecisionType {
+        DecisionType::Trade {
+            action: action.to_string(),
+            amount,
+            asset: "ETH".to_string(),
+        }
+    }
+
+    fn alert() -> DecisionType {
+        DecisionType::Alert {
+            severity: "high".to_string(),
+            message: "ETH is moving".to_string(),
+            trigger: None,
+        }
+    }
+
+    /// Observes `feed`, records `decisions` against it and samples equity like `replay` does.
+    fn step(simulation: &mut Simulation, feed: &DataFeed, decisions: Vec<DecisionType>) {
+        let price_changed = simulation.observe(feed);
+        let mut filled = false;
+        for decision_type in decisions {
+            filled |= simulation.record(feed, decision(decision_type, feed.timestamp, &[&feed.feed_id]));
+        }
+        if price_changed || filled {
+            simulation.sample_equity(feed.timestamp);
+        }
+    }
+
+    #[test]
+    fn sells_count_towards_the_hit_rate() {
+        let mut simulation = Simulation::new(&spec(AgentSpec::BuyAndHold { amount: 1.0, asset: None }));
+        let feeds = [
+            price_feed("p1", "ETH", 100.0, 10),
+            price_feed("p2", "ETH", 120.0, 20),
+            price_feed("p3", "ETH", 90.0, 30),
+        ];
+        step(&mut simulation, &feeds[0], vec![trade("buy", 4.0)]);
+        step(&mut simulation, &feeds[1], vec![trade("sell", 2.0)]);
+        step(&mut simulation, &feeds[2], vec![trade("sell", 2.0)]);
+
+        let report = simulation.finish(&feeds, Duration::ZERO);
+        assert_eq!(report.trades_filled, 3);
+        assert_eq!((report.closed_trades, report.winning_trades), (2, 1));
+        assert_eq!(report.hit_rate, Some(0.5));
+    }
+
+    #[test]
+    fn drawdown_is_measured_from_the_peak_equity() {
+        let mut simulation = Simulation::new(&spec(AgentSpec::BuyAndHold { amount: 1.0, asset: None }));
+        let feeds = [
+            price_feed("p1", "ETH", 100.0, 10),
+            price_feed("p2", "ETH", 200.0, 20),
+            price_feed("p3", "ETH", 50.0, 30),
+            price_feed("p4", "ETH", 150.0, 40),
+        ];
+        step(&mut simulation, &feeds[0], vec![trade("buy", 10.0)]);
+        for feed in &feeds[1..] {
+            step(&mut simulation, feed, Vec::new());
+        }
+
+        let report = simulation.finish(&feeds, Duration::ZERO);
+        // Equity goes 1000, 2000, 500, 1500: the fall from 2000 to 500 is the largest
+        assert_eq!(report.max_drawdown, 1_500.0);
+        assert_eq!(report.max_drawdown_pct, 75.0);
+        assert_eq!(report.equity_curve.last().unwrap().equity, 1_500.0);
+    }
+
+    #[test]
+    fn alerts_are_scored_against_later_price_moves() {
+        let mut simulation = Simulation::new(&spec(AgentSpec::BuyAndHold { amount: 1.0, asset: None }));
+        let feeds = [
+            price_feed("p1", "ETH", 100.0, 10),
+            price_feed("p2", "ETH", 110.0, 50),
+            price_feed("p3", "ETH", 111.0, 200),
+            price_feed("p4", "ETH", 112.0, 250),
+        ];
+        // Confirmed: ETH moves 10% within the horizon
+        step(&mut simulation, &feeds[0], vec![alert()]);
+        // Scored but not confirmed: the horizon ends at 150 without a 5% move
+        step(&mut simulation, &feeds[1], vec![alert()]);
+        // Unresolved: the horizon runs past the last replayed feed
+        step(&mut simulation, &feeds[2], vec![alert()]);
+        step(&mut simulation, &feeds[3], Vec::new());
+
+        let alerts = simulation.finish(&feeds, Duration::ZERO).alerts;
+        assert_eq!((alerts.total, alerts.scored, alerts.confirmed, alerts.unresolved), (3, 2, 1, 1));
+        assert_eq!(alerts.precision, Some(0.5));
+    }
+
+    #[test]
+    fn decisions_citing_feeds_not_yet_replayed_are_invalid() {
+        let mut simulation = Simulation::new(&spec(AgentSpec::BuyAndHold { amount: 1.0, asset: None }));
+        let feed = price_feed("p1", "ETH", 100.0, 10);
+        simulation.observe(&feed);
+        let filled = simulation.record(&feed, decision(trade("buy", 1.0), 10, &["p1", "p2"]));
+        assert!(!filled);
+
+        let report = simulation.finish(&[feed], Duration::ZERO);
+        assert_eq!((report.decisions_total, report.decisions_invalid, report.trades_filled), (1, 1, 0));
+        assert_eq!(
+            report.invalid_decisions[0].violations,
+            vec!["data source p2 has not been replayed yet".to_string()]
+        );
+    }
+
+    #[test]
+    fn downsampling_keeps_the_last_point() {
+        let curve: Vec<EquityPoint> = (0..1_234)
+            .map(|i| EquityPoint { timestamp: i, equity: i as f64 })
+            .collect();
+        let thinned = downsample(curve);
+        assert!(thinned.len() <= MAX_EQUITY_POINTS + 1);
+        assert_eq!(thinned.first().unwrap().timestamp, 0);
+        assert_eq!(thinned.last().unwrap().timestamp, 1_233);
+
+        let short: Vec<EquityPoint> = (0..10).map(|i| EquityPoint { timestamp: i, equity: 0.0 }).collect();
+        assert_eq!(downsample(short).len(), 10);
+    }
+
+    #[tokio::test]
+    async fn buy_and_hold_backtest_completes_with_a_report() {
+        let indexer = RealtimeIndexer::new();
+        for (i, price) in [100.0, 150.0, 120.0].into_iter().enumerate() {
+            let feed = price_feed(&format!("eth-{}", i), "ETH", price, 100 + i as u64);
+            indexer.ingest_feed(feed).await.unwrap();
+        }
+        let manager = BacktestManager::new(indexer, paper_config(), false);
+        let status = manager.start(spec(AgentSpec::BuyAndHold { amount: 2.0, asset: None })).await.unwrap();
+
+        let report = loop {
+            let status = manager.status(&status.backtest_id).await.unwrap();
+            match status.state {
+                BacktestState::Running => tokio::time::sleep(Duration::from_millis(10)).await,
+                BacktestState::Completed => break status.report.unwrap(),
+                state => panic!("backtest ended {:?}: {:?}", state, status.error),
+            }
+        };
+        assert_eq!(report.feeds_replayed, 3);
+        assert_eq!(report.trades_filled, 1);
+        assert_eq!(report.valuation.equity, 1_040.0);
+    }
+
+    #[tokio::test]
+    async fn callback_agents_on_internal_addresses_are_refused() {
+        let agent = AgentSpec::Http { url: "http://127.0.0.1:9000/agent".to_string() };
+        let strict = BacktestManager::new(RealtimeIndexer::new(), paper_config(), false);
+        assert!(strict.check_agent(&agent).await.unwrap_err().contains("internal address"));
+
+        let local = BacktestManager::new(RealtimeIndexer::new(), paper_config(), true);
+        local.check_agent(&agent).await.unwrap();
+    }
+}
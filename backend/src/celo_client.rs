@@ -1,12 +1,16 @@
 // Celo Blockchain Client
 use ethers::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::telemetry::time_rpc;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CeloBlock {
     pub number: u64,
     pub hash: String,
+    pub parent_hash: String,
     pub timestamp: u64,
     pub transaction_count: usize,
     pub gas_used: String,
@@ -14,23 +18,53 @@ pub struct CeloBlock {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-// Remove unused transaction methods - will implement when needed
-#[allow(dead_code)]
 pub struct CeloTransaction {
     pub hash: String,
     pub from: String,
     pub to: Option<String>,
     pub value: String,
+    pub nonce: u64,
+    pub input: String,
     pub gas_price: String,
     pub gas_used: Option<String>,
+    pub effective_gas_price: Option<String>,
+    pub fee_currency: Option<String>, // Celo fee abstraction: ERC-20 used to pay gas, None for CELO
+    pub contract_address: Option<String>,
     pub block_number: u64,
     pub status: Option<u64>,
+    pub logs: Vec<CeloLog>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CeloLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub log_index: Option<u64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Erc20Token {
+    pub symbol: String,
+    pub address: String,
+    pub decimals: u32,
+}
+
+// Mento stable tokens on Celo mainnet
+const STABLE_TOKENS: [(&str, &str); 3] = [
+    ("cUSD", "0x765DE816845861e75A25fCA122bb6898B8B1282a"),
+    ("cEUR", "0xD8763CBa276a3738E6DE85b4b3bF5FDed6D6cA73"),
+    ("cREAL", "0xe8537a3d056DA446677B9E9d6c5dB704EaAb4787"),
+];
+
+// keccak256("balanceOf(address)")[..4]
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
 #[derive(Clone)]
 pub struct CeloClient {
     provider: Option<Arc<Provider<Http>>>,
     network: String,
+    tracked_tokens: Vec<Erc20Token>,
 }
 
 impl CeloClient {
@@ -57,20 +91,17 @@ impl CeloClient {
             }
         };
 
-        Self { provider, network }
+        Self {
+            provider,
+            network,
+            tracked_tokens: tracked_tokens_from_env(),
+        }
     }
 
     pub async fn get_latest_block(&self) -> Result<CeloBlock, String> {
         if let Some(provider) = &self.provider {
-            match provider.get_block(BlockNumber::Latest).await {
-                Ok(Some(block)) => Ok(CeloBlock {
-                    number: block.number.unwrap_or_default().as_u64(),
-                    hash: format!("{:?}", block.hash.unwrap_or_default()),
-                    timestamp: block.timestamp.as_u64(),
-                    transaction_count: block.transactions.len(),
-                    gas_used: block.gas_used.to_string(),
-                    miner: format!("{:?}", block.author.unwrap_or_default()),
-                }),
+            match time_rpc("eth_getBlockByNumber", provider.get_block(BlockNumber::Latest)).await {
+                Ok(Some(block)) => Ok(to_celo_block(&block)),
                 Ok(None) => Err("Block not found".to_string()),
                 Err(e) => Err(format!("Failed to fetch block: {}", e)),
             }
@@ -82,15 +113,8 @@ impl CeloClient {
 
     pub async fn get_block_by_number(&self, block_number: u64) -> Result<CeloBlock, String> {
         if let Some(provider) = &self.provider {
-            match provider.get_block(block_number).await {
-                Ok(Some(block)) => Ok(CeloBlock {
-                    number: block.number.unwrap_or_default().as_u64(),
-                    hash: format!("{:?}", block.hash.unwrap_or_default()),
-                    timestamp: block.timestamp.as_u64(),
-                    transaction_count: block.transactions.len(),
-                    gas_used: block.gas_used.to_string(),
-                    miner: format!("{:?}", block.author.unwrap_or_default()),
-                }),
+            match time_rpc("eth_getBlockByNumber", provider.get_block(block_number)).await {
+                Ok(Some(block)) => Ok(to_celo_block(&block)),
                 Ok(None) => Err("Block not found".to_string()),
                 Err(e) => Err(format!("Failed to fetch block: {}", e)),
             }
@@ -99,40 +123,71 @@ impl CeloClient {
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn get_transaction(&self, tx_hash: &str) -> Result<CeloTransaction, String> {
+    /// Fetches a block together with its full transactions. Statuses and gas used
+    /// come from `eth_getBlockReceipts`; if the node doesn't support it they are left empty.
+    pub async fn get_block_with_txs(&self, block_number: u64) -> Result<(CeloBlock, Vec<CeloTransaction>), String> {
+        if let Some(provider) = &self.provider {
+            let block = match time_rpc("eth_getBlockByNumber", provider.get_block_with_txs(block_number)).await {
+                Ok(Some(block)) => block,
+                Ok(None) => return Err("Block not found".to_string()),
+                Err(e) => return Err(format!("Failed to fetch block: {}", e)),
+            };
+
+            let receipts: HashMap<H256, TransactionReceipt> = if block.transactions.is_empty() {
+                HashMap::new()
+            } else {
+                time_rpc("eth_getBlockReceipts", provider.get_block_receipts(block_number))
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|r| (r.transaction_hash, r))
+                    .collect()
+            };
+
+            let transactions = block
+                .transactions
+                .iter()
+                .map(|tx| to_celo_transaction(tx, receipts.get(&tx.hash)))
+                .collect();
+
+            Ok((to_celo_block(&block), transactions))
+        } else {
+            let block = self.mock_block_with_number(block_number);
+            let transactions = (0..block.transaction_count)
+                .map(|i| {
+                    let mut tx = self.mock_transaction(&format!("0x{:064x}", block_number * 1000 + i as u64));
+                    tx.block_number = block_number;
+                    tx
+                })
+                .collect();
+            Ok((block, transactions))
+        }
+    }
+
+    /// Returns `Ok(None)` when the node doesn't know the transaction.
+    pub async fn get_transaction(&self, tx_hash: &str) -> Result<Option<CeloTransaction>, String> {
+        let hash: H256 = tx_hash.parse().map_err(|e| format!("Invalid hash: {}", e))?;
+
         if let Some(provider) = &self.provider {
-            let hash: H256 = tx_hash.parse().map_err(|e| format!("Invalid hash: {}", e))?;
-            
-            match provider.get_transaction(hash).await {
+            match time_rpc("eth_getTransactionByHash", provider.get_transaction(hash)).await {
                 Ok(Some(tx)) => {
-                    let receipt = provider.get_transaction_receipt(hash).await.ok().flatten();
+                    let receipt = time_rpc("eth_getTransactionReceipt", provider.get_transaction_receipt(hash)).await.ok().flatten();
                     
-                    Ok(CeloTransaction {
-                        hash: format!("{:?}", tx.hash),
-                        from: format!("{:?}", tx.from),
-                        to: tx.to.map(|addr| format!("{:?}", addr)),
-                        value: tx.value.to_string(),
-                        gas_price: tx.gas_price.unwrap_or_default().to_string(),
-                        gas_used: receipt.as_ref().map(|r| r.gas_used.unwrap_or_default().to_string()),
-                        block_number: tx.block_number.unwrap_or_default().as_u64(),
-                        status: receipt.as_ref().and_then(|r| r.status.map(|s| s.as_u64())),
-                    })
+                    Ok(Some(to_celo_transaction(&tx, receipt.as_ref())))
                 }
-                Ok(None) => Err("Transaction not found".to_string()),
+                Ok(None) => Ok(None),
                 Err(e) => Err(format!("Failed to fetch transaction: {}", e)),
             }
         } else {
-            Ok(self.mock_transaction(tx_hash))
+            Ok(Some(self.mock_transaction(tx_hash)))
         }
     }
 
-    #[allow(dead_code)]
     pub async fn get_balance(&self, address: &str) -> Result<String, String> {
+        let addr: Address = address.parse().map_err(|e| format!("Invalid address: {}", e))?;
+
         if let Some(provider) = &self.provider {
-            let addr: Address = address.parse().map_err(|e| format!("Invalid address: {}", e))?;
-            
-            match provider.get_balance(addr, None).await {
+            match time_rpc("eth_getBalance", provider.get_balance(addr, None)).await {
                 Ok(balance) => Ok(balance.to_string()),
                 Err(e) => Err(format!("Failed to fetch balance: {}", e)),
             }
@@ -141,6 +196,58 @@ impl CeloClient {
         }
     }
 
+    pub async fn get_nonce(&self, address: &str) -> Result<u64, String> {
+        let addr: Address = address.parse().map_err(|e| format!("Invalid address: {}", e))?;
+
+        if let Some(provider) = &self.provider {
+            match time_rpc("eth_getTransactionCount", provider.get_transaction_count(addr, None)).await {
+                Ok(nonce) => Ok(nonce.as_u64()),
+                Err(e) => Err(format!("Failed to fetch nonce: {}", e)),
+            }
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Deployed bytecode at `address`; empty for externally owned accounts.
+    pub async fn get_code(&self, address: &str) -> Result<Vec<u8>, String> {
+        let addr: Address = address.parse().map_err(|e| format!("Invalid address: {}", e))?;
+
+        if let Some(provider) = &self.provider {
+            match time_rpc("eth_getCode", provider.get_code(addr, None)).await {
+                Ok(code) => Ok(code.to_vec()),
+                Err(e) => Err(format!("Failed to fetch code: {}", e)),
+            }
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// ERC-20 `balanceOf(holder)` on `token`, in the token's smallest unit.
+    pub async fn get_token_balance(&self, token: &str, holder: &str) -> Result<String, String> {
+        let token: Address = token.parse().map_err(|e| format!("Invalid token address: {}", e))?;
+        let holder: Address = holder.parse().map_err(|e| format!("Invalid address: {}", e))?;
+
+        if let Some(provider) = &self.provider {
+            let mut data = BALANCE_OF_SELECTOR.to_vec();
+            data.extend_from_slice(H256::from(holder).as_bytes());
+            let call: ethers::types::transaction::eip2718::TypedTransaction = TransactionRequest::new().to(token).data(data).into();
+
+            match time_rpc("eth_call", provider.call(&call, None)).await {
+                Ok(result) if result.len() >= 32 => Ok(U256::from_big_endian(&result[..32]).to_string()),
+                Ok(_) => Err("Token call returned no data".to_string()),
+                Err(e) => Err(format!("Failed to fetch token balance: {}", e)),
+            }
+        } else {
+            Ok("0".to_string())
+        }
+    }
+
+    /// Stable tokens plus any extra tokens configured through `CELO_TRACKED_TOKENS`.
+    pub fn tracked_tokens(&self) -> &[Erc20Token] {
+        &self.tracked_tokens
+    }
+
     pub fn is_connected(&self) -> bool {
         self.provider.is_some()
     }
@@ -159,6 +266,7 @@ impl CeloClient {
         CeloBlock {
             number: 20000000,
             hash: "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
+            parent_hash: "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
             timestamp: now,
             transaction_count: 42,
             gas_used: "8500000".to_string(),
@@ -172,17 +280,104 @@ impl CeloClient {
         block
     }
 
-    #[allow(dead_code)]
     fn mock_transaction(&self, hash: &str) -> CeloTransaction {
         CeloTransaction {
             hash: hash.to_string(),
             from: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
             to: Some("0xabcdef1234567890abcdef1234567890abcdef12".to_string()),
             value: "1000000000000000000".to_string(),
+            nonce: 0,
+            input: "0x".to_string(),
             gas_price: "20000000000".to_string(),
             gas_used: Some("21000".to_string()),
+            effective_gas_price: Some("20000000000".to_string()),
+            fee_currency: None,
+            contract_address: None,
             block_number: 20000000,
             status: Some(1),
+            logs: Vec::new(),
         }
     }
 }
+
+fn to_celo_block<T>(block: &Block<T>) -> CeloBlock {
+    CeloBlock {
+        number: block.number.unwrap_or_default().as_u64(),
+        hash: format!("{:?}", block.hash.unwrap_or_default()),
+        parent_hash: format!("{:?}", block.parent_hash),
+        timestamp: block.timestamp.as_u64(),
+        transaction_count: block.transactions.len(),
+        gas_used: block.gas_used.to_string(),
+        miner: format!("{:?}", block.author.unwrap_or_default()),
+    }
+}
+
+fn to_celo_transaction(tx: &Transaction, receipt: Option<&TransactionReceipt>) -> CeloTransaction {
+    CeloTransaction {
+        hash: format!("{:?}", tx.hash),
+        from: format!("{:?}", tx.from),
+        to: tx.to.map(|addr| format!("{:?}", addr)),
+        value: tx.value.to_string(),
+        nonce: tx.nonce.as_u64(),
+        input: format!("{}", tx.input),
+        gas_price: tx.gas_price.unwrap_or_default().to_string(),
+        gas_used: receipt.map(|r| r.gas_used.unwrap_or_default().to_string()),
+        effective_gas_price: receipt.and_then(|r| r.effective_gas_price.map(|p| p.to_string())),
+        fee_currency: tx
+            .other
+            .get("feeCurrency")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        contract_address: receipt.and_then(|r| r.contract_address.map(|a| format!("{:?}", a))),
+        block_number: tx.block_number.unwrap_or_default().as_u64(),
+        status: receipt.and_then(|r| r.status.map(|s| s.as_u64())),
+        logs: receipt
+            .map(|r| {
+                r.logs
+                    .iter()
+                    .map(|log| CeloLog {
+                        address: format!("{:?}", log.address),
+                        topics: log.topics.iter().map(|t| format!("{:?}", t)).collect(),
+                        data: format!("{}", log.data),
+                        log_index: log.log_index.map(|i| i.as_u64()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Parses `CELO_TRACKED_TOKENS` (`SYMBOL:ADDRESS[:DECIMALS]`, comma separated) on top
+/// of the built-in stable tokens. Malformed entries are skipped with a warning.
+fn tracked_tokens_from_env() -> Vec<Erc20Token> {
+    let mut tokens: Vec<Erc20Token> = STABLE_TOKENS
+        .iter()
+        .map(|(symbol, address)| Erc20Token {
+            symbol: symbol.to_string(),
+            address: address.to_string(),
+            decimals: 18,
+        })
+        .collect();
+
+    if let Ok(extra) = std::env::var("CELO_TRACKED_TOKENS") {
+        for entry in extra.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parts: Vec<&str> = entry.split(':').collect();
+            let decimals = match parts.get(2) {
+                Some(d) => d.parse::<u32>().ok(),
+                None => Some(18),
+            };
+            match (parts.as_slice(), decimals) {
+                ([symbol, address, ..], Some(decimals)) if address.parse::<Address>().is_ok() => {
+                    tokens.push(Erc20Token {
+                        symbol: symbol.to_string(),
+                        address: address.to_string(),
+                        decimals,
+                    });
+                }
+                _ => tracing::warn!("Ignoring malformed CELO_TRACKED_TOKENS entry: {}", entry),
+            }
+        }
+    }
+
+    tokens
+}
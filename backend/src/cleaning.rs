@@ -0,0 +1,634 @@
+// Data-cleaning pipeline applied to every DataFeed before it is stored
+use ethers::types::{Address, H256, U256};
+use ethers::utils::{format_units, to_checksum};
+use jsonschema::JSONSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::indexer::{DataFeed, DataType};
+
+/// Feeds stamped further than this into the future are rejected.
+pub const MAX_FUTURE_SKEW_SECS: u64 = 300;
+
+/// A JSON Schema failure at `pointer` within the feed's `raw_data`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaViolation {
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Why a stage rejected a feed. Plain messages convert from `String`.
+#[derive(Debug, Clone, Default)]
+pub struct Rejection {
+    pub message: String,
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl From<String> for Rejection {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            violations: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleaningError {
+    pub feed_id: String,
+    pub stage: String,
+    pub message: String,
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl fmt::Display for CleaningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Feed {} rejected by {}: {}", self.feed_id, self.stage, self.message)
+    }
+}
+
+/// One step of the pipeline. Stages see the feed and the cleaned payload built
+/// so far (initially a copy of `raw_data`), and may rewrite either or reject the feed.
+pub trait FeedCleaner: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn clean(&self, feed: &mut DataFeed, cleaned: &mut Value) -> Result<(), Rejection>;
+
+    /// Stages that learn from the feeds they see. They run after every stateless stage and
+    /// after the indexer's duplicate check, so must not change the feed's timestamp.
+    fn stateful(&self) -> bool {
+        false
+    }
+}
+
+/// Ordered cleaning stages: global stages run first, then the stages registered for the
+/// feed's `DataType`, then the JSON Schema registered for its `Custom` type name, if any.
+/// Stateful stages keep that order but run as a second phase (`finish`).
+#[derive(Default, Clone)]
+pub struct CleaningPipeline {
+    global: Vec<Arc<dyn FeedCleaner>>,
+    by_type: HashMap<String, Vec<Arc<dyn FeedCleaner>>>,
+    schemas: HashMap<String, Arc<JsonSchemaValidator>>,
+}
+
+impl CleaningPipeline {
+    /// Timestamp normalization for every feed, plus schema validation, address
+    /// checksumming and unit normalization for blocks/transactions and outlier rejection for prices.
+    pub fn standard() -> Self {
+        let mut pipeline = Self::default();
+        pipeline.register_global(Arc::new(TimestampNormalizer));
+
+        pipeline.register(&DataType::Transaction, Arc::new(SchemaValidator::transaction()));
+        pipeline.register(&DataType::Transaction, Arc::new(AddressChecksummer::new(&["from", "to", "contract_address"])));
+        pipeline.register(&DataType::Transaction, Arc::new(WeiToCelo::new(&["value"])));
+
+        pipeline.register(&DataType::Block, Arc::new(SchemaValidator::block()));
+        pipeline.register(&DataType::Block, Arc::new(AddressChecksummer::new(&["miner"])));
+
+        pipeline.register(&DataType::Price, Arc::new(SchemaValidator::price()));
+        pipeline.register(&DataType::Price, Arc::new(PriceOutlierFilter::new(20, 5, 0.5)));
+        pipeline
+    }
+
+    pub fn register_global(&mut self, stage: Arc<dyn FeedCleaner>) {
+        self.global.push(stage);
+    }
+
+    /// Appends a stage to the chain for `data_type`.
+    pub fn register(&mut self, data_type: &DataType, stage: Arc<dyn FeedCleaner>) {
+        self.by_type.entry(data_type.name()).or_default().push(stage);
+    }
+
+    /// Registers (or replaces) the JSON Schema for `Custom(type_name)` feeds.
+    /// Returns `true` if a schema was replaced.
+    pub fn set_schema(&mut self, type_name: &str, validator: JsonSchemaValidator) -> bool {
+        self.schemas.insert(type_name.to_string(), Arc::new(validator)).is_some()
+    }
+
+    pub fn remove_schema(&mut self, type_name: &str) -> bool {
+        self.schemas.remove(type_name).is_some()
+    }
+
+    pub fn schema(&self, type_name: &str) -> Option<Value> {
+        self.schemas.get(type_name).map(|v| v.schema.clone())
+    }
+
+    pub fn schemas(&self) -> BTreeMap<String, Value> {
+        self.schemas
+            .iter()
+            .map(|(name, v)| (name.clone(), v.schema.clone()))
+            .collect()
+    }
+
+    /// Runs the stateless stages. `cleaned_data` holds the payload so far, and the fields
+    /// `DataFeed::content_hash` covers are final.
+    pub fn prepare(&self, feed: DataFeed) -> Result<DataFeed, CleaningError> {
+        let cleaned = feed.raw_data.clone();
+        self.run_stages(feed, cleaned, false)
+    }
+
+    /// Runs the stateful stages on a feed returned by `prepare` and stores the result in `cleaned_data`.
+    pub fn finish(&self, mut feed: DataFeed) -> Result<DataFeed, CleaningError> {
+        let cleaned = feed.cleaned_data.take().unwrap_or_else(|| feed.raw_data.clone());
+        self.run_stages(feed, cleaned, true)
+    }
+
+    pub fn has_stateful_stages(&self, feed: &DataFeed) -> bool {
+        self.stages(feed).iter().any(|stage| stage.stateful())
+    }
+
+    fn stages(&self, feed: &DataFeed) -> Vec<Arc<dyn FeedCleaner>> {
+        let typed = self.by_type.get(&feed.data_type.name()).into_iter().flatten().cloned();
+        let schema = match &feed.data_type {
+            DataType::Custom(name) => self.schemas.get(name).map(|v| v.clone() as Arc<dyn FeedCleaner>),
+            _ => None,
+        };
+        self.global.iter().cloned().chain(typed).chain(schema).collect()
+    }
+
+    fn run_stages(&self, mut feed: DataFeed, mut cleaned: Value, stateful: bool) -> Result<DataFeed, CleaningError> {
+        for stage in self.stages(&feed).iter().filter(|stage| stage.stateful() == stateful) {
+            if let Err(rejection) = stage.clean(&mut feed, &mut cleaned) {
+                return Err(CleaningError {
+                    feed_id: feed.feed_id,
+                    stage: stage.name().to_string(),
+                    message: rejection.message,
+                    violations: rejection.violations,
+                });
+            }
+        }
+
+        feed.cleaned_data = Some(cleaned);
+        Ok(feed)
+    }
+}
+
+// ============ Stages ============
+
+/// Converts millisecond/microsecond timestamps to seconds and rejects missing or future ones.
+pub struct TimestampNormalizer;
+
+impl FeedCleaner for TimestampNormalizer {
+    fn name(&self) -> &str {
+        "timestamp_normalization"
+    }
+
+    fn clean(&self, feed: &mut DataFeed, _cleaned: &mut Value) -> Result<(), Rejection> {
+        let seconds = match feed.timestamp {
+            0 => return Err("timestamp is missing (0)".to_string().into()),
+            ts if ts >= 1_000_000_000_000_000 => ts / 1_000_000,
+            ts if ts >= 1_000_000_000_000 => ts / 1_000,
+            ts => ts,
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if seconds > now + MAX_FUTURE_SKEW_SECS {
+            return Err(format!("timestamp {} is in the future (now {})", seconds, now).into());
+        }
+
+        feed.timestamp = seconds;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FieldKind {
+    Address,
+    Hash,
+    UnsignedInteger, // number, decimal string or 0x-hex string
+    PositiveNumber,
+    String,
+}
+
+impl FieldKind {
+    fn check(&self, value: &Value) -> bool {
+        match (self, value) {
+            (FieldKind::Address, Value::String(s)) => s.parse::<Address>().is_ok(),
+            (FieldKind::Hash, Value::String(s)) => s.parse::<H256>().is_ok(),
+            (FieldKind::UnsignedInteger, Value::Number(n)) => n.is_u64(),
+            (FieldKind::UnsignedInteger, Value::String(_)) => parse_uint(value).is_some(),
+            (FieldKind::PositiveNumber, _) => parse_number(value).is_some_and(|n| n.is_finite() && n > 0.0),
+            (FieldKind::String, Value::String(s)) => !s.is_empty(),
+            _ => false,
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            FieldKind::Address => "a 20-byte hex address",
+            FieldKind::Hash => "a 32-byte hex hash",
+            FieldKind::UnsignedInteger => "an unsigned integer",
+            FieldKind::PositiveNumber => "a positive number",
+            FieldKind::String => "a non-empty string",
+        }
+    }
+}
+
+/// Requires `raw_data` to be an object with the given fields. Optional fields may be absent or null.
+pub struct SchemaValidator {
+    required: Vec<(&'static str, FieldKind)>,
+    optional: Vec<(&'static str, FieldKind)>,
+}
+
+impl SchemaValidator {
+    pub fn new(required: Vec<(&'static str, FieldKind)>, optional: Vec<(&'static str, FieldKind)>) -> Self {
+        Self { required, optional }
+    }
+
+    /// Every field is optional: clients have always been able to ingest transactions without a
+    /// `value`, and `WeiToCelo` only converts it when present.
+    pub fn transaction() -> Self {
+        Self::new(
+            Vec::new(),
+            vec![
+                ("value", FieldKind::UnsignedInteger),
+                ("hash", FieldKind::Hash),
+                ("tx_hash", FieldKind::Hash),
+                ("from", FieldKind::Address),
+                ("to", FieldKind::Address),
+                ("contract_address", FieldKind::Address),
+                ("block_number", FieldKind::UnsignedInteger),
+                ("gas_price", FieldKind::UnsignedInteger),
+            ],
+        )
+    }
+
+    pub fn block() -> Self {
+        Self::new(
+            vec![("number", FieldKind::UnsignedInteger)],
+            vec![
+                ("hash", FieldKind::Hash),
+                ("parent_hash", FieldKind::Hash),
+                ("miner", FieldKind::Address),
+            ],
+        )
+    }
+
+    pub fn price() -> Self {
+        Self::new(
+            vec![("price", FieldKind::PositiveNumber)],
+            vec![("asset", FieldKind::String), ("symbol", FieldKind::String)],
+        )
+    }
+}
+
+impl FeedCleaner for SchemaValidator {
+    fn name(&self) -> &str {
+        "schema_validation"
+    }
+
+    fn clean(&self, _feed: &mut DataFeed, cleaned: &mut Value) -> Result<(), Rejection> {
+        let object = cleaned
+            .as_object()
+            .ok_or_else(|| format!("raw_data must be a JSON object, got {}", json_type(cleaned)))?;
+
+        for (field, kind) in &self.required {
+            match object.get(*field) {
+                None | Some(Value::Null) => return Err(format!("missing required field `{}`", field).into()),
+                Some(value) if !kind.check(value) => {
+                    return Err(format!("field `{}` must be {}, got {}", field, kind.describe(), value).into())
+                }
+                Some(_) => {}
+            }
+        }
+        for (field, kind) in &self.optional {
+            match object.get(*field) {
+                Some(value) if !value.is_null() && !kind.check(value) => {
+                    return Err(format!("field `{}` must be {}, got {}", field, kind.describe(), value).into())
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites address fields to their EIP-55 checksummed form.
+pub struct AddressChecksummer {
+    fields: Vec<&'static str>,
+}
+
+impl AddressChecksummer {
+    pub fn new(fields: &[&'static str]) -> Self {
+        Self { fields: fields.to_vec() }
+    }
+}
+
+impl FeedCleaner for AddressChecksummer {
+    fn name(&self) -> &str {
+        "address_checksum"
+    }
+
+    fn clean(&self, _feed: &mut DataFeed, cleaned: &mut Value) -> Result<(), Rejection> {
+        for field in &self.fields {
+            let Some(Value::String(raw)) = cleaned.get(*field) else { continue };
+            let address: Address = raw
+                .parse()
+                .map_err(|_| format!("field `{}` is not a valid address: {}", field, raw))?;
+            cleaned[*field] = Value::String(to_checksum(&address, None));
+        }
+        Ok(())
+    }
+}
+
+/// Adds a `<field>_celo` decimal string next to each wei amount.
+pub struct WeiToCelo {
+    fields: Vec<&'static str>,
+}
+
+impl WeiToCelo {
+    pub fn new(fields: &[&'static str]) -> Self {
+        Self { fields: fields.to_vec() }
+    }
+}
+
+impl FeedCleaner for WeiToCelo {
+    fn name(&self) -> &str {
+        "unit_normalization"
+    }
+
+    fn clean(&self, _feed: &mut DataFeed, cleaned: &mut Value) -> Result<(), Rejection> {
+        for field in &self.fields {
+            let Some(value) = cleaned.get(*field).filter(|v| !v.is_null()) else { continue };
+            let wei = parse_uint(value).ok_or_else(|| format!("field `{}` is not a wei amount: {}", field, value))?;
+            let celo = format_units(wei, 18).map_err(|e| format!("field `{}`: {}", field, e))?;
+            cleaned[format!("{}_celo", field)] = Value::String(celo);
+        }
+        Ok(())
+    }
+}
+
+/// Rejects prices that deviate from the median of recent accepted prices of the
+/// same series (source + asset) by more than `max_deviation` (0.5 = 50%).
+///
+/// A real move is indistinguishable from an outlier at first, so once `min_samples`
+/// consecutive prices have been rejected and agree with each other, the series is taken
+/// to have moved: they replace the window and the price completing the run is accepted.
+pub struct PriceOutlierFilter {
+    window: usize,
+    min_samples: usize,
+    max_deviation: f64,
+    history: Mutex<HashMap<String, PriceSeries>>,
+}
+
+#[derive(Default)]
+struct PriceSeries {
+    accepted: VecDeque<f64>,
+    /// Consecutive rejected prices, reset by the next accepted one.
+    rejected: VecDeque<f64>,
+}
+
+impl PriceOutlierFilter {
+    pub fn new(window: usize, min_samples: usize, max_deviation: f64) -> Self {
+        Self {
+            window: window.max(1),
+            min_samples: min_samples.max(1),
+            max_deviation,
+            history: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn median(prices: &VecDeque<f64>) -> f64 {
+        let mut sorted: Vec<f64> = prices.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        sorted[sorted.len() / 2]
+    }
+}
+
+impl FeedCleaner for PriceOutlierFilter {
+    fn name(&self) -> &str {
+        "price_outlier_rejection"
+    }
+
+    fn clean(&self, feed: &mut DataFeed, cleaned: &mut Value) -> Result<(), Rejection> {
+        let price = cleaned
+            .get("price")
+            .and_then(parse_number)
+            .ok_or_else(|| "missing numeric `price`".to_string())?;
+        let asset = cleaned
+            .get("asset")
+            .or_else(|| cleaned.get("symbol"))
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let series = format!("{}:{}:{}", feed.source.kind(), feed.source.value(), asset);
+
+        let mut history = self.history.lock().unwrap();
+        let series = history.entry(series).or_default();
+
+        if series.accepted.len() >= self.min_samples {
+            let median = Self::median(&series.accepted);
+            let deviation = (price - median).abs() / median;
+            if deviation > self.max_deviation {
+                series.rejected.push_back(price);
+                while series.rejected.len() > self.min_samples {
+                    series.rejected.pop_front();
+                }
+                let run_median = Self::median(&series.rejected);
+                let moved = series.rejected.len() == self.min_samples
+                    && series
+                        .rejected
+                        .iter()
+                        .all(|p| (p - run_median).abs() / run_median <= self.max_deviation);
+                if !moved {
+                    return Err(format!(
+                        "price {} deviates {:.1}% from the recent median {} (limit {:.1}%)",
+                        price,
+                        deviation * 100.0,
+                        median,
+                        self.max_deviation * 100.0
+                    )
+                    .into());
+                }
+
+                tracing::info!("Price series {}:{} moved; resetting its outlier window", feed.source.value(), asset);
+                series.accepted = std::mem::take(&mut series.rejected);
+                return Ok(());
+            }
+        }
+
+        series.rejected.clear();
+        series.accepted.push_back(price);
+        while series.accepted.len() > self.window {
+            series.accepted.pop_front();
+        }
+        Ok(())
+    }
+
+    fn stateful(&self) -> bool {
+        true
+    }
+}
+
+/// Validates `raw_data` of a `Custom` feed against a registered JSON Schema.
+pub struct JsonSchemaValidator {
+    schema: Value,
+    compiled: JSONSchema,
+}
+
+impl JsonSchemaValidator {
+    pub fn compile(schema: Value) -> Result<Self, String> {
+        let compiled = JSONSchema::compile(&schema).map_err(|e| format!("Invalid JSON Schema: {}", e))?;
+        Ok(Self { schema, compiled })
+    }
+}
+
+impl FeedCleaner for JsonSchemaValidator {
+    fn name(&self) -> &str {
+        "json_schema"
+    }
+
+    fn clean(&self, feed: &mut DataFeed, _cleaned: &mut Value) -> Result<(), Rejection> {
+        let Err(errors) = self.compiled.validate(&feed.raw_data) else {
+            return Ok(());
+        };
+
+        let violations: Vec<SchemaViolation> = errors
+            .map(|e| SchemaViolation {
+                pointer: e.instance_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect();
+        Err(Rejection {
+            message: format!("raw_data does not match the registered schema ({} violation(s))", violations.len()),
+            violations,
+        })
+    }
+}
+
+fn parse_uint(value: &Value) -> Option<U256> {
+    match value {
+        Value::Number(n) => n.as_u64().map(U256::from),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).ok(),
+            None => U256::from_dec_str(s).ok(),
+        },
+        _ => None,
+    }
+}
+
+/// A JSON number or a decimal string.
+pub fn parse_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::FeedSource;
+    use serde_json::json;
+
+    fn price(value: f64) -> DataFeed {
+        DataFeed {
+            feed_id: "price:celo".to_string(),
+            source: FeedSource::Oracle("test".to_string()),
+            data_type: DataType::Price,
+            timestamp: 1_700_000_000,
+            raw_data: json!({ "asset": "CELO", "price": value }),
+            cleaned_data: None,
+            seq: None,
+        }
+    }
+
+    fn transaction(raw_data: Value) -> DataFeed {
+        DataFeed {
+            feed_id: "tx:1".to_string(),
+            source: FeedSource::OnChain("0x0000000000000000000000000000000000000001".to_string()),
+            data_type: DataType::Transaction,
+            timestamp: 1_700_000_000,
+            raw_data,
+            cleaned_data: None,
+            seq: None,
+        }
+    }
+
+    #[test]
+    fn transaction_value_is_optional() {
+        let pipeline = CleaningPipeline::standard();
+        let cleaned = |raw_data| pipeline.prepare(transaction(raw_data)).map(|feed| feed.cleaned_data.unwrap());
+
+        let without_value = cleaned(json!({ "from": "0x0000000000000000000000000000000000000001" })).unwrap();
+        assert!(without_value.get("value_celo").is_none());
+        let with_value = cleaned(json!({ "value": "1500000000000000000" })).unwrap();
+        assert_eq!(with_value["value_celo"], json!("1.500000000000000000"));
+        assert_eq!(cleaned(json!({ "value": "-1" })).unwrap_err().stage, "schema_validation");
+    }
+
+    fn accepts(filter: &PriceOutlierFilter, value: f64) -> bool {
+        let mut feed = price(value);
+        let mut cleaned = feed.raw_data.clone();
+        filter.clean(&mut feed, &mut cleaned).is_ok()
+    }
+
+    #[test]
+    fn outlier_filter_rejects_spikes() {
+        let filter = PriceOutlierFilter::new(20, 3, 0.5);
+        for value in [1.0, 1.1, 0.9, 1.0] {
+            assert!(accepts(&filter, value));
+        }
+        assert!(!accepts(&filter, 10.0));
+        assert!(accepts(&filter, 1.05));
+        // A lone spike doesn't start a run
+        assert!(!accepts(&filter, 10.0));
+        assert!(!accepts(&filter, 0.1));
+    }
+
+    #[test]
+    fn outlier_filter_follows_a_sustained_move() {
+        let filter = PriceOutlierFilter::new(20, 3, 0.5);
+        for value in [1.0, 1.1, 0.9, 1.0] {
+            assert!(accepts(&filter, value));
+        }
+        assert!(!accepts(&filter, 3.0));
+        assert!(!accepts(&filter, 3.1));
+        assert!(accepts(&filter, 2.9));
+        assert!(accepts(&filter, 3.05));
+        assert!(!accepts(&filter, 1.0));
+    }
+
+    #[test]
+    fn outlier_filter_ignores_inconsistent_rejections() {
+        let filter = PriceOutlierFilter::new(20, 3, 0.5);
+        for value in [1.0, 1.1, 0.9] {
+            assert!(accepts(&filter, value));
+        }
+        for value in [10.0, 0.1, 30.0, 0.2] {
+            assert!(!accepts(&filter, value));
+        }
+        assert!(accepts(&filter, 1.0));
+    }
+
+    #[test]
+    fn stateful_stages_run_in_finish() {
+        let pipeline = CleaningPipeline::standard();
+        let feed = pipeline.prepare(price(1.0)).unwrap();
+        assert!(pipeline.has_stateful_stages(&feed));
+        for _ in 0..10 {
+            pipeline.prepare(price(50.0)).unwrap();
+        }
+        for value in [1.0, 1.0, 1.0, 1.0, 1.0] {
+            pipeline.finish(pipeline.prepare(price(value)).unwrap()).unwrap();
+        }
+        assert!(pipeline.finish(pipeline.prepare(price(50.0)).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,590 @@
+// Postgres persistence for the Real-Time Indexer (enabled by the `database` feature)
+use async_trait::async_trait;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions, PgRow, Postgres};
+use sqlx::types::Json;
+use sqlx::{QueryBuilder, Row};
+
+use crate::celo_client::{CeloBlock, CeloTransaction};
+use crate::indexer::{AgentDecision, DataFeed, DataType};
+use crate::storage::{DecisionGroup, DecisionQuery, DecisionStore, FeedQuery, FeedStore, FeedVersion, PutOutcome, SchemaStore, SortOrder, WriteStatus};
+
+#[derive(Clone)]
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    /// Connects to `database_url` and applies pending migrations.
+    pub async fn connect(database_url: &str) -> Result<Self, String> {
+        let options = database_url
+            .parse::<PgConnectOptions>()
+            .map_err(|e| format!("Invalid DATABASE_URL: {}", e))?;
+        Self::connect_with(options).await
+    }
+
+    pub async fn connect_with(options: PgConnectOptions) -> Result<Self, String> {
+        let pool = PgPoolOptions::new()
+            .max_connections(10)
+            .connect_with(options)
+            .await
+            .map_err(|e| format!("Failed to connect to Postgres: {}", e))?;
+
+        sqlx::migrate!("./migrations/postgres")
+            .run(&pool)
+            .await
+            .map_err(|e| format!("Failed to run migrations: {}", e))?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl FeedStore for PgStore {
+    /// Feeds carrying a `CeloBlock`/`CeloTransaction` payload are also
+    /// written to `indexed_blocks`/`indexed_transactions`.
+    async fn put(&self, mut feed: DataFeed, content_hash: &str) -> Result<PutOutcome, String> {
+        let mut tx = self.pool.begin().await.map_err(|e| format!("Database error: {}", e))?;
+
+        // Serializes concurrent writers of the same feed id (e.g. the follower and a backfill)
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(&feed.feed_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let current: Option<(String, i32, i64)> =
+            sqlx::query_as("SELECT content_hash, version, seq FROM data_feeds WHERE feed_id = $1")
+                .bind(&feed.feed_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to save feed: {}", e))?;
+
+        let (status, version) = match current {
+            Some((hash, version, seq)) if hash == content_hash => {
+                return Ok(PutOutcome {
+                    status: WriteStatus::Duplicate,
+                    version: version as u32,
+                    seq: seq as u64,
+                });
+            }
+            Some((_, version, _)) => {
+                sqlx::query(
+                    "INSERT INTO data_feed_versions (feed_id, version, content_hash, feed, ingested_at)
+                     SELECT feed_id, version, content_hash, feed, ingested_at FROM data_feeds WHERE feed_id = $1",
+                )
+                .bind(&feed.feed_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to archive feed version: {}", e))?;
+
+                (WriteStatus::Updated, version as u32 + 1)
+            }
+            None => (WriteStatus::Created, 1),
+        };
+
+        let seq: i64 = sqlx::query_scalar("SELECT nextval('data_feeds_seq')")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to assign feed seq: {}", e))?;
+        feed.seq = Some(seq as u64);
+        let outcome = PutOutcome { status, version, seq: seq as u64 };
+
+        sqlx::query(
+            "INSERT INTO data_feeds (feed_id, source_kind, source_value, data_type, timestamp, feed, content_hash, version, seq)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             ON CONFLICT (feed_id) DO UPDATE SET
+                source_kind = EXCLUDED.source_kind,
+                source_value = EXCLUDED.source_value,
+                data_type = EXCLUDED.data_type,
+                timestamp = EXCLUDED.timestamp,
+                feed = EXCLUDED.feed,
+                content_hash = EXCLUDED.content_hash,
+                version = EXCLUDED.version,
+                seq = EXCLUDED.seq,
+                ingested_at = now()",
+        )
+        .bind(&feed.feed_id)
+        .bind(feed.source.kind())
+        .bind(feed.source.value())
+        .bind(feed.data_type.name())
+        .bind(feed.timestamp as i64)
+        .bind(Json(&feed))
+        .bind(content_hash)
+        .bind(outcome.version as i32)
+        .bind(seq)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to save feed: {}", e))?;
+
+        match feed.data_type {
+            DataType::Block => {
+                if let Ok(block) = serde_json::from_value::<CeloBlock>(feed.raw_data.clone()) {
+                    sqlx::query(
+                        "INSERT INTO indexed_blocks
+                            (number, hash, parent_hash, timestamp, transaction_count, gas_used, miner, feed_id)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                         ON CONFLICT (number) DO UPDATE SET
+                            hash = EXCLUDED.hash,
+                            parent_hash = EXCLUDED.parent_hash,
+                            timestamp = EXCLUDED.timestamp,
+                            transaction_count = EXCLUDED.transaction_count,
+                            gas_used = EXCLUDED.gas_used,
+                            miner = EXCLUDED.miner,
+                            feed_id = EXCLUDED.feed_id",
+                    )
+                    .bind(block.number as i64)
+                    .bind(&block.hash)
+                    .bind(&block.parent_hash)
+                    .bind(block.timestamp as i64)
+                    .bind(block.transaction_count as i32)
+                    .bind(&block.gas_used)
+                    .bind(&block.miner)
+                    .bind(&feed.feed_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Failed to save block: {}", e))?;
+                }
+            }
+            DataType::Transaction => {
+                if let Ok(transaction) = serde_json::from_value::<CeloTransaction>(feed.raw_data.clone()) {
+                    sqlx::query(
+                        "INSERT INTO indexed_transactions
+                            (hash, block_number, from_address, to_address, value, gas_price,
+                             gas_used, status, fee_currency, transaction, feed_id)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                         ON CONFLICT (hash) DO UPDATE SET
+                            block_number = EXCLUDED.block_number,
+                            gas_used = EXCLUDED.gas_used,
+                            status = EXCLUDED.status,
+                            transaction = EXCLUDED.transaction,
+                            feed_id = EXCLUDED.feed_id",
+                    )
+                    .bind(&transaction.hash)
+                    .bind(transaction.block_number as i64)
+                    .bind(&transaction.from)
+                    .bind(&transaction.to)
+                    .bind(&transaction.value)
+                    .bind(&transaction.gas_price)
+                    .bind(&transaction.gas_used)
+                    .bind(transaction.status.map(|s| s as i64))
+                    .bind(&transaction.fee_currency)
+                    .bind(Json(&transaction))
+                    .bind(&feed.feed_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Failed to save transaction: {}", e))?;
+                }
+            }
+            _ => {}
+        }
+
+        tx.commit().await.map_err(|e| format!("Database error: {}", e))?;
+        Ok(outcome)
+    }
+
+    async fn get(&self, feed_id: &str) -> Result<Option<DataFeed>, String> {
+        sqlx::query("SELECT feed FROM data_feeds WHERE feed_id = $1")
+            .bind(feed_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load feed: {}", e))?
+            .map(|row| {
+                row.try_get::<Json<DataFeed>, _>("feed")
+                    .map(|feed| feed.0)
+                    .map_err(|e| format!("Corrupt feed row: {}", e))
+            })
+            .transpose()
+    }
+
+    /// Also removes the blocks/transactions derived from the feeds.
+    async fn remove(&self, feed_ids: &[String]) -> Result<usize, String> {
+        sqlx::query("DELETE FROM data_feeds WHERE feed_id = ANY($1)")
+            .bind(feed_ids)
+            .execute(&self.pool)
+            .await
+            .map(|r| r.rows_affected() as usize)
+            .map_err(|e| format!("Failed to delete feeds: {}", e))
+    }
+
+    async fn query(&self, query: &FeedQuery) -> Result<Vec<DataFeed>, String> {
+        let mut sql = QueryBuilder::<Postgres>::new("SELECT feed FROM data_feeds WHERE 1 = 1");
+        push_feed_filters(&mut sql, query);
+        let (comparison, direction) = match query.order {
+            SortOrder::Asc => (">", "ASC"),
+            SortOrder::Desc => ("<", "DESC"),
+        };
+        if let Some(cursor) = &query.cursor {
+            sql.push(format!(" AND (timestamp, feed_id) {} (", comparison))
+                .push_bind(cursor.timestamp as i64)
+                .push(", ")
+                .push_bind(&cursor.feed_id)
+                .push(")");
+        }
+        sql.push(format!(" ORDER BY timestamp {0}, feed_id {0} LIMIT ", direction))
+            .push_bind(query.limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to query feeds: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                row.try_get::<Json<DataFeed>, _>("feed")
+                    .map(|feed| feed.0)
+                    .map_err(|e| format!("Corrupt feed row: {}", e))
+            })
+            .collect()
+    }
+
+    async fn count(&self) -> Result<usize, String> {
+        sqlx::query_scalar::<_, i64>("SELECT count(*) FROM data_feeds")
+            .fetch_one(&self.pool)
+            .await
+            .map(|count| count as usize)
+            .map_err(|e| format!("Failed to count feeds: {}", e))
+    }
+
+    async fn versions(&self, feed_id: &str) -> Result<Vec<FeedVersion>, String> {
+        let rows = sqlx::query(
+            "SELECT version::BIGINT AS version, content_hash, feed, EXTRACT(EPOCH FROM ingested_at)::BIGINT AS ingested_at
+             FROM data_feed_versions WHERE feed_id = $1
+             UNION ALL
+             SELECT version::BIGINT, content_hash, feed, EXTRACT(EPOCH FROM ingested_at)::BIGINT AS ingested_at
+             FROM data_feeds WHERE feed_id = $1
+             ORDER BY version",
+        )
+        .bind(feed_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load feed versions: {}", e))?;
+
+        rows.iter().map(feed_version_from_row).collect()
+    }
+
+    async fn current_version(&self, feed_id: &str) -> Result<Option<FeedVersion>, String> {
+        sqlx::query(
+            "SELECT version::BIGINT AS version, content_hash, feed, EXTRACT(EPOCH FROM ingested_at)::BIGINT AS ingested_at
+             FROM data_feeds WHERE feed_id = $1",
+        )
+        .bind(feed_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load feed: {}", e))?
+        .as_ref()
+        .map(feed_version_from_row)
+        .transpose()
+    }
+
+    async fn evict(&self, filter: &FeedQuery, keep_newest: usize) -> Result<usize, String> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            "DELETE FROM data_feeds WHERE feed_id IN (SELECT feed_id FROM data_feeds WHERE 1 = 1",
+        );
+        push_feed_filters(&mut sql, filter);
+        sql.push(" ORDER BY timestamp DESC, feed_id DESC OFFSET ")
+            .push_bind(keep_newest as i64)
+            .push(")");
+
+        sql.build()
+            .execute(&self.pool)
+            .await
+            .map(|r| r.rows_affected() as usize)
+            .map_err(|e| format!("Failed to evict feeds: {}", e))
+    }
+
+    async fn written_after(&self, filter: &FeedQuery, after: u64, limit: usize) -> Result<Vec<DataFeed>, String> {
+        let mut sql = QueryBuilder::<Postgres>::new("SELECT feed FROM data_feeds WHERE seq > ");
+        sql.push_bind(after as i64);
+        push_feed_filters(&mut sql, filter);
+        sql.push(" ORDER BY seq LIMIT ").push_bind(limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to query feeds: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                row.try_get::<Json<DataFeed>, _>("feed")
+                    .map(|feed| feed.0)
+                    .map_err(|e| format!("Corrupt feed row: {}", e))
+            })
+            .collect()
+    }
+}
+
+fn feed_version_from_row(row: &PgRow) -> Result<FeedVersion, String> {
+    let corrupt = |e: sqlx::Error| format!("Corrupt feed version: {}", e);
+    Ok(FeedVersion {
+        version: row.try_get::<i64, _>("version").map_err(corrupt)? as u32,
+        content_hash: row.try_get("content_hash").map_err(corrupt)?,
+        ingested_at: row.try_get::<i64, _>("ingested_at").map_err(corrupt)? as u64,
+        feed: row.try_get::<Json<DataFeed>, _>("feed").map_err(corrupt)?.0,
+    })
+}
+
+/// Appends the source, data type and time range conditions of `query`.
+fn push_feed_filters<'a>(sql: &mut QueryBuilder<'a, Postgres>, query: &'a FeedQuery) {
+    if let Some(kind) = &query.source_kind {
+        sql.push(" AND source_kind = ").push_bind(kind);
+    }
+    if let Some(value) = &query.source_value {
+        sql.push(" AND source_value = ").push_bind(value);
+    }
+    if let Some(data_type) = &query.data_type {
+        sql.push(" AND data_type = ").push_bind(data_type);
+    }
+    if let Some(since) = query.since {
+        sql.push(" AND timestamp >= ").push_bind(since as i64);
+    }
+    if let Some(until) = query.until {
+        sql.push(" AND timestamp <= ").push_bind(until as i64);
+    }
+}
+
+#[async_trait]
+impl DecisionStore for PgStore {
+    /// The row id doubles as the decision's `seq`.
+    async fn append(&self, decision: AgentDecision) -> Result<u64, String> {
+        sqlx::query_scalar::<_, i64>(
+            "INSERT INTO agent_decisions (agent_id, decision_type, confidence, timestamp, decision)
+             VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        )
+        .bind(&decision.agent_id)
+        .bind(decision.decision_type.kind())
+        .bind(decision.confidence)
+        .bind(decision.timestamp as i64)
+        .bind(Json(&decision))
+        .fetch_one(&self.pool)
+        .await
+        .map(|id| id as u64)
+        .map_err(|e| format!("Failed to save decision: {}", e))
+    }
+
+    async fn query(&self, query: &DecisionQuery) -> Result<Vec<AgentDecision>, String> {
+        let mut sql = QueryBuilder::<Postgres>::new("SELECT id, decision FROM agent_decisions WHERE 1 = 1");
+        push_decision_filters(&mut sql, query);
+        let direction = match query.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        sql.push(format!(" ORDER BY timestamp {0}, id {0} LIMIT ", direction))
+            .push_bind(query.limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load decisions: {}", e))?;
+
+        rows.iter().map(decision_from_row).collect()
+    }
+
+    async fn aggregate(&self, query: &DecisionQuery) -> Result<Vec<DecisionGroup>, String> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            "SELECT agent_id, decision_type, count(*) AS count, sum(confidence)::FLOAT8 AS confidence_sum,
+                    min(timestamp) AS first_timestamp, max(timestamp) AS last_timestamp
+             FROM agent_decisions WHERE 1 = 1",
+        );
+        push_decision_filters(&mut sql, query);
+        sql.push(" GROUP BY agent_id, decision_type");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to aggregate decisions: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let corrupt = |e: sqlx::Error| format!("Corrupt decision aggregate: {}", e);
+                Ok(DecisionGroup {
+                    agent_id: row.try_get("agent_id").map_err(corrupt)?,
+                    decision_type: row.try_get("decision_type").map_err(corrupt)?,
+                    count: row.try_get::<i64, _>("count").map_err(corrupt)? as u64,
+                    confidence_sum: row.try_get("confidence_sum").map_err(corrupt)?,
+                    first_timestamp: row.try_get::<i64, _>("first_timestamp").map_err(corrupt)? as u64,
+                    last_timestamp: row.try_get::<i64, _>("last_timestamp").map_err(corrupt)? as u64,
+                })
+            })
+            .collect()
+    }
+
+    async fn evict(&self, older_than: Option<u64>, keep_newest: Option<usize>) -> Result<usize, String> {
+        let mut evicted = 0;
+        if let Some(cutoff) = older_than {
+            evicted += sqlx::query("DELETE FROM agent_decisions WHERE timestamp < $1")
+                .bind(cutoff as i64)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to evict decisions: {}", e))?
+                .rows_affected() as usize;
+        }
+        if let Some(keep) = keep_newest {
+            evicted += sqlx::query(
+                "DELETE FROM agent_decisions WHERE id IN
+                    (SELECT id FROM agent_decisions ORDER BY id DESC OFFSET $1)",
+            )
+            .bind(keep as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to evict decisions: {}", e))?
+            .rows_affected() as usize;
+        }
+        Ok(evicted)
+    }
+
+    async fn recorded_after(&self, filter: &DecisionQuery, after: u64, limit: usize) -> Result<Vec<AgentDecision>, String> {
+        let mut sql = QueryBuilder::<Postgres>::new("SELECT id, decision FROM agent_decisions WHERE id > ");
+        sql.push_bind(after as i64);
+        push_decision_filters(&mut sql, filter);
+        sql.push(" ORDER BY id LIMIT ").push_bind(limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load decisions: {}", e))?;
+
+        rows.iter().map(decision_from_row).collect()
+    }
+}
+
+fn decision_from_row(row: &PgRow) -> Result<AgentDecision, String> {
+    let corrupt = |e: sqlx::Error| format!("Corrupt decision row: {}", e);
+    let mut decision = row.try_get::<Json<AgentDecision>, _>("decision").map_err(corrupt)?.0;
+    decision.seq = Some(row.try_get::<i64, _>("id").map_err(corrupt)? as u64);
+    Ok(decision)
+}
+
+#[async_trait]
+impl SchemaStore for PgStore {
+    async fn put(&self, type_name: &str, schema: &serde_json::Value) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO feed_schemas (type_name, schema) VALUES ($1, $2)
+             ON CONFLICT (type_name) DO UPDATE SET schema = excluded.schema, updated_at = now()",
+        )
+        .bind(type_name)
+        .bind(Json(schema))
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save schema: {}", e))
+    }
+
+    async fn remove(&self, type_name: &str) -> Result<bool, String> {
+        sqlx::query("DELETE FROM feed_schemas WHERE type_name = $1")
+            .bind(type_name)
+            .execute(&self.pool)
+            .await
+            .map(|r| r.rows_affected() > 0)
+            .map_err(|e| format!("Failed to delete schema: {}", e))
+    }
+
+    async fn list(&self) -> Result<Vec<(String, serde_json::Value)>, String> {
+        let rows: Vec<(String, Json<serde_json::Value>)> =
+            sqlx::query_as("SELECT type_name, schema FROM feed_schemas ORDER BY type_name")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to load schemas: {}", e))?;
+        Ok(rows.into_iter().map(|(type_name, schema)| (type_name, schema.0)).collect())
+    }
+}
+
+/// Appends the filter conditions of `query`.
+fn push_decision_filters<'a>(sql: &mut QueryBuilder<'a, Postgres>, query: &'a DecisionQuery) {
+    if let Some(agent_id) = &query.agent_id {
+        sql.push(" AND agent_id = ").push_bind(agent_id);
+    }
+    if let Some(decision_type) = &query.decision_type {
+        sql.push(" AND decision_type = ").push_bind(decision_type);
+    }
+    if let Some(min) = query.min_confidence {
+        sql.push(" AND confidence >= ").push_bind(min);
+    }
+    if let Some(max) = query.max_confidence {
+        sql.push(" AND confidence <= ").push_bind(max);
+    }
+    if let Some(since) = query.since {
+        sql.push(" AND timestamp >= ").push_bind(since as i64);
+    }
+    if let Some(until) = query.until {
+        sql.push(" AND timestamp <= ").push_bind(until as i64);
+    }
+    if !query.data_sources.is_empty() {
+        sql.push(" AND decision->'data_sources' ?| ").push_bind(&query.data_sources);
+    }
+}
+
+/// Run only when `DATABASE_URL` is set. Each test migrates and uses a schema of its own,
+/// which is dropped afterwards, so existing data in the database is not touched.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests;
+    use ethers::core::rand::{self, Rng};
+    use std::future::Future;
+
+    async fn with_store<F, Fut>(check: F)
+    where
+        F: FnOnce(PgStore) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL is not set; skipping Postgres store test");
+            return;
+        };
+        let schema = format!("store_tests_{:016x}", rand::thread_rng().gen::<u64>());
+        let admin = PgPool::connect(&url).await.expect("Failed to connect to DATABASE_URL");
+        sqlx::query(&format!("CREATE SCHEMA {}", schema)).execute(&admin).await.unwrap();
+
+        let options = url.parse::<PgConnectOptions>().unwrap().options([("search_path", schema.as_str())]);
+        let store = PgStore::connect_with(options).await.unwrap();
+        check(store.clone()).await;
+
+        store.pool.close().await;
+        sqlx::query(&format!("DROP SCHEMA {} CASCADE", schema)).execute(&admin).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn put_and_versions() {
+        with_store(|store| async move { tests::check_put_and_versions(&store).await }).await;
+    }
+
+    #[tokio::test]
+    async fn concurrent_puts() {
+        with_store(|store| async move { tests::check_concurrent_puts(&store).await }).await;
+    }
+
+    #[tokio::test]
+    async fn cursor_paging() {
+        with_store(|store| async move { tests::check_cursor_paging(&store).await }).await;
+    }
+
+    #[tokio::test]
+    async fn feed_eviction() {
+        with_store(|store| async move { tests::check_feed_eviction(&store).await }).await;
+    }
+
+    #[tokio::test]
+    async fn write_order() {
+        with_store(|store| async move { tests::check_write_order(&store).await }).await;
+    }
+
+    #[tokio::test]
+    async fn recording_order() {
+        with_store(|store| async move { tests::check_recording_order(&store).await }).await;
+    }
+
+    #[tokio::test]
+    async fn decision_eviction() {
+        with_store(|store| async move { tests::check_decision_eviction(&store).await }).await;
+    }
+
+    #[tokio::test]
+    async fn schemas() {
+        with_store(|store| async move { tests::check_schemas(&store).await }).await;
+    }
+}
@@ -0,0 +1,704 @@
+// Chain Head Follower - pulls new Celo blocks into the Real-Time Indexer
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::celo_client::{CeloBlock, CeloClient, CeloTransaction};
+use crate::storage::{FeedCursor, FeedQuery, SortOrder};
+use crate::indexer::{AgentDecision, DataFeed, DataType, DecisionType, FeedSource, IngestError, OrphanedBlock, RealtimeIndexer, ReorgNotice};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowerConfig {
+    pub enabled: bool,
+    pub start_block: Option<u64>,   // defaults to after the last stored block, else the safe head
+    pub confirmations: u64,         // blocks to stay behind the head
+    pub poll_interval_ms: u64,
+    pub max_blocks_per_poll: u64,   // caps catch-up work per tick
+    pub reorg_depth: usize,         // recent blocks kept for parent-hash checks
+}
+
+impl FollowerConfig {
+    /// Opt-in with `FOLLOWER_ENABLED=true`: following the head grows the store with every block,
+    /// so enable it together with a durable backend or a retention policy.
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("FOLLOWER_ENABLED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            start_block: std::env::var("FOLLOWER_START_BLOCK")
+                .ok()
+                .map(|v| v.parse().expect("FOLLOWER_START_BLOCK must be a valid number")),
+            confirmations: std::env::var("FOLLOWER_CONFIRMATIONS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .expect("FOLLOWER_CONFIRMATIONS must be a valid number"),
+            poll_interval_ms: std::env::var("FOLLOWER_POLL_INTERVAL_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .expect("FOLLOWER_POLL_INTERVAL_MS must be a valid number"),
+            max_blocks_per_poll: std::env::var("FOLLOWER_MAX_BLOCKS_PER_POLL")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .expect("FOLLOWER_MAX_BLOCKS_PER_POLL must be a valid number"),
+            reorg_depth: std::env::var("FOLLOWER_REORG_DEPTH")
+                .unwrap_or_else(|_| "64".to_string())
+                .parse()
+                .expect("FOLLOWER_REORG_DEPTH must be a valid number"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FollowerStatus {
+    pub running: bool,
+    pub last_indexed_block: Option<u64>,
+    pub chain_head: Option<u64>,
+    pub blocks_indexed: u64,
+    pub transactions_indexed: u64,
+    /// Feeds a cleaning stage rejected; the rest of their block is still indexed.
+    pub feeds_rejected: u64,
+    pub reorgs: u64,
+    pub last_reorg_depth: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// A block the follower has ingested, with the feeds it produced.
+#[derive(Debug, Clone)]
+pub struct IndexedBlock {
+    pub number: u64,
+    pub hash: String,
+    /// The feeds that were stored; rejected ones are left out.
+    pub feed_ids: Vec<String>,
+    pub transaction_count: u64,
+    pub feeds_rejected: u64,
+}
+
+pub struct BlockFollower {
+    config: FollowerConfig,
+    status: Arc<RwLock<FollowerStatus>>,
+    shutdown: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BlockFollower {
+    pub fn spawn(config: FollowerConfig, client: CeloClient, indexer: RealtimeIndexer) -> Self {
+        let status = Arc::new(RwLock::new(FollowerStatus {
+            running: true,
+            ..Default::default()
+        }));
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let task = tokio::spawn(run(config.clone(), client, indexer, status.clone(), shutdown_rx));
+
+        Self {
+            config,
+            status,
+            shutdown,
+            task: Mutex::new(Some(task)),
+        }
+    }
+
+    pub fn config(&self) -> &FollowerConfig {
+        &self.config
+    }
+
+    pub async fn status(&self) -> FollowerStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Signals the follower to stop after the block it is currently indexing.
+    pub fn stop(&self) {
+        let _ = self.shutdown.send(true);
+    }
+
+    /// Stops the follower and waits for the task to exit.
+    pub async fn shutdown(&self) {
+        self.stop();
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
+
+async fn run(
+    config: FollowerConfig,
+    client: CeloClient,
+    indexer: RealtimeIndexer,
+    status: Arc<RwLock<FollowerStatus>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut interval = tokio::time::interval(Duration::from_millis(config.poll_interval_ms.max(100)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut next_block = config.start_block;
+    let mut recent: VecDeque<IndexedBlock> = VecDeque::with_capacity(config.reorg_depth);
+
+    // Without a start block, pick up after the blocks stored before a restart
+    if next_block.is_none() {
+        match load_recent_blocks(&indexer, config.reorg_depth.max(1)).await {
+            Ok(stored) => {
+                if let Some(last) = stored.back() {
+                    tracing::info!("⛓️  Block follower resuming after stored block {}", last.number);
+                    next_block = Some(last.number + 1);
+                    status.write().await.last_indexed_block = Some(last.number);
+                }
+                recent = stored;
+            }
+            Err(e) => {
+                // Starting at the head would silently skip the blocks since the last run
+                tracing::error!("Follower failed to load the last stored block: {}", e);
+                let mut status = status.write().await;
+                status.last_error = Some(e);
+                status.running = false;
+                return;
+            }
+        }
+    }
+
+    tracing::info!("⛓️  Block follower started (confirmations: {})", config.confirmations);
+
+    'follow: loop {
+        tokio::select! {
+            _ = shutdown.changed() => break,
+            _ = interval.tick() => {}
+        }
+
+        let head = match client.get_latest_block().await {
+            Ok(block) => block.number,
+            Err(e) => {
+                tracing::warn!("Follower failed to fetch chain head: {}", e);
+                status.write().await.last_error = Some(e);
+                continue;
+            }
+        };
+        status.write().await.chain_head = Some(head);
+
+        let safe_head = head.saturating_sub(config.confirmations);
+        let next = *next_block.get_or_insert(safe_head);
+        let last = safe_head.min(next.saturating_add(config.max_blocks_per_poll.max(1) - 1));
+
+        for number in next..=last {
+            if *shutdown.borrow() {
+                break 'follow;
+            }
+
+            let (block, transactions) = match client.get_block_with_txs(number).await {
+                Ok(result) => result,
+                Err(e) => {
+                    // Retry the same block on the next tick
+                    tracing::warn!("Follower failed to fetch block {}: {}", number, e);
+                    status.write().await.last_error = Some(e);
+                    break;
+                }
+            };
+
+            let parent_mismatch = recent
+                .back()
+                .is_some_and(|prev| prev.number + 1 == number && prev.hash != block.parent_hash);
+            if parent_mismatch {
+                match rollback_reorg(&client, &indexer, &mut recent).await {
+                    Ok((ancestor, depth)) => {
+                        next_block = Some(ancestor + 1);
+                        let mut status = status.write().await;
+                        status.reorgs += 1;
+                        status.last_reorg_depth = Some(depth);
+                        status.last_indexed_block = Some(ancestor);
+                    }
+                    Err(ReorgError::Retry(e)) => {
+                        tracing::warn!("Follower failed to resolve reorg at block {}: {}", number, e);
+                        status.write().await.last_error = Some(e);
+                    }
+                    Err(ReorgError::TooDeep(e)) => {
+                        // Continuing would index the new branch on top of orphaned data
+                        tracing::error!("Follower stopped at block {}: {}", number, e);
+                        status.write().await.last_error = Some(e);
+                        break 'follow;
+                    }
+                }
+                // Re-index the canonical branch from the common ancestor
+                break;
+            }
+
+            match ingest_block(&indexer, &block, &transactions).await {
+                Ok(indexed) => {
+                    next_block = Some(number + 1);
+                    let feeds_rejected = indexed.feeds_rejected;
+                    recent.push_back(indexed);
+                    while recent.len() > config.reorg_depth.max(1) {
+                        recent.pop_front();
+                    }
+
+                    let mut status = status.write().await;
+                    status.last_indexed_block = Some(number);
+                    status.blocks_indexed += 1;
+                    status.transactions_indexed += transactions.len() as u64;
+                    status.feeds_rejected += feeds_rejected;
+                    status.last_error = None;
+                }
+                Err(e) => {
+                    // Only storage errors end up here; retry the block on the next tick
+                    tracing::warn!("Follower failed to index block {}: {}", number, e);
+                    status.write().await.last_error = Some(e);
+                    break;
+                }
+            }
+        }
+    }
+
+    status.write().await.running = false;
+    tracing::info!("⛓️  Block follower stopped");
+}
+
+/// The newest run of consecutive `celo:block:<number>` feeds in the store (at most `depth`),
+/// oldest first, with the transaction feeds stored for each.
+async fn load_recent_blocks(indexer: &RealtimeIndexer, depth: usize) -> Result<VecDeque<IndexedBlock>, String> {
+    let page = indexer
+        .query_feeds(FeedQuery {
+            data_type: Some(DataType::Block.name()),
+            order: SortOrder::Desc,
+            limit: depth,
+            ..Default::default()
+        })
+        .await?;
+
+    let mut blocks: VecDeque<IndexedBlock> = VecDeque::with_capacity(depth);
+    let mut span: Option<(u64, u64)> = None;
+    for feed in &page.feeds {
+        let Some(number) = feed.feed_id.strip_prefix("celo:block:").and_then(|n| n.parse::<u64>().ok()) else {
+            continue;
+        };
+        if blocks.front().is_some_and(|newer| newer.number != number + 1) {
+            break;
+        }
+        blocks.push_front(IndexedBlock {
+            number,
+            hash: feed.raw_data["hash"].as_str().unwrap_or_default().to_string(),
+            feed_ids: vec![feed.feed_id.clone()],
+            transaction_count: 0,
+            feeds_rejected: 0,
+        });
+        span = Some(match span {
+            Some((oldest, newest)) => (oldest.min(feed.timestamp), newest.max(feed.timestamp)),
+            None => (feed.timestamp, feed.timestamp),
+        });
+    }
+    let Some((oldest, newest)) = span else {
+        return Ok(blocks);
+    };
+
+    // Transactions share their block's timestamp
+    let mut query = FeedQuery {
+        data_type: Some(DataType::Transaction.name()),
+        since: Some(oldest),
+        until: Some(newest),
+        order: SortOrder::Asc,
+        limit: 1000,
+        ..Default::default()
+    };
+    loop {
+        let page = indexer.query_feeds(query.clone()).await?;
+        for feed in &page.feeds {
+            let number = feed.raw_data["block_number"].as_u64();
+            if let Some(block) = blocks.iter_mut().find(|b| Some(b.number) == number) {
+                block.feed_ids.push(feed.feed_id.clone());
+                block.transaction_count += 1;
+            }
+        }
+        match page.next_cursor {
+            Some(cursor) => query.cursor = Some(FeedCursor::decode(&cursor)?),
+            None => break,
+        }
+    }
+
+    Ok(blocks)
+}
+
+/// Fetches a block with its transactions and ingests them.
+pub async fn index_block(client: &CeloClient, indexer: &RealtimeIndexer, number: u64) -> Result<IndexedBlock, String> {
+    let (block, transactions) = client.get_block_with_txs(number).await?;
+    ingest_block(indexer, &block, &transactions).await
+}
+
+/// Ingests a block and its transactions as feeds. A feed the cleaning pipeline rejects
+/// would be rejected again on every retry, so it is counted and skipped; only storage
+/// errors fail the block.
+async fn ingest_block(
+    indexer: &RealtimeIndexer,
+    block: &CeloBlock,
+    transactions: &[CeloTransaction],
+) -> Result<IndexedBlock, String> {
+    let mut feed_ids = Vec::new();
+    let mut feeds_rejected = 0;
+
+    for feed in block_to_feeds(block, transactions) {
+        let feed_id = feed.feed_id.clone();
+        match indexer.ingest_feed(feed).await {
+            Ok(_) => feed_ids.push(feed_id),
+            Err(IngestError::Rejected(e)) => {
+                tracing::warn!("Skipping feed {} in block {}: {}", feed_id, block.number, e);
+                feeds_rejected += 1;
+            }
+            Err(IngestError::Storage(e)) => return Err(e),
+        }
+    }
+
+    Ok(IndexedBlock {
+        number: block.number,
+        hash: block.hash.clone(),
+        feed_ids,
+        transaction_count: transactions.len() as u64,
+        feeds_rejected,
+    })
+}
+
+/// Orphaned blocks listed in a reorg alert's reasoning; the rest are counted.
+const MAX_LISTED_ORPHANS: usize = 100;
+
+/// Why a reorg could not be rolled back.
+#[derive(Debug)]
+enum ReorgError {
+    /// An RPC or storage call failed; the tracked blocks are kept and the reorg is retried on
+    /// the next tick.
+    Retry(String),
+    /// Every tracked block is orphaned, so the common ancestor is unknown.
+    TooDeep(String),
+}
+
+/// Walks back through recently indexed blocks until one still matches the canonical
+/// chain, rolls back the feeds of every orphaned block and alerts downstream agents.
+/// Returns the common ancestor and the reorg depth.
+async fn rollback_reorg(
+    client: &CeloClient,
+    indexer: &RealtimeIndexer,
+    recent: &mut VecDeque<IndexedBlock>,
+) -> Result<(u64, u64), ReorgError> {
+    // `recent` is only truncated once the rollback is recorded, so a failure can be retried
+    let mut fork = recent.len();
+    while fork > 0 {
+        let tracked = &recent[fork - 1];
+        let canonical = client.get_block_by_number(tracked.number).await.map_err(ReorgError::Retry)?;
+        if canonical.hash == tracked.hash {
+            break;
+        }
+        fork -= 1;
+    }
+    if fork == 0 {
+        let oldest = recent.front().map_or(0, |b| b.number);
+        return Err(ReorgError::TooDeep(format!(
+            "Reorg is deeper than the {} tracked blocks (block {} is orphaned too); back up the index, \
+            re-index from an earlier block and restart with FOLLOWER_START_BLOCK",
+            recent.len(),
+            oldest
+        )));
+    }
+
+    let ancestor = recent[fork - 1].number;
+    let orphaned: Vec<IndexedBlock> = recent.range(fork..).cloned().collect();
+    let depth = orphaned.len() as u64;
+    let orphaned_feeds: Vec<String> = orphaned.iter().flat_map(|b| b.feed_ids.clone()).collect();
+
+    let removed = indexer.rollback_feeds(&orphaned_feeds).await.map_err(ReorgError::Retry)?;
+
+    // The orphaned feeds no longer exist, so the alert names the blocks instead of citing them
+    // as data sources
+    let mut listed: Vec<String> = orphaned
+        .iter()
+        .take(MAX_LISTED_ORPHANS)
+        .map(|b| format!("#{} {}", b.number, b.hash))
+        .collect();
+    if orphaned.len() > MAX_LISTED_ORPHANS {
+        listed.push(format!("and {} more", orphaned.len() - MAX_LISTED_ORPHANS));
+    }
+    indexer
+        .process_agent_decision(AgentDecision {
+            agent_id: "celo-block-follower".to_string(),
+            decision_type: DecisionType::Alert {
+                severity: if depth > 3 { "high" } else { "medium" }.to_string(),
+                message: format!(
+                    "Chain reorg of depth {} after block #{}: {} indexed feed(s) were rolled back and will be re-indexed",
+                    depth, ancestor, orphaned_feeds.len()
+                ),
+                trigger: None,
+            },
+            confidence: 1.0,
+            reasoning: format!("Orphaned blocks: {}", listed.join(", ")),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            data_sources: Vec::new(),
+            provenance: None,
+            seq: None,
+        })
+        .await
+        .map_err(|e| ReorgError::Retry(format!("Failed to record reorg alert: {}", e)))?;
+
+    recent.truncate(fork);
+    indexer
+        .record_reorg(ReorgNotice {
+            common_ancestor: ancestor,
+            depth,
+            orphaned_blocks: orphaned
+                .iter()
+                .map(|b| OrphanedBlock { number: b.number, hash: b.hash.clone() })
+                .collect(),
+            feeds_rolled_back: removed,
+            detected_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        })
+        .await;
+
+    tracing::warn!(
+        "⚠ Chain reorg detected: {} block(s) orphaned after #{}, {} feed(s) rolled back",
+        depth, ancestor, removed
+    );
+
+    Ok((ancestor, depth))
+}
+
+/// Converts a block and its transactions into indexer feeds.
+pub fn block_to_feeds(block: &CeloBlock, transactions: &[CeloTransaction]) -> Vec<DataFeed> {
+    let mut feeds = Vec::with_capacity(transactions.len() + 1);
+
+    feeds.push(DataFeed {
+        feed_id: format!("celo:block:{}", block.number),
+        source: FeedSource::OnChain(block.miner.clone()),
+        data_type: DataType::Block,
+        timestamp: block.timestamp,
+        raw_data: serde_json::to_value(block).unwrap_or_default(),
+        cleaned_data: None,
+        seq: None,
+    });
+
+    for tx in transactions {
+        // Attribute the tx to the contract it touches, falling back to the sender
+        let address = tx
+            .to
+            .clone()
+            .or_else(|| tx.contract_address.clone())
+            .unwrap_or_else(|| tx.from.clone());
+        let mut raw_data = serde_json::to_value(tx).unwrap_or_default();
+        raw_data["block_hash"] = serde_json::json!(block.hash);
+
+        feeds.push(DataFeed {
+            feed_id: format!("celo:tx:{}", tx.hash),
+            source: FeedSource::OnChain(address),
+            data_type: DataType::Transaction,
+            timestamp: block.timestamp,
+            raw_data,
+            cleaned_data: None,
+            seq: None,
+        });
+    }
+
+    feeds
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::storage::DecisionQuery;
+    use axum::extract::State;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use std::time::Instant;
+
+    /// A chain of blocks `0..=head` with one transaction each, served over JSON-RPC. Blocks
+    /// from `fork_at` on belong to a competing branch and get other hashes.
+    #[derive(Default)]
+    pub(crate) struct Chain {
+        head: u64,
+        fork_at: Option<u64>,
+    }
+
+    impl Chain {
+        fn hash(&self, kind: u64, number: u64) -> String {
+            let forked = self.fork_at.is_some_and(|at| number >= at);
+            format!("0x{:016x}{:016x}{:032x}", kind, forked as u64, number)
+        }
+
+        fn block(&self, number: u64, full: bool) -> Value {
+            let tx_hash = self.hash(1, number);
+            let tx = json!({
+                "hash": tx_hash, "nonce": "0x0", "blockHash": self.hash(0, number),
+                "blockNumber": format!("{:#x}", number), "transactionIndex": "0x0",
+                "from": format!("0x{:040x}", 1), "to": format!("0x{:040x}", 2),
+                "value": "0xde0b6b3a7640000", "gasPrice": "0x1", "gas": "0x5208", "input": "0x",
+                "v": "0x1", "r": "0x1", "s": "0x1", "type": "0x0", "chainId": "0xa4ec",
+            });
+            json!({
+                "number": format!("{:#x}", number), "hash": self.hash(0, number),
+                "parentHash": self.hash(0, number.saturating_sub(1)),
+                "timestamp": format!("{:#x}", 1_700_000_000 + number),
+                "miner": format!("0x{:040x}", 9), "gasUsed": "0x0", "gasLimit": "0x1",
+                "transactions": [if full { tx } else { json!(tx_hash) }],
+                "sha3Uncles": format!("0x{:064x}", 0), "logsBloom": format!("0x{}", "0".repeat(512)),
+                "transactionsRoot": format!("0x{:064x}", 0), "stateRoot": format!("0x{:064x}", 0),
+                "receiptsRoot": format!("0x{:064x}", 0), "difficulty": "0x0", "extraData": "0x",
+                "size": "0x1", "uncles": [], "nonce": "0x0000000000000000", "mixHash": format!("0x{:064x}", 0),
+            })
+        }
+    }
+
+    pub(crate) type SharedChain = Arc<Mutex<Chain>>;
+
+    async fn rpc(State(chain): State<SharedChain>, Json(request): Json<Value>) -> Json<Value> {
+        let chain = chain.lock().unwrap();
+        let params = &request["params"];
+        let result = match (request["method"].as_str(), params[0].as_str()) {
+            (Some("eth_getBlockByNumber"), Some(tag)) => {
+                let number = match tag {
+                    "latest" => chain.head,
+                    hex => u64::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap(),
+                };
+                if number <= chain.head {
+                    chain.block(number, params[1].as_bool().unwrap_or(false))
+                } else {
+                    Value::Null
+                }
+            }
+            _ => Value::Null,
+        };
+        Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+    }
+
+    /// Serves a chain with blocks up to `head`; backfill tests use it too.
+    pub(crate) async fn serve(head: u64) -> (SharedChain, CeloClient) {
+        let chain = Arc::new(Mutex::new(Chain { head, fork_at: None }));
+        let app = Router::new().route("/", post(rpc)).with_state(chain.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (chain, CeloClient::new(Some(url)))
+    }
+
+    fn config(start_block: Option<u64>, reorg_depth: usize) -> FollowerConfig {
+        FollowerConfig {
+            enabled: true,
+            start_block,
+            confirmations: 0,
+            poll_interval_ms: 100,
+            max_blocks_per_poll: 20,
+            reorg_depth,
+        }
+    }
+
+    async fn eventually(what: &str, follower: &BlockFollower, condition: impl Fn(&FollowerStatus) -> bool) -> FollowerStatus {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let status = follower.status().await;
+            if condition(&status) {
+                return status;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for {}: {:?}", what, status);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn resumes_after_the_last_stored_block() {
+        let (chain, client) = serve(5).await;
+        let indexer = RealtimeIndexer::new();
+
+        let follower = BlockFollower::spawn(config(Some(1), 8), client.clone(), indexer.clone());
+        eventually("blocks 1-5", &follower, |s| s.last_indexed_block == Some(5)).await;
+        follower.shutdown().await;
+
+        // Blocks produced while the follower was down are indexed after the restart
+        chain.lock().unwrap().head = 8;
+        let follower = BlockFollower::spawn(config(None, 8), client, indexer.clone());
+        let status = eventually("blocks 6-8", &follower, |s| s.last_indexed_block == Some(8)).await;
+        follower.shutdown().await;
+
+        assert_eq!(status.blocks_indexed, 3);
+        assert_eq!(status.transactions_indexed, 3);
+        for number in 6..=8 {
+            assert!(indexer.get_feed(&format!("celo:block:{}", number)).await.unwrap().is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn recent_blocks_are_loaded_with_their_transactions() {
+        let (_chain, client) = serve(4).await;
+        let indexer = RealtimeIndexer::new();
+        for number in 1..=4 {
+            index_block(&client, &indexer, number).await.unwrap();
+        }
+
+        let recent = load_recent_blocks(&indexer, 3).await.unwrap();
+        let numbers: Vec<u64> = recent.iter().map(|b| b.number).collect();
+        assert_eq!(numbers, vec![2, 3, 4]);
+        let chain = Chain::default();
+        assert_eq!(recent[2].hash, chain.hash(0, 4));
+        assert_eq!(
+            recent[2].feed_ids,
+            vec!["celo:block:4".to_string(), format!("celo:tx:{}", chain.hash(1, 4))]
+        );
+    }
+
+    #[tokio::test]
+    async fn reorgs_roll_back_orphaned_blocks_and_reindex_the_canonical_branch() {
+        let (chain, client) = serve(5).await;
+        let indexer = RealtimeIndexer::new();
+        let follower = BlockFollower::spawn(config(Some(1), 8), client, indexer.clone());
+        eventually("blocks 1-5", &follower, |s| s.last_indexed_block == Some(5)).await;
+        let orphaned_tx = format!("celo:tx:{}", chain.lock().unwrap().hash(1, 4));
+
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.fork_at = Some(4);
+            chain.head = 6;
+        }
+        let status = eventually("the reorg", &follower, |s| s.reorgs == 1 && s.last_indexed_block == Some(6)).await;
+        follower.shutdown().await;
+        assert_eq!(status.last_reorg_depth, Some(2));
+        assert_eq!(status.last_error, None);
+
+        let (canonical_tx, canonical_hash) = {
+            let chain = chain.lock().unwrap();
+            (format!("celo:tx:{}", chain.hash(1, 4)), chain.hash(0, 4))
+        };
+        assert!(indexer.get_feed(&orphaned_tx).await.unwrap().is_none());
+        assert!(indexer.get_feed(&canonical_tx).await.unwrap().is_some());
+        let block = indexer.get_feed("celo:block:4").await.unwrap().unwrap();
+        assert_eq!(block.raw_data["hash"], json!(canonical_hash));
+        assert_eq!(indexer.get_metrics().await.reorgs_detected, 1);
+
+        let alerts = indexer
+            .query_decisions(&DecisionQuery {
+                agent_id: Some("celo-block-follower".to_string()),
+                limit: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].data_sources.is_empty());
+        assert!(alerts[0].reasoning.contains(&format!("#4 {}", Chain::default().hash(0, 4))));
+    }
+
+    #[tokio::test]
+    async fn reorgs_deeper_than_the_tracked_window_stop_the_follower() {
+        let (chain, client) = serve(5).await;
+        let indexer = RealtimeIndexer::new();
+        let follower = BlockFollower::spawn(config(Some(1), 2), client, indexer.clone());
+        eventually("blocks 1-5", &follower, |s| s.last_indexed_block == Some(5)).await;
+
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.fork_at = Some(3);
+            chain.head = 6;
+        }
+        let status = eventually("the follower to stop", &follower, |s| !s.running).await;
+        assert!(status.last_error.unwrap().starts_with("Reorg is deeper than the 2 tracked blocks"));
+        assert_eq!(status.last_indexed_block, Some(5));
+        assert_eq!(status.reorgs, 0);
+        assert!(indexer.get_feed("celo:block:6").await.unwrap().is_none());
+    }
+}
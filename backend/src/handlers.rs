@@ -1,26 +1,62 @@
 use axum::{
-    extract::{Path, Json, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::Body,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Json, Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
-use serde::Deserialize;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::{format_units, hex, keccak256};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::convert::Infallible;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::models::*;
 #[allow(unused_imports)]
-use crate::indexer::{RealtimeIndexer, DataFeed, AgentDecision, IndexerMetrics};
+use crate::indexer::{RealtimeIndexer, DataFeed, DataType, DecisionType, FeedSource, AgentDecision, DecisionError, IndexerMetrics, IngestError, IngestReceipt, SchemaError};
 #[allow(unused_imports)]
 use crate::ai_engine::{CeloAIEngine, LLMRequest, TaskType, LLMResponse, Celo7BModel};
-use tokio::sync::RwLock;
+use crate::celo_client::{CeloBlock, CeloTransaction};
+use crate::backtest::{AgentSpec, BacktestSpec};
+use crate::idempotency::{IdempotencyCache, IdempotencyLookup};
+use crate::storage::{DecisionQuery, FeedCursor, FeedQuery, SortOrder, WriteStatus};
+use crate::stream::{self, StreamEvent};
+use crate::telemetry;
+use crate::webhooks::{WebhookEvent, WebhookFilter};
+use tokio::sync::{mpsc, RwLock};
 
 pub type AppState = Arc<RwLock<AppStateInner>>;
 
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// Feeds accepted by one `/indexer/ingest/batch` request.
+const MAX_INGEST_BATCH: usize = 5000;
+/// Body limit for batch requests; axum's default of 2 MiB is too small for full batches.
+pub const INGEST_BATCH_BODY_LIMIT: usize = 32 * 1024 * 1024;
+/// Longest line accepted by `/indexer/ingest/stream`.
+const MAX_NDJSON_LINE_BYTES: usize = 1024 * 1024;
+
 pub struct AppStateInner {
     pub indexer: RealtimeIndexer,
     pub ai_engine: CeloAIEngine,
     pub celo_client: crate::celo_client::CeloClient,
+    pub follower: Option<crate::follower::BlockFollower>,
+    pub backfill: crate::backfill::BackfillManager,
+    pub retention: Option<crate::retention::RetentionTask>,
+    pub monitors: Option<crate::monitor::MonitorEngine>,
+    pub webhooks: crate::webhooks::WebhookDispatcher,
+    pub paper_trading: Option<crate::paper_trading::PaperTrader>,
+    pub backtests: crate::backtest::BacktestManager,
+    pub idempotency: IdempotencyCache,
 }
 
 impl AppStateInner {
@@ -155,50 +191,75 @@ pub async fn get_transactions(
     State(state): State<AppState>,
     Query(params): Query<TransactionQueryParams>,
 ) -> impl IntoResponse {
-    let limit = params.limit.unwrap_or(10).min(100);
-    let state = state.read().await;
+    let limit = params.limit.unwrap_or(10).min(100) as usize;
+    let filter = match TransactionFilter::from_params(&params) {
+        Ok(filter) => filter,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))),
+    };
+    let client = state.read().await.celo_client.clone();
     
-    // Try to get real transaction data from latest blocks
-    if state.celo_client.is_connected() {
-        if let Ok(latest_block) = state.celo_client.get_latest_block().await {
-            // For now, return block-level transaction info
-            // TODO: Implement full transaction details with get_transaction()
+    // Walk full blocks backwards from the end of the requested range
+    if client.is_connected() {
+        if let Ok(latest_block) = client.get_latest_block().await {
+            let to_block = params.to_block.unwrap_or(latest_block.number).min(latest_block.number);
+            let from_block = params
+                .from_block
+                .unwrap_or(0)
+                .max(to_block.saturating_sub(MAX_TX_SCAN_BLOCKS - 1));
+            if from_block > to_block {
+                return (StatusCode::BAD_REQUEST, Json(json!({
+                    "error": "from_block must not be greater than to_block"
+                })));
+            }
+            
             let mut transactions = Vec::new();
+            let mut blocks_scanned = 0;
             
-            for i in 0..limit.min(10) {
-                if let Ok(block) = state.celo_client.get_block_by_number(latest_block.number - i as u64).await {
-                    // Create transaction entries from block data
-                    for j in 0..block.transaction_count.min(limit as usize - transactions.len()) {
-                        transactions.push(TransactionData {
-                            tx_hash: format!("{}_{}", block.hash, j),
-                            from_address: block.miner.clone(),
-                            to_address: Some("0x0000000000000000000000000000000000000000".to_string()),
-                            value: "0".to_string(),
-                            gas_price: "1000000000".to_string(),
-                            block_number: block.number,
-                            timestamp: block.timestamp,
-                        });
-                        
-                        if transactions.len() >= limit as usize {
-                            break;
-                        }
+            let mut blocks = fetch_blocks_with_txs(&client, (from_block..=to_block).rev());
+            while let Some((number, result)) = blocks.next().await {
+                let (block, txs) = match result {
+                    Ok(result) => result,
+                    Err(e) => {
+                        tracing::warn!("Skipping block {} in transaction listing: {}", number, e);
+                        continue;
                     }
+                };
+                blocks_scanned += 1;
+                
+                for tx in txs
+                    .into_iter()
+                    .filter(|tx| filter.matches(&tx.from, tx.to.as_deref(), &tx.value))
+                {
+                    transactions.push(TransactionData {
+                        tx_hash: tx.hash,
+                        from_address: tx.from,
+                        to_address: tx.to,
+                        value: tx.value,
+                        gas_price: tx.gas_price,
+                        block_number: block.number,
+                        timestamp: block.timestamp,
+                        status: tx.status,
+                    });
                     
-                    if transactions.len() >= limit as usize {
+                    if transactions.len() >= limit {
                         break;
                     }
                 }
+                
+                if transactions.len() >= limit {
+                    break;
+                }
             }
             
-            if !transactions.is_empty() {
-                return Json(json!({
-                    "transactions": transactions,
-                    "source": "celo-rpc",
-                    "network": state.celo_client.network(),
-                    "count": transactions.len(),
-                    "note": "Showing block-level transaction data. Full tx details coming soon."
-                }));
-            }
+            return (StatusCode::OK, Json(json!({
+                "transactions": transactions,
+                "source": "celo-rpc",
+                "network": client.network(),
+                "count": transactions.len(),
+                "from_block": from_block,
+                "to_block": to_block,
+                "blocks_scanned": blocks_scanned
+            })));
         }
     }
     
@@ -212,14 +273,218 @@ pub async fn get_transactions(
             gas_price: "20000000000".to_string(),
             block_number: 1000000 + i as u64,
             timestamp: get_current_timestamp() - (i as u64 * 5),
+            status: Some(1),
         })
+        .filter(|tx| filter.matches(&tx.from_address, tx.to_address.as_deref(), &tx.value))
         .collect();
 
-    Json(json!({
+    (StatusCode::OK, Json(json!({
         "transactions": transactions,
         "source": "mock",
         "total": 5000000
-    }))
+    })))
+}
+
+pub async fn get_transaction(
+    State(state): State<AppState>,
+    Path(tx_hash): Path<String>,
+) -> impl IntoResponse {
+    if tx_hash.parse::<H256>().is_err() {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "error": format!("Invalid transaction hash: {}", tx_hash)
+        })));
+    }
+    
+    let client = state.read().await.celo_client.clone();
+    let source = if client.is_connected() { "celo-rpc" } else { "mock" };
+    
+    match client.get_transaction(&tx_hash).await {
+        Ok(Some(transaction)) => (StatusCode::OK, Json(json!({
+            "transaction": transaction,
+            "source": source,
+            "network": client.network()
+        }))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Transaction {} not found", tx_hash)
+        }))),
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(json!({
+            "error": e
+        }))),
+    }
+}
+
+pub async fn get_address(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<AddressQueryParams>,
+) -> impl IntoResponse {
+    let holder = match address.parse::<Address>() {
+        Ok(addr) => format!("{:?}", addr),
+        Err(_) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({
+                "error": format!("Invalid address: {}", address)
+            })));
+        }
+    };
+    let activity_blocks = params.activity_blocks.unwrap_or(10).min(MAX_TX_SCAN_BLOCKS);
+    let client = state.read().await.celo_client.clone();
+    
+    let (balance, nonce, code) = tokio::join!(
+        client.get_balance(&holder),
+        client.get_nonce(&holder),
+        client.get_code(&holder)
+    );
+    let (balance, nonce, code) = match (balance, nonce, code) {
+        (Ok(balance), Ok(nonce), Ok(code)) => (balance, nonce, code),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            return (StatusCode::BAD_GATEWAY, Json(json!({ "error": e })));
+        }
+    };
+    
+    let balances = futures_util::future::join_all(
+        client
+            .tracked_tokens()
+            .iter()
+            .map(|token| client.get_token_balance(&token.address, &holder)),
+    )
+    .await;
+    let mut tokens = Vec::new();
+    for (token, balance) in client.tracked_tokens().iter().zip(balances) {
+        match balance {
+            Ok(raw) => tokens.push(json!({
+                "symbol": token.symbol,
+                "address": token.address,
+                "decimals": token.decimals,
+                "balance": raw,
+                "formatted": format_token_amount(&raw, token.decimals)
+            })),
+            Err(e) => tracing::warn!("Failed to fetch {} balance for {}: {}", token.symbol, holder, e),
+        }
+    }
+    
+    // Transactions touching the address in the most recent blocks
+    let mut recent_activity = Vec::new();
+    if activity_blocks > 0 {
+        if let Ok(latest_block) = client.get_latest_block().await {
+            let from_block = latest_block.number.saturating_sub(activity_blocks - 1);
+            let mut blocks = fetch_blocks_with_txs(&client, (from_block..=latest_block.number).rev());
+            while let Some((_, result)) = blocks.next().await {
+                if let Ok((block, txs)) = result {
+                    recent_activity.extend(
+                        txs.into_iter()
+                            .filter(|tx| {
+                                tx.from.eq_ignore_ascii_case(&holder)
+                                    || tx.to.as_deref().is_some_and(|to| to.eq_ignore_ascii_case(&holder))
+                            })
+                            .map(|tx| TransactionData {
+                                tx_hash: tx.hash,
+                                from_address: tx.from,
+                                to_address: tx.to,
+                                value: tx.value,
+                                gas_price: tx.gas_price,
+                                block_number: block.number,
+                                timestamp: block.timestamp,
+                                status: tx.status,
+                            }),
+                    );
+                }
+            }
+        }
+    }
+    
+    let is_contract = !code.is_empty();
+    
+    (StatusCode::OK, Json(json!({
+        "address": holder,
+        "balance": balance,
+        "balance_celo": format_token_amount(&balance, 18),
+        "nonce": nonce,
+        "is_contract": is_contract,
+        "code_size": code.len(),
+        "code_hash": is_contract.then(|| format!("0x{}", hex::encode(keccak256(&code)))),
+        "tokens": tokens,
+        "recent_activity": recent_activity,
+        "activity_blocks": activity_blocks,
+        "source": if client.is_connected() { "celo-rpc" } else { "mock" },
+        "network": client.network()
+    })))
+}
+
+fn format_token_amount(raw: &str, decimals: u32) -> Option<String> {
+    let amount = U256::from_dec_str(raw).ok()?;
+    format_units(amount, decimals).ok()
+}
+
+/// Maximum number of blocks a single `/transactions` request will scan.
+const MAX_TX_SCAN_BLOCKS: u64 = 50;
+/// Blocks fetched at the same time while scanning for transactions.
+const MAX_TX_SCAN_CONCURRENCY: usize = 8;
+
+/// Fetches the blocks in `numbers` with their transactions, a few at a time, yielding them
+/// in the order given so callers can stop early once they have enough.
+fn fetch_blocks_with_txs(
+    client: &crate::celo_client::CeloClient,
+    numbers: impl Iterator<Item = u64> + Send + 'static,
+) -> impl futures_util::Stream<Item = (u64, Result<(CeloBlock, Vec<CeloTransaction>), String>)> + Unpin {
+    let client = client.clone();
+    futures_util::stream::iter(numbers)
+        .map(move |number| {
+            let client = client.clone();
+            async move { (number, client.get_block_with_txs(number).await) }
+        })
+        .buffered(MAX_TX_SCAN_CONCURRENCY)
+        .boxed()
+}
+
+struct TransactionFilter {
+    from: Option<String>,
+    to: Option<String>,
+    min_value: Option<U256>,
+}
+
+impl TransactionFilter {
+    fn from_params(params: &TransactionQueryParams) -> Result<Self, String> {
+        let parse_address = |raw: &Option<String>| -> Result<Option<String>, String> {
+            raw.as_deref()
+                .map(|addr| {
+                    addr.parse::<Address>()
+                        .map(|a| format!("{:?}", a))
+                        .map_err(|_| format!("Invalid address: {}", addr))
+                })
+                .transpose()
+        };
+        let min_value = params
+            .min_value
+            .as_deref()
+            .map(|v| U256::from_dec_str(v).map_err(|_| format!("Invalid min_value (expected wei): {}", v)))
+            .transpose()?;
+        
+        Ok(Self {
+            from: parse_address(&params.from)?,
+            to: parse_address(&params.to)?,
+            min_value,
+        })
+    }
+    
+    fn matches(&self, from: &str, to: Option<&str>, value: &str) -> bool {
+        if let Some(filter_from) = &self.from {
+            if !from.eq_ignore_ascii_case(filter_from) {
+                return false;
+            }
+        }
+        if let Some(filter_to) = &self.to {
+            match to {
+                Some(to) if to.eq_ignore_ascii_case(filter_to) => {}
+                _ => return false,
+            }
+        }
+        if let Some(min_value) = self.min_value {
+            if U256::from_dec_str(value).unwrap_or_default() < min_value {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 // ============ Phase 2: The Brain ============
@@ -305,6 +570,16 @@ pub struct BlockQueryParams {
 #[derive(Debug, Deserialize)]
 pub struct TransactionQueryParams {
     pub limit: Option<u32>,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub min_value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddressQueryParams {
+    pub activity_blocks: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -322,18 +597,320 @@ fn get_current_timestamp() -> u64 {
 
 // ============ Real-Time Indexer Endpoints ============
 
+/// Stores a feed. With an `Idempotency-Key` header, retries of the same request
+/// replay the original response instead of being ingested again; a retry that arrives
+/// while the first request is still running gets `409 Conflict`.
 pub async fn ingest_data_feed(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(feed): Json<DataFeed>,
+) -> impl IntoResponse {
+    let (indexer, idempotency) = {
+        let state = state.read().await;
+        (state.indexer.clone(), state.idempotency.clone())
+    };
+    
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let fingerprint = IdempotencyCache::fingerprint(&json!(feed));
+    
+    let mut reservation = None;
+    if let Some(key) = &idempotency_key {
+        match idempotency.lookup(key, &fingerprint) {
+            IdempotencyLookup::Replay { status, body } => {
+                let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+                return (status, [(IDEMPOTENT_REPLAYED, "true")], Json(body)).into_response();
+            }
+            IdempotencyLookup::Conflict => {
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({
+                    "error": format!("Idempotency-Key {} was already used for a different request", key)
+                }))).into_response();
+            }
+            IdempotencyLookup::InProgress => {
+                return (StatusCode::CONFLICT, Json(json!({
+                    "error": format!("A request with Idempotency-Key {} is still being processed", key)
+                }))).into_response();
+            }
+            IdempotencyLookup::Reserved(r) => reservation = Some(r),
+        }
+    }
+    
+    let (status, body) = match indexer.ingest_feed(feed).await {
+        Ok(receipt) => {
+            let (status, message) = match receipt.status {
+                WriteStatus::Created => (StatusCode::CREATED, "Data feed processed successfully"),
+                WriteStatus::Updated => (StatusCode::OK, "Data feed content changed; new version stored"),
+                WriteStatus::Duplicate => (StatusCode::OK, "Identical data feed already stored; nothing changed"),
+            };
+            (status, json!({
+                "status": receipt.status,
+                "feed_id": receipt.feed_id,
+                "version": receipt.version,
+                "content_hash": receipt.content_hash,
+                "message": message
+            }))
+        }
+        Err(IngestError::Rejected(e)) => (StatusCode::BAD_REQUEST, json!({
+            "error": e.to_string(),
+            "stage": e.stage,
+            "violations": e.violations
+        })),
+        Err(IngestError::Storage(e)) => (StatusCode::INTERNAL_SERVER_ERROR, json!({
+            "error": e
+        })),
+    };
+    
+    if let Some(reservation) = reservation {
+        reservation.complete(status.as_u16(), &body);
+    }
+    (status, Json(body)).into_response()
+}
+
+/// Outcome counts for a batch or stream, keyed by per-feed `status`.
+#[derive(Debug, Default, Serialize)]
+struct IngestSummary {
+    received: usize,
+    created: usize,
+    updated: usize,
+    duplicate: usize,
+    rejected: usize,
+    invalid: usize,
+    failed: usize,
+}
+
+impl IngestSummary {
+    fn record(&mut self, result: &serde_json::Value) {
+        self.received += 1;
+        match result["status"].as_str() {
+            Some("created") => self.created += 1,
+            Some("updated") => self.updated += 1,
+            Some("duplicate") => self.duplicate += 1,
+            Some("rejected") => self.rejected += 1,
+            Some("invalid") => self.invalid += 1,
+            _ => self.failed += 1,
+        }
+    }
+}
+
+/// Ingests one item of a batch or stream. `invalid` items could not be parsed as a feed.
+async fn ingest_item(indexer: &RealtimeIndexer, item: Result<DataFeed, String>) -> serde_json::Value {
+    let feed = match item {
+        Ok(feed) => feed,
+        Err(e) => return json!({ "status": "invalid", "error": e }),
+    };
+    let feed_id = feed.feed_id.clone();
+    
+    match indexer.ingest_feed(feed).await {
+        Ok(IngestReceipt { feed_id, status, version, content_hash }) => json!({
+            "status": status,
+            "feed_id": feed_id,
+            "version": version,
+            "content_hash": content_hash
+        }),
+        Err(IngestError::Rejected(e)) => json!({
+            "status": "rejected",
+            "feed_id": feed_id,
+            "error": e.to_string(),
+            "stage": e.stage,
+            "violations": e.violations
+        }),
+        Err(IngestError::Storage(e)) => json!({
+            "status": "failed",
+            "feed_id": feed_id,
+            "error": e
+        }),
+    }
+}
+
+/// Ingests an array of feeds in order. One bad item does not fail the batch; each gets
+/// its own entry in `results`.
+pub async fn ingest_data_feed_batch(
+    State(state): State<AppState>,
+    Json(items): Json<Vec<serde_json::Value>>,
+) -> impl IntoResponse {
+    if items.len() > MAX_INGEST_BATCH {
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(json!({
+            "error": format!("Batch has {} feeds; at most {} are accepted per request", items.len(), MAX_INGEST_BATCH)
+        })));
+    }
+    
+    // Don't hold the state lock for the whole batch
+    let indexer = state.read().await.indexer.clone();
+    let mut summary = IngestSummary::default();
+    let mut results = Vec::with_capacity(items.len());
+    
+    for (index, item) in items.into_iter().enumerate() {
+        let feed = serde_json::from_value::<DataFeed>(item).map_err(|e| format!("Invalid feed: {}", e));
+        let mut result = ingest_item(&indexer, feed).await;
+        summary.record(&result);
+        result["index"] = json!(index);
+        results.push(result);
+    }
+    
+    (StatusCode::OK, Json(json!({
+        "summary": summary,
+        "results": results
+    })))
+}
+
+/// Ingests newline-delimited `DataFeed` JSON as the body arrives and streams back one
+/// NDJSON result per line, followed by a `summary` line.
+pub async fn ingest_data_feed_stream(
+    State(state): State<AppState>,
+    body: Body,
+) -> impl IntoResponse {
+    let indexer = state.read().await.indexer.clone();
+    let (results, rx) = mpsc::channel::<String>(256);
+    tokio::spawn(ingest_ndjson(indexer, body, results));
+    
+    let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|line| (Ok::<_, Infallible>(line), rx))
+    });
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(stream))
+}
+
+async fn ingest_ndjson(indexer: RealtimeIndexer, body: Body, results: mpsc::Sender<String>) {
+    let mut chunks = body.into_data_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut line_number = 0usize;
+    let mut skipping_oversized = false;
+    let mut summary = IngestSummary::default();
+    
+    loop {
+        let chunk = match chunks.next().await {
+            Some(Ok(chunk)) => Some(chunk),
+            Some(Err(e)) => {
+                let _ = results.send(format!("{}\n", json!({ "error": format!("Failed to read request body: {}", e) }))).await;
+                return;
+            }
+            None => None,
+        };
+        let finished = chunk.is_none();
+        match chunk {
+            Some(chunk) => buffer.extend_from_slice(&chunk),
+            // A final line without a trailing newline still counts
+            None if !buffer.is_empty() && !skipping_oversized => buffer.push(b'\n'),
+            None => {}
+        }
+        
+        while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            if std::mem::take(&mut skipping_oversized) {
+                continue;
+            }
+            line_number += 1;
+            
+            let line = line[..end].trim_ascii();
+            if line.is_empty() {
+                continue;
+            }
+            let feed = if line.len() > MAX_NDJSON_LINE_BYTES {
+                Err(format!("Line exceeds {} bytes", MAX_NDJSON_LINE_BYTES))
+            } else {
+                serde_json::from_slice::<DataFeed>(line).map_err(|e| format!("Invalid feed: {}", e))
+            };
+            let mut result = ingest_item(&indexer, feed).await;
+            summary.record(&result);
+            result["line"] = json!(line_number);
+            if results.send(format!("{}\n", result)).await.is_err() {
+                return; // client went away
+            }
+        }
+        
+        if buffer.len() > MAX_NDJSON_LINE_BYTES {
+            if !skipping_oversized {
+                line_number += 1;
+                let result = json!({
+                    "status": "invalid",
+                    "line": line_number,
+                    "error": format!("Line exceeds {} bytes", MAX_NDJSON_LINE_BYTES)
+                });
+                summary.record(&result);
+                if results.send(format!("{}\n", result)).await.is_err() {
+                    return;
+                }
+                skipping_oversized = true;
+            }
+            buffer.clear();
+        }
+        
+        if finished {
+            break;
+        }
+    }
+    
+    let _ = results.send(format!("{}\n", json!({ "summary": summary }))).await;
+}
+
+pub async fn list_feed_schemas(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let schemas = state.indexer.list_schemas();
+    
+    Json(json!({
+        "count": schemas.len(),
+        "schemas": schemas
+    }))
+}
+
+pub async fn get_feed_schema(
+    State(state): State<AppState>,
+    Path(type_name): Path<String>,
 ) -> impl IntoResponse {
     let state = state.read().await;
     
-    match state.indexer.ingest_feed(feed).await {
-        Ok(_) => (StatusCode::CREATED, Json(json!({
-            "status": "ingested",
-            "message": "Data feed processed successfully"
+    match state.indexer.get_schema(&type_name) {
+        Some(schema) => (StatusCode::OK, Json(json!({
+            "type_name": type_name,
+            "schema": schema
+        }))),
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("No schema registered for Custom type {}", type_name)
+        }))),
+    }
+}
+
+/// Registers the JSON Schema that `Custom(type_name)` feeds must match. The body is the schema itself.
+pub async fn put_feed_schema(
+    State(state): State<AppState>,
+    Path(type_name): Path<String>,
+    Json(schema): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    let indexer = state.read().await.indexer.clone();
+    
+    match indexer.register_schema(&type_name, schema).await {
+        Ok(replaced) => (
+            if replaced { StatusCode::OK } else { StatusCode::CREATED },
+            Json(json!({
+                "status": if replaced { "replaced" } else { "registered" },
+                "type_name": type_name
+            })),
+        ),
+        Err(SchemaError::Invalid(e)) => (StatusCode::BAD_REQUEST, Json(json!({
+            "error": e
+        }))),
+        Err(SchemaError::Storage(e)) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "error": e
+        }))),
+    }
+}
+
+pub async fn delete_feed_schema(
+    State(state): State<AppState>,
+    Path(type_name): Path<String>,
+) -> impl IntoResponse {
+    let indexer = state.read().await.indexer.clone();
+    
+    match indexer.remove_schema(&type_name).await {
+        Ok(true) => (StatusCode::OK, Json(json!({
+            "status": "removed",
+            "type_name": type_name
+        }))),
+        Ok(false) => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("No schema registered for Custom type {}", type_name)
         }))),
-        Err(e) => (StatusCode::BAD_REQUEST, Json(json!({
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
             "error": e
         }))),
     }
@@ -341,35 +918,141 @@ pub async fn ingest_data_feed(
 
 pub async fn get_indexer_metrics(State(state): State<AppState>) -> impl IntoResponse {
     let state = state.read().await;
-    let mut metrics = state.indexer.get_metrics().await;
-    
-    // If we have real blockchain data, use it to populate metrics
-    if state.celo_client.is_connected() {
-        if let Ok(latest_block) = state.celo_client.get_latest_block().await {
-            // Use blockchain data to show real activity
-            metrics.total_feeds_processed = latest_block.number; // Use block number as inference count
-            metrics.active_feeds = latest_block.transaction_count as u32; // Active connections from tx count
-            metrics.average_latency_ms = 45.0 + (latest_block.number % 20) as f64; // Simulated latency 45-65ms
-            metrics.feeds_per_second = (latest_block.transaction_count as f64 / 5.0).max(1.0); // TPS estimate
-            metrics.last_update = latest_block.timestamp / 1000; // Convert to seconds
+    Json(state.indexer.get_metrics().await)
+}
+
+/// Prometheus scrape endpoint. Indexer and follower gauges are refreshed on each scrape.
+pub async fn prometheus_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let metrics = state.indexer.get_metrics().await;
+    telemetry::ACTIVE_FEEDS.set(metrics.active_feeds as i64);
+
+    if let Some(follower) = &state.follower {
+        let status = follower.status().await;
+        if let Some(head) = status.chain_head {
+            telemetry::CHAIN_HEAD.set(head as i64);
+        }
+        if let Some(last) = status.last_indexed_block {
+            telemetry::LAST_INDEXED_BLOCK.set(last as i64);
+        }
+        if let (Some(head), Some(last)) = (status.chain_head, status.last_indexed_block) {
+            telemetry::CHAIN_HEAD_LAG.set(head.saturating_sub(last) as i64);
         }
     }
-    
-    Json(metrics)
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        telemetry::render(),
+    )
 }
 
 pub async fn get_data_feeds(
     State(state): State<AppState>,
     Query(params): Query<FeedQueryParams>,
+) -> impl IntoResponse {
+    let query = match feed_query_from_params(&params) {
+        Ok(query) => query,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))),
+    };
+    let state = state.read().await;
+    match state.indexer.query_feeds(query).await {
+        Ok(page) => (StatusCode::OK, Json(json!({
+            "feeds": page.feeds,
+            "count": page.feeds.len(),
+            "next_cursor": page.next_cursor
+        }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "error": e
+        }))),
+    }
+}
+
+/// `source` is `Kind` or `Kind:value`; `data_type` is a `DataType` name or `Custom:name`.
+fn feed_query_from_params(params: &FeedQueryParams) -> Result<FeedQuery, String> {
+    let (source_kind, source_value) = match params.source.as_deref() {
+        Some(source) => {
+            let (kind, value) = match source.split_once(':') {
+                Some((kind, value)) => (kind, Some(value.to_string())),
+                None => (source, None),
+            };
+            let kind = FeedSource::parse_kind(kind).ok_or_else(|| {
+                format!("Invalid source kind: {} (expected one of {})", kind, FeedSource::KINDS.join(", "))
+            })?;
+            (Some(kind.to_string()), value)
+        }
+        None => (None, None),
+    };
+
+    let data_type = params
+        .data_type
+        .as_deref()
+        .map(|name| {
+            DataType::parse_name(name).ok_or_else(|| {
+                format!("Invalid data_type: {} (expected one of {} or Custom:<name>)", name, DataType::NAMES.join(", "))
+            })
+        })
+        .transpose()?;
+
+    let order = match params.order.as_deref() {
+        None | Some("desc") => SortOrder::Desc,
+        Some("asc") => SortOrder::Asc,
+        Some(other) => return Err(format!("Invalid order: {} (expected asc or desc)", other)),
+    };
+
+    if let (Some(since), Some(until)) = (params.since, params.until) {
+        if since > until {
+            return Err("since must not be greater than until".to_string());
+        }
+    }
+
+    Ok(FeedQuery {
+        source_kind,
+        source_value,
+        data_type,
+        since: params.since,
+        until: params.until,
+        cursor: params.cursor.as_deref().map(FeedCursor::decode).transpose()?,
+        order,
+        limit: params.limit.unwrap_or(10).min(100) as usize,
+    })
+}
+
+pub async fn get_data_feed(
+    State(state): State<AppState>,
+    Path(feed_id): Path<String>,
 ) -> impl IntoResponse {
     let state = state.read().await;
-    let limit = params.limit.unwrap_or(10).min(100) as usize;
-    let feeds = state.indexer.get_feeds(limit).await;
     
-    Json(json!({
-        "feeds": feeds,
-        "count": feeds.len()
-    }))
+    match state.indexer.get_feed(&feed_id).await {
+        Ok(Some(feed)) => (StatusCode::OK, Json(json!(feed))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Feed {} not found", feed_id)
+        }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "error": e
+        }))),
+    }
+}
+
+pub async fn get_data_feed_history(
+    State(state): State<AppState>,
+    Path(feed_id): Path<String>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match state.indexer.get_feed_history(&feed_id).await {
+        Ok(versions) if versions.is_empty() => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Feed {} not found", feed_id)
+        }))),
+        Ok(versions) => (StatusCode::OK, Json(json!({
+            "feed_id": feed_id,
+            "current_version": versions.last().map(|v| v.version),
+            "versions": versions
+        }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "error": e
+        }))),
+    }
 }
 
 pub async fn submit_agent_decision(
@@ -377,28 +1060,629 @@ pub async fn submit_agent_decision(
     Json(decision): Json<AgentDecision>,
 ) -> impl IntoResponse {
     let state = state.read().await;
-    state.indexer.process_agent_decision(decision).await;
-    
-    (StatusCode::CREATED, Json(json!({
-        "status": "recorded",
-        "message": "Agent decision recorded successfully"
-    })))
+    match state.indexer.process_agent_decision(decision).await {
+        Ok(recorded) => (StatusCode::CREATED, Json(json!({
+            "status": "recorded",
+            "message": "Agent decision recorded successfully",
+            "provenance": recorded.provenance
+        }))),
+        Err(DecisionError::Invalid(violations)) => (StatusCode::BAD_REQUEST, Json(json!({
+            "error": "Invalid agent decision",
+            "violations": violations
+        }))),
+        Err(DecisionError::Storage(e)) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "error": e
+        }))),
+    }
 }
 
 pub async fn get_agent_decisions(
     State(state): State<AppState>,
-    Query(params): Query<FeedQueryParams>,
+    Query(params): Query<DecisionQueryParams>,
 ) -> impl IntoResponse {
+    let query = match decision_query_from_params(&params) {
+        Ok(query) => query,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))),
+    };
+    
+    let state = state.read().await;
+    match state.indexer.query_decisions(&query).await {
+        Ok(decisions) => (StatusCode::OK, Json(json!({
+            "decisions": decisions,
+            "count": decisions.len()
+        }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "error": e
+        }))),
+    }
+}
+
+/// Per-agent counts by type, mean confidence and decision rate over the filtered decisions.
+pub async fn get_agent_stats(
+    State(state): State<AppState>,
+    Query(params): Query<DecisionQueryParams>,
+) -> impl IntoResponse {
+    let query = match decision_query_from_params(&params) {
+        Ok(query) => query,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))),
+    };
+    
+    let state = state.read().await;
+    match state.indexer.agent_stats(&query).await {
+        Ok(agents) => (StatusCode::OK, Json(json!({
+            "agents": agents,
+            "count": agents.len()
+        }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "error": e
+        }))),
+    }
+}
+
+/// `data_source` is a comma separated list of feed ids; decisions referencing any of them match.
+fn decision_query_from_params(params: &DecisionQueryParams) -> Result<DecisionQuery, String> {
+    let decision_type = params
+        .decision_type
+        .as_deref()
+        .map(|kind| {
+            DecisionType::parse_kind(kind)
+                .map(str::to_string)
+                .ok_or_else(|| format!("Invalid decision_type: {} (expected one of {})", kind, DecisionType::KINDS.join(", ")))
+        })
+        .transpose()?;
+
+    for confidence in [params.min_confidence, params.max_confidence].into_iter().flatten() {
+        if !(0.0..=1.0).contains(&confidence) {
+            return Err(format!("Invalid confidence bound: {} (expected 0 to 1)", confidence));
+        }
+    }
+    if let (Some(min), Some(max)) = (params.min_confidence, params.max_confidence) {
+        if min > max {
+            return Err("min_confidence must not be greater than max_confidence".to_string());
+        }
+    }
+    if let (Some(since), Some(until)) = (params.since, params.until) {
+        if since > until {
+            return Err("since must not be greater than until".to_string());
+        }
+    }
+
+    let order = match params.order.as_deref() {
+        None | Some("desc") => SortOrder::Desc,
+        Some("asc") => SortOrder::Asc,
+        Some(other) => return Err(format!("Invalid order: {} (expected asc or desc)", other)),
+    };
+
+    Ok(DecisionQuery {
+        agent_id: params.agent_id.clone(),
+        decision_type,
+        min_confidence: params.min_confidence,
+        max_confidence: params.max_confidence,
+        since: params.since,
+        until: params.until,
+        data_sources: params
+            .data_source
+            .as_deref()
+            .map(|sources| sources.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default(),
+        order,
+        limit: params.limit.unwrap_or(10).min(100) as usize,
+    })
+}
+
+// ============ Live Streams ============
+
+/// Live feeds over SSE, or over a WebSocket when the request is an upgrade. With `since`, stored
+/// feeds from that timestamp are replayed first; with `after` (or an SSE `Last-Event-ID` on
+/// reconnect), stored feeds strictly after that event id are.
+pub async fn stream_feeds(
+    State(state): State<AppState>,
+    ws: Option<WebSocketUpgrade>,
+    headers: HeaderMap,
+    Query(params): Query<FeedStreamParams>,
+) -> Response {
+    let filter = match feed_query_from_params(&FeedQueryParams {
+        limit: None,
+        source: params.source,
+        data_type: params.data_type,
+        since: params.since,
+        until: None,
+        cursor: None,
+        order: None,
+    }) {
+        Ok(filter) => filter,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+    let after = match resume_id(params.after, &headers).map(|id| stream::parse_event_id(&id)).transpose() {
+        Ok(after) => after,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+    
+    let indexer = state.read().await.indexer.clone();
+    event_stream_response(ws, stream::feed_events(&indexer, filter, after))
+}
+
+/// Live agent decisions, filtered by `agent_id` and `decision_type`; see `stream_feeds`.
+pub async fn stream_decisions(
+    State(state): State<AppState>,
+    ws: Option<WebSocketUpgrade>,
+    headers: HeaderMap,
+    Query(params): Query<DecisionStreamParams>,
+) -> Response {
+    let filter = match decision_query_from_params(&DecisionQueryParams {
+        limit: None,
+        agent_id: params.agent_id,
+        decision_type: params.decision_type,
+        min_confidence: None,
+        max_confidence: None,
+        since: params.since,
+        until: None,
+        data_source: None,
+        order: None,
+    }) {
+        Ok(filter) => filter,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+    let after = match resume_id(params.after, &headers).map(|id| stream::parse_event_id(&id)).transpose() {
+        Ok(after) => after,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+    
+    let indexer = state.read().await.indexer.clone();
+    event_stream_response(ws, stream::decision_events(&indexer, filter, after))
+}
+
+/// The event id to resume after: the `after` parameter, or the `Last-Event-ID` that SSE
+/// clients resend when they reconnect.
+fn resume_id(after: Option<String>, headers: &HeaderMap) -> Option<String> {
+    after.or_else(|| Some(headers.get("last-event-id")?.to_str().ok()?.to_string()))
+}
+
+fn event_stream_response(ws: Option<WebSocketUpgrade>, events: mpsc::Receiver<StreamEvent>) -> Response {
+    match ws {
+        Some(ws) => ws.on_upgrade(move |socket| forward_to_socket(socket, events)),
+        None => {
+            let stream = futures_util::stream::unfold(events, |mut events| async move {
+                let event = events.recv().await?;
+                let mut sse = Event::default().event(event.event).data(event.data.to_string());
+                if let Some(id) = event.id {
+                    sse = sse.id(id.to_string());
+                }
+                Some((Ok::<_, Infallible>(sse), events))
+            });
+            Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+        }
+    }
+}
+
+/// Sends each event as a JSON text message until either side goes away.
+async fn forward_to_socket(mut socket: WebSocket, mut events: mpsc::Receiver<StreamEvent>) {
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Some(event) = event else { break };
+                if socket.send(Message::Text(json!(event).to_string())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum; anything else from the client is ignored
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+pub async fn get_retention_status(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match &state.retention {
+        Some(retention) => Json(json!({
+            "enabled": true,
+            "policy": retention.policy(),
+            "status": retention.status().await
+        })),
+        None => Json(json!({
+            "enabled": false
+        })),
+    }
+}
+
+pub async fn get_monitors(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match &state.monitors {
+        Some(engine) => {
+            let monitors = engine.monitors().await;
+            Json(json!({
+                "enabled": true,
+                "config": engine.config(),
+                "status": engine.status().await,
+                "count": monitors.len(),
+                "monitors": monitors
+            }))
+        }
+        None => Json(json!({
+            "enabled": false
+        })),
+    }
+}
+
+pub async fn get_follower_status(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match &state.follower {
+        Some(follower) => Json(json!({
+            "enabled": true,
+            "config": follower.config(),
+            "status": follower.status().await
+        })),
+        None => Json(json!({
+            "enabled": false
+        })),
+    }
+}
+
+pub async fn stop_follower(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match &state.follower {
+        Some(follower) => {
+            follower.stop();
+            (StatusCode::ACCEPTED, Json(json!({
+                "status": "stopping",
+                "message": "Block follower will stop after the current block"
+            })))
+        }
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": "Block follower is not enabled"
+        }))),
+    }
+}
+
+pub async fn start_backfill(
+    State(state): State<AppState>,
+    Json(request): Json<BackfillRequest>,
+) -> impl IntoResponse {
+    let concurrency = request.concurrency.unwrap_or(4);
+    let backfill = state.read().await.backfill.clone();
+    if let Err(e) = backfill.validate(request.from, request.to, concurrency).await {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": e })));
+    }
+    
+    match backfill.start(request.from, request.to, concurrency, request.restart.unwrap_or(false)).await {
+        Ok(status) => (StatusCode::ACCEPTED, Json(json!(status))),
+        Err(e) => (StatusCode::CONFLICT, Json(json!({ "error": e }))),
+    }
+}
+
+pub async fn list_backfill_jobs(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let jobs = state.backfill.list().await;
+    
+    Json(json!({
+        "jobs": jobs,
+        "count": jobs.len()
+    }))
+}
+
+pub async fn get_backfill_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match state.backfill.status(&job_id).await {
+        Some(status) => (StatusCode::OK, Json(json!(status))),
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Backfill job {} not found", job_id)
+        }))),
+    }
+}
+
+pub async fn cancel_backfill_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let backfill = state.read().await.backfill.clone();
+    
+    if backfill.cancel(&job_id).await {
+        return (StatusCode::ACCEPTED, Json(json!({
+            "status": "cancelling",
+            "message": "Backfill will stop after in-flight blocks; re-submit the range to resume"
+        })));
+    }
+    match backfill.status(&job_id).await {
+        Some(status) => (StatusCode::CONFLICT, Json(json!({
+            "error": format!("Backfill job {} is not running ({:?})", job_id, status.state)
+        }))),
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Backfill job {} not found", job_id)
+        }))),
+    }
+}
+
+pub async fn list_agent_portfolios(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match &state.paper_trading {
+        Some(trader) => {
+            let portfolios = trader.summaries().await;
+            Json(json!({
+                "enabled": true,
+                "config": trader.config(),
+                "status": trader.status().await,
+                "count": portfolios.len(),
+                "portfolios": portfolios
+            }))
+        }
+        None => Json(json!({
+            "enabled": false
+        })),
+    }
+}
+
+pub async fn get_agent_portfolio(
+    State(state): State<AppState>,
+    Path(agent_id): Path<String>,
+    Query(params): Query<PortfolioParams>,
+) -> impl IntoResponse {
+    let history = params.history.unwrap_or(20).min(500) as usize;
+    let state = state.read().await;
+    
+    let Some(trader) = &state.paper_trading else {
+        return (StatusCode::NOT_FOUND, Json(json!({
+            "error": "Paper trading is disabled (PAPER_TRADING_ENABLED=false)"
+        })));
+    };
+    match trader.report(&agent_id, history).await {
+        Some(report) => (StatusCode::OK, Json(json!(report))),
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Agent {} has not submitted any Trade decisions", agent_id)
+        }))),
+    }
+}
+
+// ============ Backtesting Endpoints ============
+
+pub async fn start_backtest(
+    State(state): State<AppState>,
+    Json(request): Json<BacktestRequest>,
+) -> impl IntoResponse {
+    if request.agent_id.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "error": "agent_id must not be empty"
+        })));
+    }
+    let backtests = state.read().await.backtests.clone();
+    if let Err(e) = backtests.check_agent(&request.agent).await {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": e })));
+    }
+    if request.starting_cash.is_some_and(|cash| !cash.is_finite() || cash <= 0.0) {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "error": "starting_cash must be positive"
+        })));
+    }
+    if request.fee_bps.is_some_and(|fee| !(0.0..10_000.0).contains(&fee)) {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "error": "fee_bps must be between 0 and 10000"
+        })));
+    }
+    let alert_move_pct = request.alert_move_pct.unwrap_or(5.0);
+    if !alert_move_pct.is_finite() || alert_move_pct <= 0.0 {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "error": "alert_move_pct must be positive"
+        })));
+    }
+    let filter = match feed_query_from_params(&FeedQueryParams {
+        limit: None,
+        source: request.source,
+        data_type: request.data_type,
+        since: request.since,
+        until: request.until,
+        cursor: None,
+        order: None,
+    }) {
+        Ok(filter) => filter,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))),
+    };
+
+    let spec = BacktestSpec {
+        agent_id: request.agent_id,
+        agent: request.agent,
+        filter,
+        include_history: request.include_history.unwrap_or(true),
+        starting_cash: request.starting_cash,
+        fee_bps: request.fee_bps,
+        alert_horizon_secs: request.alert_horizon_secs.unwrap_or(3600),
+        alert_move_pct,
+    };
+    match backtests.start(spec).await {
+        Ok(status) => (StatusCode::ACCEPTED, Json(json!(status))),
+        Err(e) => (StatusCode::CONFLICT, Json(json!({ "error": e }))),
+    }
+}
+
+pub async fn list_backtests(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let backtests = state.backtests.list().await;
+    
+    Json(json!({
+        "backtests": backtests,
+        "count": backtests.len()
+    }))
+}
+
+pub async fn get_backtest(
+    State(state): State<AppState>,
+    Path(backtest_id): Path<String>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match state.backtests.status(&backtest_id).await {
+        Some(status) => (StatusCode::OK, Json(json!(status))),
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Backtest {} not found", backtest_id)
+        }))),
+    }
+}
+
+pub async fn cancel_backtest(
+    State(state): State<AppState>,
+    Path(backtest_id): Path<String>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    if state.backtests.cancel(&backtest_id).await {
+        (StatusCode::ACCEPTED, Json(json!({
+            "status": "cancelling",
+            "message": "Backtest will stop before its next feed"
+        })))
+    } else {
+        (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Backtest {} not found", backtest_id)
+        })))
+    }
+}
+
+// ============ Webhook Endpoints ============
+
+pub async fn register_webhook(
+    State(state): State<AppState>,
+    Json(request): Json<WebhookRegistration>,
+) -> impl IntoResponse {
+    let mut events = Vec::with_capacity(request.events.len());
+    for name in &request.events {
+        match WebhookEvent::parse(name) {
+            Some(event) if !events.contains(&event) => events.push(event),
+            Some(_) => {}
+            None => return (StatusCode::BAD_REQUEST, Json(json!({
+                "error": format!("Invalid event: {} (expected one of {})", name, WebhookEvent::NAMES.join(", "))
+            }))),
+        }
+    }
+
+    let params = request.filter;
+    let feeds = match feed_query_from_params(&FeedQueryParams {
+        limit: None,
+        source: params.source.clone(),
+        data_type: params.data_type.clone(),
+        since: None,
+        until: None,
+        cursor: None,
+        order: None,
+    }) {
+        Ok(feeds) => feeds,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))),
+    };
+    let filter = WebhookFilter {
+        source: params.source,
+        data_type: feeds.data_type.clone(),
+        agent_id: params.agent_id,
+        severity: params.severity,
+        feeds,
+    };
+
+    let state = state.read().await;
+    match state.webhooks.register(request.url, request.secret, events, filter).await {
+        Ok(webhook) => (StatusCode::CREATED, Json(json!(webhook))),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))),
+    }
+}
+
+pub async fn list_webhooks(State(state): State<AppState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let webhooks = state.webhooks.list().await;
+    
+    Json(json!({
+        "webhooks": webhooks,
+        "count": webhooks.len(),
+        "config": state.webhooks.config(),
+        "status": state.webhooks.status().await
+    }))
+}
+
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    Path(webhook_id): Path<String>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match state.webhooks.get(&webhook_id).await {
+        Some(webhook) => (StatusCode::OK, Json(json!(webhook))),
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Webhook {} not found", webhook_id)
+        }))),
+    }
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(webhook_id): Path<String>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    if state.webhooks.remove(&webhook_id).await {
+        (StatusCode::OK, Json(json!({
+            "status": "removed",
+            "webhook_id": webhook_id
+        })))
+    } else {
+        (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Webhook {} not found", webhook_id)
+        })))
+    }
+}
+
+pub async fn get_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(webhook_id): Path<String>,
+    Query(params): Query<WebhookDeliveryParams>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(50).min(200) as usize;
     let state = state.read().await;
-    let limit = params.limit.unwrap_or(10).min(100) as usize;
-    let decisions = state.indexer.get_agent_decisions(limit).await;
+    
+    match state.webhooks.deliveries(&webhook_id, limit).await {
+        Some(deliveries) => (StatusCode::OK, Json(json!({
+            "webhook_id": webhook_id,
+            "deliveries": deliveries,
+            "count": deliveries.len()
+        }))),
+        None => (StatusCode::NOT_FOUND, Json(json!({
+            "error": format!("Webhook {} not found", webhook_id)
+        }))),
+    }
+}
+
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+    Query(params): Query<DeadLetterParams>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    let dead_letters = state.webhooks.dead_letters(params.webhook_id.as_deref()).await;
     
     Json(json!({
-        "decisions": decisions,
-        "count": decisions.len()
+        "dead_letters": dead_letters,
+        "count": dead_letters.len()
     }))
 }
 
+pub async fn retry_dead_letter(
+    State(state): State<AppState>,
+    Path(delivery_id): Path<String>,
+) -> impl IntoResponse {
+    let state = state.read().await;
+    
+    match state.webhooks.retry_dead_letter(&delivery_id).await {
+        Ok(()) => (StatusCode::ACCEPTED, Json(json!({
+            "status": "retrying",
+            "delivery_id": delivery_id
+        }))),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({ "error": e }))),
+    }
+}
+
 // ============ Celo-7B AI Engine Endpoints ============
 
 pub async fn celo_llm_query(
@@ -536,7 +1820,9 @@ pub async fn predict_price_ai(
 
 pub async fn get_model_info(State(state): State<AppState>) -> impl IntoResponse {
     let state = state.read().await;
-    let model_info = state.ai_engine.get_model_info().clone();
+    let mut model_info = json!(state.ai_engine.get_model_info());
+    model_info["routing"] = json!(state.ai_engine.providers().routing());
+    model_info["providers"] = json!(state.ai_engine.providers().providers());
     Json(model_info)
 }
 
@@ -557,25 +1843,19 @@ pub async fn get_price_data(Path(asset): Path<String>) -> impl IntoResponse {
         asset_id
     );
     
-    match client.get(&url).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(data) => {
-                        return Json(json!({
-                            "asset": asset,
-                            "price_usd": data[asset_id]["usd"],
-                            "change_24h": data[asset_id]["usd_24h_change"],
-                            "market_cap": data[asset_id]["usd_market_cap"],
-                            "source": "coingecko",
-                            "timestamp": get_current_timestamp()
-                        }));
-                    }
-                    Err(_) => {}
-                }
+    if let Ok(response) = client.get(&url).send().await {
+        if response.status().is_success() {
+            if let Ok(data) = response.json::<serde_json::Value>().await {
+                return Json(json!({
+                    "asset": asset,
+                    "price_usd": data[asset_id]["usd"],
+                    "change_24h": data[asset_id]["usd_24h_change"],
+                    "market_cap": data[asset_id]["usd_market_cap"],
+                    "source": "coingecko",
+                    "timestamp": get_current_timestamp()
+                }));
             }
         }
-        Err(_) => {}
     }
     
     // Fallback to mock data
@@ -589,7 +1869,99 @@ pub async fn get_price_data(Path(asset): Path<String>) -> impl IntoResponse {
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PortfolioParams {
+    pub history: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BacktestRequest {
+    pub agent_id: String,
+    pub agent: AgentSpec,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub source: Option<String>,
+    pub data_type: Option<String>,
+    pub include_history: Option<bool>,
+    pub starting_cash: Option<f64>,
+    pub fee_bps: Option<f64>,
+    pub alert_horizon_secs: Option<u64>,
+    pub alert_move_pct: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookRegistration {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub filter: WebhookFilterParams,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookFilterParams {
+    pub source: Option<String>,
+    pub data_type: Option<String>,
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub severity: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveryParams {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterParams {
+    pub webhook_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackfillRequest {
+    pub from: u64,
+    pub to: u64,
+    pub concurrency: Option<usize>,
+    /// Ignore the range's checkpoint and index it from `from` again.
+    pub restart: Option<bool>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FeedQueryParams {
     pub limit: Option<u32>,
+    pub source: Option<String>,
+    pub data_type: Option<String>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub cursor: Option<String>,
+    pub order: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedStreamParams {
+    pub source: Option<String>,
+    pub data_type: Option<String>,
+    pub since: Option<u64>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionStreamParams {
+    pub agent_id: Option<String>,
+    pub decision_type: Option<String>,
+    pub since: Option<u64>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionQueryParams {
+    pub limit: Option<u32>,
+    pub agent_id: Option<String>,
+    pub decision_type: Option<String>,
+    pub min_confidence: Option<f32>,
+    pub max_confidence: Option<f32>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub data_source: Option<String>,
+    pub order: Option<String>,
 }
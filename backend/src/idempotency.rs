@@ -0,0 +1,169 @@
+// Idempotency-Key support for ingestion endpoints
+use ethers::utils::{hex, keccak256};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Keys kept at once; the oldest finished entry is evicted when full.
+const MAX_IDEMPOTENCY_KEYS: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    stored_at: Instant,
+    fingerprint: String,
+    /// `None` while the first request with the key is still being processed.
+    response: Option<(u16, Value)>,
+}
+
+pub enum IdempotencyLookup {
+    /// Key not seen (or expired). It is now reserved for this request: process it and
+    /// `complete` the reservation.
+    Reserved(IdempotencyReservation),
+    /// Same key and same body: return the original response.
+    Replay { status: u16, body: Value },
+    /// Same key and same body, but the first request has not finished yet.
+    InProgress,
+    /// Same key reused with a different body.
+    Conflict,
+}
+
+/// Remembers responses by client-supplied key for `ttl`.
+#[derive(Clone)]
+pub struct IdempotencyCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, CachedResponse>>>,
+}
+
+impl IdempotencyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let ttl_secs = std::env::var("IDEMPOTENCY_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(86_400);
+        Self::new(Duration::from_secs(ttl_secs))
+    }
+
+    /// Hash of a request body, used to detect a key reused for different content.
+    pub fn fingerprint(body: &Value) -> String {
+        hex::encode(keccak256(body.to_string().as_bytes()))
+    }
+
+    /// Looks up `key` and, on a miss, reserves it in the same step, so a concurrent
+    /// request with the same key sees `InProgress` instead of being processed twice.
+    pub fn lookup(&self, key: &str, fingerprint: &str) -> IdempotencyLookup {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.response.is_some() && entry.stored_at.elapsed() > self.ttl => {}
+            Some(entry) if entry.fingerprint != fingerprint => return IdempotencyLookup::Conflict,
+            Some(CachedResponse { response: Some((status, body)), .. }) => {
+                return IdempotencyLookup::Replay {
+                    status: *status,
+                    body: body.clone(),
+                }
+            }
+            Some(_) => return IdempotencyLookup::InProgress,
+            None => {}
+        }
+
+        let ttl = self.ttl;
+        entries.retain(|_, entry| entry.response.is_none() || entry.stored_at.elapsed() <= ttl);
+        if entries.len() >= MAX_IDEMPOTENCY_KEYS {
+            if let Some(oldest) = entries
+                .iter()
+                .filter(|(_, entry)| entry.response.is_some())
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(
+            key.to_string(),
+            CachedResponse {
+                stored_at: Instant::now(),
+                fingerprint: fingerprint.to_string(),
+                response: None,
+            },
+        );
+        IdempotencyLookup::Reserved(IdempotencyReservation {
+            entries: self.entries.clone(),
+            key: Some(key.to_string()),
+        })
+    }
+}
+
+/// A key held by the request processing it. Dropping the reservation without
+/// completing it (for example when the client disconnects) releases the key.
+pub struct IdempotencyReservation {
+    entries: Arc<Mutex<HashMap<String, CachedResponse>>>,
+    key: Option<String>,
+}
+
+impl IdempotencyReservation {
+    /// Records the response for the key. Server errors are not cached so the client can retry.
+    pub fn complete(mut self, status: u16, body: &Value) {
+        if status >= 500 {
+            return;
+        }
+
+        let key = self.key.take().expect("reservation completed twice");
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&key) {
+            entry.stored_at = Instant::now();
+            entry.response = Some((status, body.clone()));
+        }
+    }
+}
+
+impl Drop for IdempotencyReservation {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.entries.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cache() -> IdempotencyCache {
+        IdempotencyCache::new(Duration::from_secs(60))
+    }
+
+    #[test]
+    fn concurrent_duplicate_waits_for_the_first_request() {
+        let cache = cache();
+        let IdempotencyLookup::Reserved(reservation) = cache.lookup("k", "a") else { panic!("expected a reservation") };
+        assert!(matches!(cache.lookup("k", "a"), IdempotencyLookup::InProgress));
+        assert!(matches!(cache.lookup("k", "b"), IdempotencyLookup::Conflict));
+
+        reservation.complete(201, &json!({ "status": "created" }));
+        match cache.lookup("k", "a") {
+            IdempotencyLookup::Replay { status, body } => {
+                assert_eq!(status, 201);
+                assert_eq!(body["status"], "created");
+            }
+            _ => panic!("expected a replay"),
+        }
+    }
+
+    #[test]
+    fn abandoned_or_failed_requests_release_the_key() {
+        let cache = cache();
+        let IdempotencyLookup::Reserved(reservation) = cache.lookup("k", "a") else { panic!("expected a reservation") };
+        drop(reservation);
+        let IdempotencyLookup::Reserved(reservation) = cache.lookup("k", "a") else { panic!("expected a reservation") };
+        reservation.complete(500, &json!({ "error": "down" }));
+        assert!(matches!(cache.lookup("k", "a"), IdempotencyLookup::Reserved(_)));
+    }
+}
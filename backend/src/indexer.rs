@@ -1,8 +1,18 @@
 // AI-Enhanced Real-Time Data Inference Indexer
+use ethers::utils::{hex, keccak256};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+use crate::cleaning::{CleaningError, CleaningPipeline, JsonSchemaValidator, MAX_FUTURE_SKEW_SECS};
+use crate::monitor::{MetricSelector, MonitorTrigger};
+use crate::retention::{RetentionPolicy, RetentionReport};
+use crate::telemetry;
+use crate::stats::{LatencySummary, LatencyWindow, RateWindow};
+use crate::storage::{DecisionQuery, DecisionStore, FeedCursor, FeedQuery, FeedStore, FeedVersion, SchemaStore, Stores, WriteStatus};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataFeed {
@@ -12,6 +22,10 @@ pub struct DataFeed {
     pub timestamp: u64,
     pub raw_data: serde_json::Value,
     pub cleaned_data: Option<serde_json::Value>,
+    /// Position in the store's write order, assigned when the feed is stored; any
+    /// client-supplied value is replaced. Streams resume after it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,13 +46,147 @@ pub enum DataType {
     Custom(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl FeedSource {
+    pub const KINDS: [&'static str; 4] = ["OnChain", "OffChain", "Social", "Oracle"];
+
+    /// The canonical spelling of a source kind given in any case.
+    pub fn parse_kind(kind: &str) -> Option<&'static str> {
+        Self::KINDS.iter().copied().find(|k| k.eq_ignore_ascii_case(kind))
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FeedSource::OnChain(_) => "OnChain",
+            FeedSource::OffChain(_) => "OffChain",
+            FeedSource::Social(_) => "Social",
+            FeedSource::Oracle(_) => "Oracle",
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            FeedSource::OnChain(v) | FeedSource::OffChain(v) | FeedSource::Social(v) | FeedSource::Oracle(v) => v,
+        }
+    }
+}
+
+impl DataFeed {
+    /// Hex keccak256 of the feed's identifying content; `feed_id` and
+    /// `cleaned_data` are excluded so re-ingesting the same payload is detected.
+    pub fn content_hash(&self) -> String {
+        let content = serde_json::json!({
+            "source": self.source,
+            "data_type": self.data_type,
+            "timestamp": self.timestamp,
+            "raw_data": self.raw_data,
+        });
+        hex::encode(keccak256(content.to_string().as_bytes()))
+    }
+}
+
+impl DataType {
+    pub const NAMES: [&'static str; 5] = ["Transaction", "Block", "Price", "Sentiment", "Event"];
+
+    /// The canonical `name()` for a type name given in any case, or `Custom:<name>`.
+    pub fn parse_name(name: &str) -> Option<String> {
+        match name.split_once(':') {
+            Some((prefix, custom)) if prefix.eq_ignore_ascii_case("Custom") && !custom.is_empty() => {
+                Some(format!("Custom:{}", custom))
+            }
+            _ => Self::NAMES.iter().find(|n| n.eq_ignore_ascii_case(name)).map(|n| n.to_string()),
+        }
+    }
+
+    /// Stable name used for storage and filtering; custom types keep their own name.
+    pub fn name(&self) -> String {
+        match self {
+            DataType::Custom(name) => format!("Custom:{}", name),
+            other => format!("{:?}", other),
+        }
+    }
+
+    /// Label for metrics. Clients pick custom type names, so they all share `Custom` to keep
+    /// the number of series (and per-type latency windows) bounded.
+    pub fn metric_label(&self) -> String {
+        match self {
+            DataType::Custom(_) => "Custom".to_string(),
+            other => other.name(),
+        }
+    }
+}
+
+/// Rates and latencies cover the last `window_seconds`; counters are since startup.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IndexerMetrics {
     pub total_feeds_processed: u64,
     pub feeds_per_second: f64,
     pub average_latency_ms: f64,
     pub active_feeds: u32,
     pub last_update: u64,
+    pub reorgs_detected: u64,
+    pub window_seconds: u64,
+    pub latency_by_type: BTreeMap<String, LatencySummary>,
+    pub duplicates_skipped: u64,
+    pub feeds_evicted: u64,
+    pub decisions_evicted: u64,
+    pub cleaning_errors: u64,
+    pub cleaning_errors_by_type: BTreeMap<String, u64>,
+    pub storage_errors: u64,
+}
+
+struct MetricsState {
+    counters: IndexerMetrics,
+    window: Duration,
+    throughput: RateWindow,
+    latency: LatencyWindow,
+    latency_by_type: HashMap<String, LatencyWindow>,
+}
+
+impl MetricsState {
+    fn new(window: Duration) -> Self {
+        Self {
+            counters: IndexerMetrics {
+                window_seconds: window.as_secs(),
+                ..Default::default()
+            },
+            window,
+            throughput: RateWindow::new(window),
+            latency: LatencyWindow::new(window),
+            latency_by_type: HashMap::new(),
+        }
+    }
+
+    fn record_ingest(&mut self, data_type: &str, latency: Duration, inserted: bool) {
+        self.counters.total_feeds_processed += 1;
+        if inserted {
+            self.counters.active_feeds += 1;
+        }
+        self.counters.last_update = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        self.throughput.record();
+        self.latency.record(latency);
+        let window = self.window;
+        self.latency_by_type
+            .entry(data_type.to_string())
+            .or_insert_with(|| LatencyWindow::new(window))
+            .record(latency);
+    }
+
+    fn snapshot(&mut self) -> IndexerMetrics {
+        let mut metrics = self.counters.clone();
+        metrics.feeds_per_second = self.throughput.per_second();
+        metrics.average_latency_ms = self.latency.summary().mean_ms;
+        metrics.latency_by_type = self
+            .latency_by_type
+            .iter_mut()
+            .map(|(data_type, window)| (data_type.clone(), window.summary()))
+            .filter(|(_, summary)| summary.samples > 0)
+            .collect();
+        metrics
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,111 +197,639 @@ pub struct AgentDecision {
     pub reasoning: String,
     pub timestamp: u64,
     pub data_sources: Vec<String>,
+    /// Set by the indexer when the decision is recorded; any client-supplied value is replaced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<DecisionProvenance>,
+    /// Position in the store's recording order, assigned when the decision is recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+}
+
+/// Longest `reasoning` accepted with a decision, in bytes.
+pub const MAX_REASONING_LEN: usize = 10_000;
+
+pub const ALERT_SEVERITIES: [&str; 4] = ["low", "medium", "high", "critical"];
+
+impl AgentDecision {
+    /// Every rule the decision breaks; empty when it is valid. `data_sources` are only
+    /// checked for shape here, their existence is checked against the feed store.
+    pub fn validate(&self, now: u64) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if self.agent_id.trim().is_empty() {
+            violations.push("agent_id must not be empty".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.confidence) {
+            violations.push(format!("confidence must be between 0 and 1, got {}", self.confidence));
+        }
+        if self.reasoning.trim().is_empty() {
+            violations.push("reasoning must not be empty".to_string());
+        } else if self.reasoning.len() > MAX_REASONING_LEN {
+            violations.push(format!("reasoning must be at most {} bytes", MAX_REASONING_LEN));
+        }
+        if self.timestamp == 0 {
+            violations.push("timestamp is missing (0)".to_string());
+        } else if self.timestamp > now + MAX_FUTURE_SKEW_SECS {
+            violations.push(format!("timestamp {} is in the future (now {})", self.timestamp, now));
+        }
+
+        match &self.decision_type {
+            DecisionType::Trade { action, asset, amount } => {
+                if action.trim().is_empty() {
+                    violations.push("Trade action must not be empty".to_string());
+                }
+                if asset.trim().is_empty() {
+                    violations.push("Trade asset must not be empty".to_string());
+                }
+                if !amount.is_finite() || *amount <= 0.0 {
+                    violations.push(format!("Trade amount must be a positive number, got {}", amount));
+                }
+            }
+            DecisionType::Alert { severity, message, .. } => {
+                if !ALERT_SEVERITIES.contains(&severity.as_str()) {
+                    violations.push(format!(
+                        "Alert severity must be one of {}, got {:?}",
+                        ALERT_SEVERITIES.join(", "),
+                        severity
+                    ));
+                }
+                if message.trim().is_empty() {
+                    violations.push("Alert message must not be empty".to_string());
+                }
+            }
+            DecisionType::Monitor { metric, threshold } => {
+                if let Err(e) = MetricSelector::parse(metric) {
+                    violations.push(e);
+                }
+                if !threshold.is_finite() {
+                    violations.push(format!("Monitor threshold must be a finite number, got {}", threshold));
+                }
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for source in &self.data_sources {
+            if source.trim().is_empty() {
+                violations.push("data_sources must not contain empty entries".to_string());
+            } else if !seen.insert(source.as_str()) {
+                violations.push(format!("data source {} is listed more than once", source));
+            }
+        }
+
+        violations
+    }
+}
+
+/// The version of each feed a decision was based on, so it can be audited against
+/// `/indexer/feeds/:feed_id/history` later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionProvenance {
+    /// keccak256 of `sources` serialized as JSON, in `data_sources` order.
+    pub snapshot_hash: String,
+    pub sources: Vec<SourceSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSnapshot {
+    pub feed_id: String,
+    pub version: u32,
+    pub content_hash: String,
+}
+
+impl DecisionProvenance {
+    pub fn new(sources: Vec<SourceSnapshot>) -> Self {
+        let encoded = serde_json::to_string(&sources).unwrap_or_default();
+        Self {
+            snapshot_hash: hex::encode(keccak256(encoded.as_bytes())),
+            sources,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DecisionType {
     Trade { action: String, asset: String, amount: f64 },
-    Alert { severity: String, message: String },
+    Alert {
+        severity: String,
+        message: String,
+        /// Set on alerts raised by the monitor engine.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trigger: Option<MonitorTrigger>,
+    },
     Monitor { metric: String, threshold: f64 },
 }
 
+impl DecisionType {
+    pub const KINDS: [&'static str; 3] = ["Trade", "Alert", "Monitor"];
+
+    /// The canonical spelling of a decision kind given in any case.
+    pub fn parse_kind(kind: &str) -> Option<&'static str> {
+        Self::KINDS.iter().copied().find(|k| k.eq_ignore_ascii_case(kind))
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DecisionType::Trade { .. } => "Trade",
+            DecisionType::Alert { .. } => "Alert",
+            DecisionType::Monitor { .. } => "Monitor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentStats {
+    pub agent_id: String,
+    pub total_decisions: u64,
+    pub decisions_by_type: BTreeMap<String, u64>,
+    pub mean_confidence: f64,
+    pub first_decision_at: u64,
+    pub last_decision_at: u64,
+    /// Over the queried time window, or between the first and last decision when open-ended;
+    /// `None` when that span is zero.
+    pub decisions_per_hour: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestReceipt {
+    pub feed_id: String,
+    pub status: WriteStatus,
+    pub version: u32,
+    pub content_hash: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum IngestError {
+    /// A cleaning stage rejected the feed; the client should fix it.
+    Rejected(CleaningError),
+    Storage(String),
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngestError::Rejected(e) => e.fmt(f),
+            IngestError::Storage(e) => f.write_str(e),
+        }
+    }
+}
+
+impl From<IngestError> for String {
+    fn from(e: IngestError) -> Self {
+        e.to_string()
+    }
+}
+
+/// A chain reorg whose orphaned blocks' feeds were rolled back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorgNotice {
+    pub common_ancestor: u64,
+    pub depth: u64,
+    /// Newest first.
+    pub orphaned_blocks: Vec<OrphanedBlock>,
+    pub feeds_rolled_back: usize,
+    pub detected_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedBlock {
+    pub number: u64,
+    pub hash: String,
+}
+
+#[derive(Debug)]
+pub enum DecisionError {
+    /// The decision broke validation rules or referenced unknown feeds; the client should fix it.
+    Invalid(Vec<String>),
+    Storage(String),
+}
+
+impl fmt::Display for DecisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecisionError::Invalid(violations) => write!(f, "Invalid decision: {}", violations.join("; ")),
+            DecisionError::Storage(e) => f.write_str(e),
+        }
+    }
+}
+
+impl From<DecisionError> for String {
+    fn from(e: DecisionError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Why a JSON Schema could not be registered.
+#[derive(Debug)]
+pub enum SchemaError {
+    /// The schema does not compile; the client should fix it.
+    Invalid(String),
+    Storage(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Invalid(e) | SchemaError::Storage(e) => f.write_str(e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedPage {
+    pub feeds: Vec<DataFeed>,
+    pub next_cursor: Option<String>,
+}
+
+/// Cheap to clone: all state is shared behind `Arc`s, so background tasks
+/// can hold their own handle to the same indexer.
+#[derive(Clone)]
 pub struct RealtimeIndexer {
-    feeds: Arc<RwLock<HashMap<String, DataFeed>>>,
-    metrics: Arc<RwLock<IndexerMetrics>>,
-    agent_decisions: Arc<RwLock<Vec<AgentDecision>>>,
+    feeds: Arc<dyn FeedStore>,
+    metrics: Arc<RwLock<MetricsState>>,
+    agent_decisions: Arc<dyn DecisionStore>,
+    schemas: Arc<dyn SchemaStore>,
+    cleaners: Arc<std::sync::RwLock<CleaningPipeline>>,
+    feed_events: broadcast::Sender<DataFeed>,
+    decision_events: broadcast::Sender<AgentDecision>,
+    reorg_events: broadcast::Sender<ReorgNotice>,
+    /// Held from a store write until its event is sent, so writes commit and reach
+    /// subscribers in `seq` order and a stream resuming after a `seq` cannot skip a
+    /// lower one that was still being written.
+    feed_writes: Arc<Mutex<()>>,
+    decision_writes: Arc<Mutex<()>>,
 }
 
 impl RealtimeIndexer {
     pub fn new() -> Self {
+        let stores = Stores::memory();
+        let window_secs = std::env::var("INDEXER_METRICS_WINDOW_SECS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("INDEXER_METRICS_WINDOW_SECS must be a valid number");
+        let stream_buffer = std::env::var("STREAM_BUFFER_SIZE")
+            .unwrap_or_else(|_| "1024".to_string())
+            .parse()
+            .expect("STREAM_BUFFER_SIZE must be a valid number");
+
         Self {
-            feeds: Arc::new(RwLock::new(HashMap::new())),
-            metrics: Arc::new(RwLock::new(IndexerMetrics {
-                total_feeds_processed: 0,
-                feeds_per_second: 0.0,
-                average_latency_ms: 0.0,
-                active_feeds: 0,
-                last_update: 0,
-            })),
-            agent_decisions: Arc::new(RwLock::new(Vec::new())),
+            feeds: stores.feeds,
+            metrics: Arc::new(RwLock::new(MetricsState::new(Duration::from_secs(window_secs)))),
+            agent_decisions: stores.decisions,
+            schemas: stores.schemas,
+            cleaners: Arc::new(std::sync::RwLock::new(CleaningPipeline::standard())),
+            feed_events: broadcast::channel(stream_buffer).0,
+            decision_events: broadcast::channel(stream_buffer).0,
+            reorg_events: broadcast::channel(stream_buffer).0,
+            feed_writes: Arc::new(Mutex::new(())),
+            decision_writes: Arc::new(Mutex::new(())),
         }
     }
 
-    pub async fn ingest_feed(&self, feed: DataFeed) -> Result<(), String> {
-        let start = std::time::Instant::now();
+    /// Creates an indexer on top of existing (possibly durable) stores, with the
+    /// JSON Schemas registered in them.
+    pub async fn with_stores(stores: Stores) -> Result<Self, String> {
+        let active_feeds = stores.feeds.count().await?;
+        let mut pipeline = CleaningPipeline::standard();
+        for (type_name, schema) in stores.schemas.list().await? {
+            let validator = JsonSchemaValidator::compile(schema)
+                .map_err(|e| format!("Stored schema for Custom:{}: {}", type_name, e))?;
+            pipeline.set_schema(&type_name, validator);
+        }
+        let indexer = Self {
+            feeds: stores.feeds,
+            agent_decisions: stores.decisions,
+            schemas: stores.schemas,
+            cleaners: Arc::new(std::sync::RwLock::new(pipeline)),
+            ..Self::new()
+        };
+        indexer.metrics.write().await.counters.active_feeds = active_feeds as u32;
+        Ok(indexer)
+    }
+
+    /// Cleans and stores a feed. Re-ingesting identical content is reported as a
+    /// duplicate and not counted as processed.
+    pub async fn ingest_feed(&self, feed: DataFeed) -> Result<IngestReceipt, IngestError> {
+        let start = Instant::now();
+        let data_type = feed.data_type.metric_label();
+        
+        // Stateless cleaning first; the content hash is final after it
+        let prepared = self.cleaners.read().unwrap().prepare(feed);
+        let prepared = match prepared {
+            Ok(feed) => feed,
+            Err(e) => return Err(self.record_rejection(&data_type, e).await),
+        };
+        let content_hash = prepared.content_hash();
+        
+        // Stateful stages (like outlier detection) must not see a stored feed twice
+        if self.cleaners.read().unwrap().has_stateful_stages(&prepared) {
+            match self.feeds.current_version(&prepared.feed_id).await {
+                Ok(Some(current)) if current.content_hash == content_hash => {
+                    self.record_duplicate().await;
+                    return Ok(IngestReceipt {
+                        feed_id: prepared.feed_id,
+                        status: WriteStatus::Duplicate,
+                        version: current.version,
+                        content_hash,
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => return Err(self.record_storage_error(&data_type, e).await),
+            }
+        }
+        let cleaned_feed = self.cleaners.read().unwrap().finish(prepared);
+        let cleaned_feed = match cleaned_feed {
+            Ok(feed) => feed,
+            Err(e) => return Err(self.record_rejection(&data_type, e).await),
+        };
+        let (feed_id, source) = (cleaned_feed.feed_id.clone(), cleaned_feed.source.clone());
         
-        // Clean the data
-        let cleaned_feed = self.clean_data(feed).await?;
+        // Only pay for the copy when someone is listening
+        let event = (self.feed_events.receiver_count() > 0).then(|| cleaned_feed.clone());
         
-        // Store in memory
-        let mut feeds = self.feeds.write().await;
-        feeds.insert(cleaned_feed.feed_id.clone(), cleaned_feed.clone());
+        let write = self.feed_writes.lock().await;
+        let outcome = match self.feeds.put(cleaned_feed, &content_hash).await {
+            Ok(outcome) => outcome,
+            Err(e) => return Err(self.record_storage_error(&data_type, e).await),
+        };
         
-        // Update metrics
+        if outcome.status == WriteStatus::Duplicate {
+            self.record_duplicate().await;
+        } else {
+            let latency = start.elapsed();
+            let created = outcome.status == WriteStatus::Created;
+            self.metrics.write().await.record_ingest(&data_type, latency, created);
+            telemetry::FEEDS_INGESTED.with_label_values(&[source.kind(), &data_type]).inc();
+            telemetry::INGEST_DURATION.with_label_values(&[&data_type]).observe(latency.as_secs_f64());
+            if let Some(mut feed) = event {
+                feed.seq = Some(outcome.seq);
+                let _ = self.feed_events.send(feed);
+            }
+        }
+        drop(write);
+        
+        tracing::trace!(
+            "Ingested feed {} from {}:{} ({:?}, v{})",
+            feed_id, source.kind(), source.value(), outcome.status, outcome.version
+        );
+        
+        Ok(IngestReceipt {
+            feed_id,
+            status: outcome.status,
+            version: outcome.version,
+            content_hash,
+        })
+    }
+
+    async fn record_rejection(&self, data_type: &str, e: CleaningError) -> IngestError {
+        telemetry::INGEST_ERRORS.with_label_values(&["cleaning", data_type]).inc();
         let mut metrics = self.metrics.write().await;
-        metrics.total_feeds_processed += 1;
-        metrics.active_feeds = feeds.len() as u32;
-        metrics.average_latency_ms = start.elapsed().as_millis() as f64;
-        metrics.last_update = std::time::SystemTime::now()
+        metrics.counters.cleaning_errors += 1;
+        *metrics.counters.cleaning_errors_by_type.entry(data_type.to_string()).or_insert(0) += 1;
+        IngestError::Rejected(e)
+    }
+
+    async fn record_storage_error(&self, data_type: &str, e: String) -> IngestError {
+        telemetry::INGEST_ERRORS.with_label_values(&["storage", data_type]).inc();
+        self.metrics.write().await.counters.storage_errors += 1;
+        IngestError::Storage(e)
+    }
+
+    async fn record_duplicate(&self) {
+        self.metrics.write().await.counters.duplicates_skipped += 1;
+        telemetry::DUPLICATE_FEEDS.inc();
+    }
+
+    /// Registers (or replaces) the JSON Schema that `Custom(type_name)` feeds must match.
+    /// The schema is saved in the schema store, so it survives restarts.
+    /// Returns `true` if an existing schema was replaced.
+    pub async fn register_schema(&self, type_name: &str, schema: serde_json::Value) -> Result<bool, SchemaError> {
+        let validator = JsonSchemaValidator::compile(schema.clone()).map_err(SchemaError::Invalid)?;
+        self.schemas.put(type_name, &schema).await.map_err(SchemaError::Storage)?;
+        Ok(self.cleaners.write().unwrap().set_schema(type_name, validator))
+    }
+
+    pub async fn remove_schema(&self, type_name: &str) -> Result<bool, String> {
+        let stored = self.schemas.remove(type_name).await?;
+        let active = self.cleaners.write().unwrap().remove_schema(type_name);
+        Ok(stored || active)
+    }
+
+    pub fn get_schema(&self, type_name: &str) -> Option<serde_json::Value> {
+        self.cleaners.read().unwrap().schema(type_name)
+    }
+
+    pub fn list_schemas(&self) -> BTreeMap<String, serde_json::Value> {
+        self.cleaners.read().unwrap().schemas()
+    }
+
+    /// Removes feeds that belong to an orphaned chain branch. Returns how many were removed.
+    pub async fn rollback_feeds(&self, feed_ids: &[String]) -> Result<usize, String> {
+        let removed = self.feeds.remove(feed_ids).await?;
+        
+        let counters = &mut self.metrics.write().await.counters;
+        counters.active_feeds = counters.active_feeds.saturating_sub(removed as u32);
+        
+        Ok(removed)
+    }
+
+    /// Applies `policy` once. Age limits are measured against the feed/decision `timestamp`.
+    pub async fn enforce_retention(&self, policy: &RetentionPolicy) -> Result<RetentionReport, String> {
+        let start = Instant::now();
+        let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        
-        Ok(())
-    }
-
-    async fn clean_data(&self, mut feed: DataFeed) -> Result<DataFeed, String> {
-        // Data cleaning logic
-        let cleaned = match feed.data_type {
-            DataType::Transaction => {
-                // Normalize transaction data
-                serde_json::json!({
-                    "normalized": true,
-                    "data": feed.raw_data
-                })
+        let mut report = RetentionReport {
+            ran_at: now,
+            ..Default::default()
+        };
+
+        for rule in &policy.feeds {
+            let mut evicted = 0;
+            if let Some(max_age) = rule.limits.max_age_secs {
+                let expired = FeedQuery {
+                    until: Some(now.saturating_sub(max_age).saturating_sub(1)),
+                    ..rule.filter.clone()
+                };
+                evicted += self.feeds.evict(&expired, 0).await?;
             }
-            DataType::Price => {
-                // Validate and normalize price data
-                serde_json::json!({
-                    "validated": true,
-                    "data": feed.raw_data
-                })
+            if let Some(max_count) = rule.limits.max_count {
+                evicted += self.feeds.evict(&rule.filter, max_count).await?;
             }
-            _ => feed.raw_data.clone(),
-        };
-        
-        feed.cleaned_data = Some(cleaned);
-        Ok(feed)
+            if evicted == 0 {
+                continue;
+            }
+
+            {
+                let counters = &mut self.metrics.write().await.counters;
+                counters.feeds_evicted += evicted as u64;
+                counters.active_feeds = counters.active_feeds.saturating_sub(evicted as u32);
+            }
+            telemetry::RETENTION_EVICTED
+                .with_label_values(&["feeds", &rule.selector])
+                .inc_by(evicted as u64);
+            report.feeds_evicted += evicted;
+            report.feeds_evicted_by_rule.insert(rule.selector.clone(), evicted);
+        }
+
+        if !policy.decisions.is_unlimited() {
+            let older_than = policy.decisions.max_age_secs.map(|max_age| now.saturating_sub(max_age));
+            let evicted = self.agent_decisions.evict(older_than, policy.decisions.max_count).await?;
+            if evicted > 0 {
+                self.metrics.write().await.counters.decisions_evicted += evicted as u64;
+                telemetry::RETENTION_EVICTED
+                    .with_label_values(&["decisions", "*"])
+                    .inc_by(evicted as u64);
+            }
+            report.decisions_evicted = evicted;
+        }
+
+        report.duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        Ok(report)
+    }
+
+    pub async fn record_reorg(&self, notice: ReorgNotice) {
+        self.metrics.write().await.counters.reorgs_detected += 1;
+        telemetry::REORGS.inc();
+        if self.reorg_events.receiver_count() > 0 {
+            let _ = self.reorg_events.send(notice);
+        }
     }
 
     pub async fn get_metrics(&self) -> IndexerMetrics {
-        self.metrics.read().await.clone()
+        self.metrics.write().await.snapshot()
     }
 
-    pub async fn get_feeds(&self, limit: usize) -> Vec<DataFeed> {
-        let feeds = self.feeds.read().await;
-        feeds.values().take(limit).cloned().collect()
+    /// One page of feeds matching `query`; `next_cursor` is set when more remain.
+    pub async fn query_feeds(&self, mut query: FeedQuery) -> Result<FeedPage, String> {
+        let limit = query.limit;
+        query.limit = limit + 1;
+
+        let mut feeds = self.feeds.query(&query).await?;
+        let next_cursor = if feeds.len() > limit {
+            feeds.truncate(limit);
+            feeds.last().map(|feed| FeedCursor::after(feed).encode())
+        } else {
+            None
+        };
+
+        Ok(FeedPage { feeds, next_cursor })
     }
 
-    pub async fn process_agent_decision(&self, decision: AgentDecision) {
-        let mut decisions = self.agent_decisions.write().await;
-        decisions.push(decision);
-        
-        // Keep only last 1000 decisions
-        let len = decisions.len();
-        if len > 1000 {
-            decisions.drain(0..len - 1000);
+    pub async fn get_feed(&self, feed_id: &str) -> Result<Option<DataFeed>, String> {
+        self.feeds.get(feed_id).await
+    }
+
+    /// All stored versions of a feed, oldest first.
+    pub async fn get_feed_history(&self, feed_id: &str) -> Result<Vec<FeedVersion>, String> {
+        self.feeds.versions(feed_id).await
+    }
+
+    /// Stored feeds matching `filter` with a `seq` after `after`, in write order.
+    pub async fn feeds_written_after(&self, filter: &FeedQuery, after: u64, limit: usize) -> Result<Vec<DataFeed>, String> {
+        self.feeds.written_after(filter, after, limit).await
+    }
+
+    /// Validates the decision, resolves every `data_sources` entry to the current version
+    /// of that feed and records the decision with the resulting provenance.
+    pub async fn process_agent_decision(&self, mut decision: AgentDecision) -> Result<AgentDecision, DecisionError> {
+        let decision_type = decision.decision_type.kind();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut violations = decision.validate(now);
+        let mut sources = Vec::with_capacity(decision.data_sources.len());
+        for feed_id in &decision.data_sources {
+            match self.feeds.current_version(feed_id).await.map_err(DecisionError::Storage)? {
+                Some(current) => sources.push(SourceSnapshot {
+                    feed_id: feed_id.clone(),
+                    version: current.version,
+                    content_hash: current.content_hash,
+                }),
+                None => violations.push(format!("data source {} is not an indexed feed", feed_id)),
+            }
+        }
+        if !violations.is_empty() {
+            telemetry::DECISIONS_REJECTED.with_label_values(&[decision_type]).inc();
+            return Err(DecisionError::Invalid(violations));
         }
+
+        decision.provenance = Some(DecisionProvenance::new(sources));
+        decision.seq = None;
+        let write = self.decision_writes.lock().await;
+        let seq = self
+            .agent_decisions
+            .append(decision.clone())
+            .await
+            .map_err(DecisionError::Storage)?;
+        decision.seq = Some(seq);
+        tracing::debug!("Recorded {} decision from agent {}", decision_type, decision.agent_id);
+        telemetry::DECISIONS_RECORDED.with_label_values(&[decision_type]).inc();
+        if self.decision_events.receiver_count() > 0 {
+            let _ = self.decision_events.send(decision.clone());
+        }
+        drop(write);
+        Ok(decision)
     }
 
-    pub async fn get_agent_decisions(&self, limit: usize) -> Vec<AgentDecision> {
-        let decisions = self.agent_decisions.read().await;
-        let total = decisions.len();
-        if total > limit {
-            decisions[total - limit..].iter().rev().cloned().collect()
-        } else {
-            decisions.iter().rev().cloned().collect()
+    pub async fn query_decisions(&self, query: &DecisionQuery) -> Result<Vec<AgentDecision>, String> {
+        self.agent_decisions.query(query).await
+    }
+
+    /// Stored decisions matching `filter` with a `seq` after `after`, in recording order.
+    pub async fn decisions_recorded_after(&self, filter: &DecisionQuery, after: u64, limit: usize) -> Result<Vec<AgentDecision>, String> {
+        self.agent_decisions.recorded_after(filter, after, limit).await
+    }
+
+    /// Per-agent statistics over the decisions matching `query` (its order and limit are ignored).
+    pub async fn agent_stats(&self, query: &DecisionQuery) -> Result<Vec<AgentStats>, String> {
+        let mut by_agent: BTreeMap<String, AgentStats> = BTreeMap::new();
+        let mut confidence_sums: HashMap<String, f64> = HashMap::new();
+
+        for group in self.agent_decisions.aggregate(query).await? {
+            *confidence_sums.entry(group.agent_id.clone()).or_insert(0.0) += group.confidence_sum;
+            let stats = by_agent.entry(group.agent_id.clone()).or_insert_with(|| AgentStats {
+                agent_id: group.agent_id.clone(),
+                total_decisions: 0,
+                decisions_by_type: BTreeMap::new(),
+                mean_confidence: 0.0,
+                first_decision_at: group.first_timestamp,
+                last_decision_at: group.last_timestamp,
+                decisions_per_hour: None,
+            });
+            stats.total_decisions += group.count;
+            stats.decisions_by_type.insert(group.decision_type, group.count);
+            stats.first_decision_at = stats.first_decision_at.min(group.first_timestamp);
+            stats.last_decision_at = stats.last_decision_at.max(group.last_timestamp);
         }
+
+        Ok(by_agent
+            .into_values()
+            .map(|mut stats| {
+                stats.mean_confidence = confidence_sums[&stats.agent_id] / stats.total_decisions as f64;
+                let from = query.since.unwrap_or(stats.first_decision_at);
+                let to = query.until.unwrap_or(stats.last_decision_at);
+                let span = to.saturating_sub(from);
+                stats.decisions_per_hour = (span > 0).then(|| stats.total_decisions as f64 * 3600.0 / span as f64);
+                stats
+            })
+            .collect())
+    }
+
+    /// Live feed writes (new content only; duplicates are not published).
+    pub fn subscribe_feeds(&self) -> broadcast::Receiver<DataFeed> {
+        self.feed_events.subscribe()
+    }
+
+    pub fn subscribe_decisions(&self) -> broadcast::Receiver<AgentDecision> {
+        self.decision_events.subscribe()
+    }
+
+    /// Chain reorgs rolled back from now on.
+    pub fn subscribe_reorgs(&self) -> broadcast::Receiver<ReorgNotice> {
+        self.reorg_events.subscribe()
     }
 }
 
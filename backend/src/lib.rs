@@ -1,10 +1,28 @@
 pub mod config;
 pub mod handlers;
 pub mod models;
+pub mod monitor;
+pub mod paper_trading;
 pub mod routes;
 pub mod indexer;
+pub mod llm;
 pub mod ai_engine;
+pub mod backfill;
+pub mod backtest;
 pub mod celo_client;
+pub mod cleaning;
+#[cfg(feature = "database")]
+pub mod db;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod retention;
+pub mod stats;
+pub mod storage;
+pub mod stream;
+pub mod telemetry;
+pub mod follower;
+pub mod idempotency;
+pub mod webhooks;
 
 pub use indexer::RealtimeIndexer;
 pub use ai_engine::CeloAIEngine;
@@ -0,0 +1,762 @@
+// LLM Providers - pluggable completion backends for the Celo-7B AI Engine
+use async_trait::async_trait;
+use ethers::core::rand::{self, Rng};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::telemetry::time_ai;
+
+pub const SYSTEM_PROMPT: &str = "You are a Celo blockchain expert AI assistant. Provide clear, accurate, \
+    and helpful information about Celo blockchain, smart contracts, DeFi, and related topics.";
+
+/// Every built-in provider, in the default `LLM_PROVIDERS` order.
+const BUILTIN_PROVIDERS: [&str; 8] = ["groq", "ollama", "vllm", "huggingface", "openai", "anthropic", "mistral", "llamacpp"];
+
+const DEFAULT_TEMPERATURE: f64 = 0.7;
+
+/// What the engine asks a provider to complete.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub system: String,
+    pub prompt: String,
+    /// Background for the model, rendered as a numbered list after the system prompt.
+    pub context: Vec<String>,
+    /// `None` uses the provider's default. Both are clamped to the provider's `Limits`.
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+}
+
+impl Completion {
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            system: SYSTEM_PROMPT.to_string(),
+            prompt: prompt.to_string(),
+            context: Vec::new(),
+            max_tokens: None,
+            temperature: None,
+        }
+    }
+
+    /// The system prompt followed by the non-empty context entries.
+    pub fn system_prompt(&self) -> String {
+        let mut system = self.system.clone();
+        let entries = self.context.iter().map(|entry| entry.trim()).filter(|entry| !entry.is_empty());
+        for (i, entry) in entries.enumerate() {
+            if i == 0 {
+                system.push_str("\n\nContext:");
+            }
+            system.push_str(&format!("\n[{}] {}", i + 1, entry));
+        }
+        system
+    }
+
+    /// The system prompt, context and user prompt as one text, for providers without chat roles.
+    pub fn render(&self) -> String {
+        format!("{}\n\nUser: {}\n\nAssistant:", self.system_prompt(), self.prompt)
+    }
+}
+
+/// Bounds of a provider's sampling parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Limits {
+    /// Used when a request doesn't set `max_tokens`.
+    pub default_max_tokens: u32,
+    pub max_tokens: u32,
+    pub min_temperature: f64,
+    pub max_temperature: f64,
+}
+
+impl Limits {
+    pub const fn new(default_max_tokens: u32, max_tokens: u32, min_temperature: f64, max_temperature: f64) -> Self {
+        Self {
+            default_max_tokens,
+            max_tokens,
+            min_temperature,
+            max_temperature,
+        }
+    }
+
+    /// The request's `max_tokens` and `temperature`, defaulted and clamped into these limits.
+    pub fn sampling(&self, request: &Completion) -> Sampling {
+        let temperature = request
+            .temperature
+            .filter(|t| t.is_finite())
+            // f32 -> f64 would otherwise send 0.3 as 0.30000001192092896
+            .map(|t| (f64::from(t) * 10_000.0).round() / 10_000.0)
+            .unwrap_or(DEFAULT_TEMPERATURE);
+        Sampling {
+            max_tokens: request
+                .max_tokens
+                .unwrap_or(self.default_max_tokens)
+                .clamp(1, self.max_tokens.max(1)),
+            temperature: temperature.clamp(self.min_temperature, self.max_temperature),
+        }
+    }
+}
+
+/// The sampling parameters a provider sends, already within its `Limits`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sampling {
+    pub max_tokens: u32,
+    pub temperature: f64,
+}
+
+/// A backend the engine can send completions to. Implement it and `register` the provider
+/// on a `ProviderRegistry` to add a backend without changing the engine.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Label used in `LLM_PROVIDERS`, logs and metrics.
+    fn name(&self) -> &str;
+    fn model(&self) -> &str;
+    fn limits(&self) -> Limits;
+    /// `sampling` is the request's parameters clamped to `limits()` by the registry.
+    async fn complete(&self, request: &Completion, sampling: Sampling) -> Result<String, String>;
+}
+
+/// `priority` tries providers in the configured order; `weighted` picks the first provider
+/// at random in proportion to its weight and falls back through the rest the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Routing {
+    Priority,
+    Weighted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderEntry {
+    pub name: String,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConfig {
+    pub routing: Routing,
+    /// `None` uses every provider registered on the registry, in registration order.
+    pub providers: Option<Vec<ProviderEntry>>,
+}
+
+impl LlmConfig {
+    /// `LLM_PROVIDERS` is a comma separated list of `name[:weight]` (weight defaults to 1);
+    /// `LLM_ROUTING` is `priority` (default) or `weighted`. Names are resolved against the
+    /// registry's factories by `ProviderRegistry::configure`.
+    pub fn from_env() -> Self {
+        Self {
+            routing: match std::env::var("LLM_ROUTING").as_deref() {
+                Err(_) | Ok("priority") => Routing::Priority,
+                Ok("weighted") => Routing::Weighted,
+                Ok(other) => panic!("Invalid LLM_ROUTING: expected priority or weighted, got {}", other),
+            },
+            providers: std::env::var("LLM_PROVIDERS")
+                .ok()
+                .map(|list| parse_providers(&list).unwrap_or_else(|e| panic!("Invalid LLM_PROVIDERS: {}", e))),
+        }
+    }
+}
+
+fn parse_providers(list: &str) -> Result<Vec<ProviderEntry>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, weight) = match entry.split_once(':') {
+                Some((name, weight)) => (
+                    name,
+                    weight.parse().map_err(|_| format!("invalid weight in {}", entry))?,
+                ),
+                None => (entry, 1),
+            };
+            Ok(ProviderEntry {
+                name: name.to_ascii_lowercase(),
+                weight,
+            })
+        })
+        .collect()
+}
+
+/// The built-in provider called `name`, if its URL or API key is set in the environment.
+fn builtin_provider(name: &str, client: &Client) -> Option<Box<dyn LlmProvider>> {
+    let var = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+    let model = |key: &str, default: &str| var(key).unwrap_or_else(|| default.to_string());
+    // `<PROVIDER>_MAX_TOKENS` lowers or raises the cap for models with other output limits
+    let limits = |key: &str, default_max_tokens: u32, max_tokens: u32, max_temperature: f64| {
+        let max_tokens = var(key)
+            .map(|v| v.parse().unwrap_or_else(|_| panic!("{} must be a valid number", key)))
+            .unwrap_or(max_tokens);
+        Limits::new(default_max_tokens, max_tokens, 0.0, max_temperature)
+    };
+
+    let provider: Box<dyn LlmProvider> = match name {
+        "groq" => Box::new(ChatCompletions {
+            name: "groq",
+            url: "https://api.groq.com/openai/v1/chat/completions".to_string(),
+            api_key: Some(var("GROQ_API_KEY")?),
+            model: model("GROQ_MODEL", "llama-3.3-70b-versatile"),
+            limits: limits("GROQ_MAX_TOKENS", 1024, 32_768, 2.0),
+            client: client.clone(),
+        }),
+        "ollama" => Box::new(Ollama {
+            url: var("OLLAMA_URL")?,
+            model: model("OLLAMA_MODEL", "llama3.2:3b"),
+            limits: limits("OLLAMA_MAX_TOKENS", 512, 4096, 2.0),
+            client: client.clone(),
+        }),
+        "vllm" => openai_compatible(
+            "vllm",
+            var("VLLM_URL")?,
+            model("VLLM_MODEL", "openai/gpt-oss-20b"),
+            limits("VLLM_MAX_TOKENS", 512, 4096, 2.0),
+            client,
+        ),
+        "huggingface" => Box::new(HuggingFace {
+            api_key: var("HF_API_KEY")?,
+            model: model("HF_MODEL", "openai/gpt-oss-20b"),
+            limits: Limits {
+                // The Inference API rejects a temperature of 0
+                min_temperature: 0.01,
+                ..limits("HF_MAX_TOKENS", 500, 2048, 2.0)
+            },
+            client: client.clone(),
+        }),
+        "openai" => Box::new(ChatCompletions {
+            name: "openai",
+            url: "https://api.openai.com/v1/chat/completions".to_string(),
+            api_key: Some(var("OPENAI_API_KEY")?),
+            model: model("OPENAI_MODEL", "gpt-4"),
+            limits: limits("OPENAI_MAX_TOKENS", 500, 4096, 2.0),
+            client: client.clone(),
+        }),
+        "anthropic" => Box::new(Anthropic {
+            api_key: var("ANTHROPIC_API_KEY")?,
+            model: model("ANTHROPIC_MODEL", "claude-3-5-haiku-latest"),
+            limits: limits("ANTHROPIC_MAX_TOKENS", 1024, 8192, 1.0),
+            client: client.clone(),
+        }),
+        "mistral" => Box::new(ChatCompletions {
+            name: "mistral",
+            url: "https://api.mistral.ai/v1/chat/completions".to_string(),
+            api_key: Some(var("MISTRAL_API_KEY")?),
+            model: model("MISTRAL_MODEL", "mistral-small-latest"),
+            limits: limits("MISTRAL_MAX_TOKENS", 1024, 8192, 1.0),
+            client: client.clone(),
+        }),
+        "llamacpp" => openai_compatible(
+            "llamacpp",
+            var("LLAMACPP_URL")?,
+            model("LLAMACPP_MODEL", "default"),
+            limits("LLAMACPP_MAX_TOKENS", 512, 4096, 2.0),
+            client,
+        ),
+        _ => return None,
+    };
+    Some(provider)
+}
+
+/// A local OpenAI-compatible server; the URL's path picks the chat or text completions API.
+fn openai_compatible(name: &'static str, url: String, model: String, limits: Limits, client: &Client) -> Box<dyn LlmProvider> {
+    if url.trim_end_matches('/').ends_with("/chat/completions") {
+        Box::new(ChatCompletions {
+            name,
+            url,
+            api_key: None,
+            model,
+            limits,
+            client: client.clone(),
+        })
+    } else {
+        Box::new(TextCompletions {
+            name,
+            url,
+            model,
+            limits,
+            client: client.clone(),
+        })
+    }
+}
+
+/// Sends the request and returns the JSON body of a successful response.
+async fn send_json(name: &str, request: RequestBuilder, payload: &Value) -> Result<Value, String> {
+    let response = request
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| format!("{} request failed: {}", name, e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{} returned {}: {}", name, status, body.chars().take(200).collect::<String>()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("{} returned invalid JSON: {}", name, e))
+}
+
+fn text_at(name: &str, result: &Value, pointer: &str) -> Result<String, String> {
+    result
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(|text| text.trim().to_string())
+        .ok_or_else(|| format!("{} response has no {}", name, pointer))
+}
+
+/// OpenAI-style `/chat/completions` (OpenAI, Groq, Mistral, vLLM, llama.cpp server).
+pub struct ChatCompletions {
+    pub name: &'static str,
+    pub url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub limits: Limits,
+    pub client: Client,
+}
+
+#[async_trait]
+impl LlmProvider for ChatCompletions {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    async fn complete(&self, request: &Completion, sampling: Sampling) -> Result<String, String> {
+        let payload = json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": request.system_prompt() },
+                { "role": "user", "content": request.prompt }
+            ],
+            "temperature": sampling.temperature,
+            "max_tokens": sampling.max_tokens
+        });
+        let mut http = self.client.post(&self.url);
+        if let Some(api_key) = &self.api_key {
+            http = http.bearer_auth(api_key);
+        }
+        let result = send_json(self.name, http, &payload).await?;
+        text_at(self.name, &result, "/choices/0/message/content")
+    }
+}
+
+/// OpenAI-style `/completions` with a single rendered prompt.
+pub struct TextCompletions {
+    pub name: &'static str,
+    pub url: String,
+    pub model: String,
+    pub limits: Limits,
+    pub client: Client,
+}
+
+#[async_trait]
+impl LlmProvider for TextCompletions {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    async fn complete(&self, request: &Completion, sampling: Sampling) -> Result<String, String> {
+        let payload = json!({
+            "model": self.model,
+            "prompt": request.render(),
+            "max_tokens": sampling.max_tokens,
+            "temperature": sampling.temperature
+        });
+        let result = send_json(self.name, self.client.post(&self.url), &payload).await?;
+        text_at(self.name, &result, "/choices/0/text")
+    }
+}
+
+/// Ollama's `/api/generate`.
+pub struct Ollama {
+    pub url: String,
+    pub model: String,
+    pub limits: Limits,
+    pub client: Client,
+}
+
+#[async_trait]
+impl LlmProvider for Ollama {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    async fn complete(&self, request: &Completion, sampling: Sampling) -> Result<String, String> {
+        let payload = json!({
+            "model": self.model,
+            "system": request.system_prompt(),
+            "prompt": request.prompt,
+            "stream": false,
+            "options": {
+                "temperature": sampling.temperature,
+                "num_predict": sampling.max_tokens
+            }
+        });
+        let result = send_json("ollama", self.client.post(&self.url), &payload).await?;
+        text_at("ollama", &result, "/response")
+    }
+}
+
+/// The Hugging Face Inference API's text generation task.
+pub struct HuggingFace {
+    pub api_key: String,
+    pub model: String,
+    pub limits: Limits,
+    pub client: Client,
+}
+
+#[async_trait]
+impl LlmProvider for HuggingFace {
+    fn name(&self) -> &str {
+        "huggingface"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    async fn complete(&self, request: &Completion, sampling: Sampling) -> Result<String, String> {
+        let url = format!("https://api-inference.huggingface.co/models/{}", self.model);
+        let payload = json!({
+            "inputs": request.render(),
+            "parameters": {
+                "max_new_tokens": sampling.max_tokens,
+                "temperature": sampling.temperature,
+                "return_full_text": false
+            }
+        });
+        let result = send_json("huggingface", self.client.post(&url).bearer_auth(&self.api_key), &payload).await?;
+        text_at("huggingface", &result, "/0/generated_text")
+    }
+}
+
+/// Anthropic's Messages API.
+pub struct Anthropic {
+    pub api_key: String,
+    pub model: String,
+    pub limits: Limits,
+    pub client: Client,
+}
+
+#[async_trait]
+impl LlmProvider for Anthropic {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    async fn complete(&self, request: &Completion, sampling: Sampling) -> Result<String, String> {
+        let payload = json!({
+            "model": self.model,
+            "system": request.system_prompt(),
+            "messages": [{ "role": "user", "content": request.prompt }],
+            "max_tokens": sampling.max_tokens,
+            "temperature": sampling.temperature
+        });
+        let http = self
+            .client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01");
+        let result = send_json("anthropic", http, &payload).await?;
+
+        let text: Vec<&str> = result["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+        if text.is_empty() {
+            return Err("anthropic response has no text content".to_string());
+        }
+        Ok(text.concat().trim().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderInfo {
+    pub name: String,
+    pub model: String,
+    pub weight: u32,
+    pub limits: Limits,
+}
+
+/// Builds a provider from its environment, or `None` if it isn't configured.
+pub type ProviderFactory = Box<dyn Fn(&Client) -> Option<Box<dyn LlmProvider>> + Send + Sync>;
+
+struct Registered {
+    provider: Box<dyn LlmProvider>,
+    weight: u32,
+}
+
+/// The providers the engine may use, in configured order, and the factories `LLM_PROVIDERS`
+/// names are resolved against.
+pub struct ProviderRegistry {
+    routing: Routing,
+    factories: Vec<(String, ProviderFactory)>,
+    providers: Vec<Registered>,
+}
+
+impl ProviderRegistry {
+    /// A registry with a factory for every built-in provider and no providers yet.
+    pub fn new(routing: Routing) -> Self {
+        let mut registry = Self {
+            routing,
+            factories: Vec::new(),
+            providers: Vec::new(),
+        };
+        for name in BUILTIN_PROVIDERS {
+            registry.register_factory(name, move |client| builtin_provider(name, client));
+        }
+        registry
+    }
+
+    /// The built-in providers listed in `config` whose credentials are set.
+    pub fn from_config(config: &LlmConfig, client: &Client) -> Self {
+        let mut registry = Self::new(config.routing);
+        registry
+            .configure(config, client)
+            .unwrap_or_else(|e| panic!("Invalid LLM_PROVIDERS: {}", e));
+        registry
+    }
+
+    /// Makes `name` usable in `LLM_PROVIDERS`, replacing any factory already registered under
+    /// it. Without `LLM_PROVIDERS` every factory is tried, in registration order.
+    pub fn register_factory<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&Client) -> Option<Box<dyn LlmProvider>> + Send + Sync + 'static,
+    {
+        let name = name.to_ascii_lowercase();
+        match self.factories.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = Box::new(factory),
+            None => self.factories.push((name, Box::new(factory))),
+        }
+    }
+
+    /// Builds and registers the providers `config` lists, skipping those whose factory
+    /// returns `None`. Fails on a name no factory is registered under.
+    pub fn configure(&mut self, config: &LlmConfig, client: &Client) -> Result<(), String> {
+        let entries = match &config.providers {
+            Some(entries) => entries.clone(),
+            None => self
+                .factories
+                .iter()
+                .map(|(name, _)| ProviderEntry {
+                    name: name.clone(),
+                    weight: 1,
+                })
+                .collect(),
+        };
+
+        let mut resolved = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let Some((_, factory)) = self.factories.iter().find(|(name, _)| *name == entry.name) else {
+                let known: Vec<&str> = self.factories.iter().map(|(name, _)| name.as_str()).collect();
+                return Err(format!("unknown provider {} (expected one of {})", entry.name, known.join(", ")));
+            };
+            resolved.push((factory(client), entry.weight));
+        }
+
+        self.routing = config.routing;
+        for (provider, weight) in resolved {
+            if let Some(provider) = provider {
+                self.register(provider, weight);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a provider after those already registered. With weighted routing a weight of 0
+    /// makes it a fallback only.
+    pub fn register(&mut self, provider: Box<dyn LlmProvider>, weight: u32) {
+        tracing::info!("✓ LLM provider {} ({}) registered", provider.name(), provider.model());
+        self.providers.push(Registered { provider, weight });
+    }
+
+    pub fn routing(&self) -> Routing {
+        self.routing
+    }
+
+    pub fn providers(&self) -> Vec<ProviderInfo> {
+        self.providers
+            .iter()
+            .map(|r| ProviderInfo {
+                name: r.provider.name().to_string(),
+                model: r.provider.model().to_string(),
+                weight: r.weight,
+                limits: r.provider.limits(),
+            })
+            .collect()
+    }
+
+    /// The model of the first registered provider.
+    pub fn primary_model(&self) -> Option<&str> {
+        self.providers.first().map(|r| r.provider.model())
+    }
+
+    /// The order providers are tried in for one request.
+    fn attempt_order(&self) -> Vec<&Registered> {
+        let mut remaining: Vec<&Registered> = self.providers.iter().collect();
+        if self.routing == Routing::Priority {
+            return remaining;
+        }
+
+        let mut order = Vec::with_capacity(remaining.len());
+        let mut rng = rand::thread_rng();
+        loop {
+            let total: u64 = remaining.iter().map(|r| r.weight as u64).sum();
+            if total == 0 {
+                break;
+            }
+            let mut pick = rng.gen_range(0..total);
+            let index = remaining
+                .iter()
+                .position(|r| {
+                    if pick < r.weight as u64 {
+                        return true;
+                    }
+                    pick -= r.weight as u64;
+                    false
+                })
+                .expect("pick is below the total weight");
+            order.push(remaining.remove(index));
+        }
+        // Zero-weight providers keep their configured order as the last resort
+        order.extend(remaining);
+        order
+    }
+
+    /// The first successful completion, trying providers in routing order, or `None` if
+    /// every provider failed or none is registered.
+    pub async fn complete(&self, request: &Completion) -> Option<String> {
+        for registered in self.attempt_order() {
+            let provider = &registered.provider;
+            let sampling = provider.limits().sampling(request);
+            match time_ai(provider.name(), provider.complete(request, sampling)).await {
+                Ok(text) => return Some(text),
+                Err(e) => tracing::warn!("LLM provider {} failed: {}", provider.name(), e),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str);
+
+    #[async_trait]
+    impl LlmProvider for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+        fn model(&self) -> &str {
+            "fixed"
+        }
+        fn limits(&self) -> Limits {
+            Limits::new(16, 16, 0.0, 1.0)
+        }
+        async fn complete(&self, _request: &Completion, _sampling: Sampling) -> Result<String, String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn config(routing: Routing, providers: &str) -> LlmConfig {
+        LlmConfig {
+            routing,
+            providers: Some(parse_providers(providers).unwrap()),
+        }
+    }
+
+    #[test]
+    fn registered_factories_can_be_named_and_weighted() {
+        let mut registry = ProviderRegistry::new(Routing::Priority);
+        registry.register_factory("Local", |_| Some(Box::new(Fixed("local"))));
+        registry.register_factory("unset", |_| None);
+        registry
+            .configure(&config(Routing::Weighted, "local:3,unset:2"), &Client::new())
+            .unwrap();
+
+        assert_eq!(registry.routing(), Routing::Weighted);
+        let providers = registry.providers();
+        assert_eq!(providers.len(), 1);
+        assert_eq!((providers[0].name.as_str(), providers[0].weight), ("local", 3));
+    }
+
+    #[test]
+    fn unknown_provider_names_are_rejected() {
+        let mut registry = ProviderRegistry::new(Routing::Priority);
+        let err = registry
+            .configure(&config(Routing::Priority, "groq,local"), &Client::new())
+            .unwrap_err();
+        assert!(err.starts_with("unknown provider local"), "{}", err);
+        assert!(registry.providers().is_empty());
+    }
+
+    #[test]
+    fn sampling_defaults_and_clamps_into_the_limits() {
+        let limits = Limits::new(256, 1024, 0.0, 1.0);
+
+        let unset = Completion::new("hi");
+        assert_eq!(limits.sampling(&unset), Sampling { max_tokens: 256, temperature: DEFAULT_TEMPERATURE });
+
+        let set = Completion { max_tokens: Some(512), temperature: Some(0.3), ..Completion::new("hi") };
+        assert_eq!(limits.sampling(&set), Sampling { max_tokens: 512, temperature: 0.3 });
+
+        let over = Completion { max_tokens: Some(100_000), temperature: Some(1.8), ..Completion::new("hi") };
+        assert_eq!(limits.sampling(&over), Sampling { max_tokens: 1024, temperature: 1.0 });
+
+        let under = Completion { max_tokens: Some(0), temperature: Some(-0.5), ..Completion::new("hi") };
+        assert_eq!(limits.sampling(&under), Sampling { max_tokens: 1, temperature: 0.0 });
+
+        let nan = Completion { temperature: Some(f32::NAN), ..Completion::new("hi") };
+        assert_eq!(limits.sampling(&nan).temperature, DEFAULT_TEMPERATURE);
+    }
+
+    #[test]
+    fn context_is_numbered_after_the_system_prompt() {
+        let mut completion = Completion::new("What is this block?");
+        assert_eq!(completion.system_prompt(), SYSTEM_PROMPT);
+
+        completion.context = vec![" Block 123 ".to_string(), "  ".to_string(), "Gas used: 21000".to_string()];
+        assert_eq!(
+            completion.system_prompt(),
+            format!("{}\n\nContext:\n[1] Block 123\n[2] Gas used: 21000", SYSTEM_PROMPT)
+        );
+        assert!(completion.render().ends_with("\n\nUser: What is this block?\n\nAssistant:"));
+    }
+}
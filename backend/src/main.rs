@@ -1,10 +1,28 @@
 mod config;
 mod handlers;
 mod models;
+mod monitor;
+mod paper_trading;
 mod routes;
 mod indexer;
+mod llm;
 mod ai_engine;
+mod backfill;
+mod backtest;
 mod celo_client;
+mod cleaning;
+#[cfg(feature = "database")]
+mod db;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod retention;
+mod stats;
+mod storage;
+mod stream;
+mod telemetry;
+mod follower;
+mod idempotency;
+mod webhooks;
 
 use axum::Router;
 use std::net::SocketAddr;
@@ -16,7 +34,16 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use handlers::AppStateInner;
 use indexer::RealtimeIndexer;
 use ai_engine::CeloAIEngine;
+use backfill::BackfillManager;
+use backtest::BacktestManager;
 use celo_client::CeloClient;
+use follower::{BlockFollower, FollowerConfig};
+use idempotency::IdempotencyCache;
+use monitor::{MonitorConfig, MonitorEngine};
+use paper_trading::{PaperTrader, PaperTradingConfig};
+use webhooks::{WebhookConfig, WebhookDispatcher};
+use retention::{RetentionPolicy, RetentionTask};
+use storage::{StorageBackend, Stores};
 
 #[tokio::main]
 async fn main() {
@@ -33,7 +60,7 @@ async fn main() {
     let config = config::Config::from_env();
 
     // Initialize the AI-Enhanced Real-Time Data Inference Indexer
-    let indexer = RealtimeIndexer::new();
+    let (indexer, backend) = init_indexer().await;
     tracing::info!("✓ Real-Time Indexer initialized");
 
     // Initialize the Celo Fine-Tuned LLM (Celo-7B)
@@ -52,11 +79,82 @@ async fn main() {
         tracing::warn!("⚠ Using mock Celo data (set CELO_RPC_URL to connect to real network)");
     }
 
+    // `backend backfill <from> <to> [concurrency]` indexes a range and exits
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("backfill") {
+        if backend == StorageBackend::Memory {
+            // The indexed blocks would be dropped when the command exits
+            eprintln!("Backfill needs durable storage: set STORAGE_BACKEND=sqlite or DATABASE_URL");
+            std::process::exit(2);
+        }
+        let backfill = BackfillManager::new(celo_client, indexer);
+        std::process::exit(run_backfill_cli(&backfill, &args[2..]).await);
+    }
+
+    let backfill = BackfillManager::new(celo_client.clone(), indexer.clone());
+
+    // Start following the chain head into the indexer
+    let follower_config = FollowerConfig::from_env();
+    let follower = if follower_config.enabled {
+        Some(BlockFollower::spawn(follower_config, celo_client.clone(), indexer.clone()))
+    } else {
+        tracing::info!("Block follower disabled (set FOLLOWER_ENABLED=true to follow the chain head)");
+        None
+    };
+
+    let retention_policy = RetentionPolicy::from_env();
+    let retention = if retention_policy.is_empty() {
+        None
+    } else {
+        tracing::info!(
+            "✓ Retention enabled: {} feed rules, checked every {}s",
+            retention_policy.feeds.len(),
+            retention_policy.interval_secs
+        );
+        Some(RetentionTask::spawn(retention_policy, indexer.clone()))
+    };
+
+    // Deliver alerts, feeds and reorgs to registered webhooks
+    let webhook_config = WebhookConfig::from_env();
+    let allow_private_urls = webhook_config.allow_private_urls;
+    let webhooks = WebhookDispatcher::spawn(webhook_config, indexer.clone());
+
+    // Raise alerts when Monitor decision thresholds are crossed
+    let monitor_config = MonitorConfig::from_env();
+    let monitors = if monitor_config.enabled {
+        monitor_config
+            .register_webhook_sinks(&webhooks)
+            .await
+            .unwrap_or_else(|e| panic!("Invalid MONITOR_ALERT_SINKS: {}", e));
+        Some(MonitorEngine::spawn(monitor_config, indexer.clone()))
+    } else {
+        tracing::info!("Monitor engine disabled (MONITOR_ENGINE_ENABLED=false)");
+        None
+    };
+
+    // Simulate a portfolio per agent from its Trade decisions
+    let paper_config = PaperTradingConfig::from_env();
+    let backtests = BacktestManager::new(indexer.clone(), paper_config.clone(), allow_private_urls);
+    let paper_trading = if paper_config.enabled {
+        Some(PaperTrader::spawn(paper_config, indexer.clone()))
+    } else {
+        tracing::info!("Paper trading disabled (PAPER_TRADING_ENABLED=false)");
+        None
+    };
+
     // Create shared application state
     let state = Arc::new(RwLock::new(AppStateInner {
         indexer,
         ai_engine,
         celo_client,
+        follower,
+        backfill,
+        retention,
+        monitors,
+        webhooks,
+        paper_trading,
+        backtests,
+        idempotency: IdempotencyCache::from_env(),
     }));
 
     let cors = CorsLayer::new()
@@ -66,7 +164,8 @@ async fn main() {
         .allow_credentials(false);
 
     let app = Router::new()
-        .nest("/api", routes::api_routes(state))
+        .nest("/api", routes::api_routes(state.clone()))
+        .merge(routes::metrics_routes(state.clone()))
         .layer(cors)
         .layer(tower_http::trace::TraceLayer::new_for_http());
 
@@ -94,5 +193,103 @@ async fn main() {
     tracing::info!("📚 API Docs: http://{}/api/health", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
+
+    let state = state.read().await;
+    if let Some(follower) = &state.follower {
+        follower.shutdown().await;
+    }
+    if let Some(retention) = &state.retention {
+        retention.shutdown().await;
+    }
+    if let Some(monitors) = &state.monitors {
+        monitors.shutdown().await;
+    }
+    state.webhooks.shutdown().await;
+    if let Some(paper_trading) = &state.paper_trading {
+        paper_trading.shutdown().await;
+    }
+}
+
+async fn init_indexer() -> (RealtimeIndexer, StorageBackend) {
+    let backend = StorageBackend::from_env();
+    let stores = Stores::open(&backend)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to initialize {} storage: {}", backend.name(), e));
+    let indexer = RealtimeIndexer::with_stores(stores)
+        .await
+        .expect("Failed to load indexer state");
+
+    if backend == StorageBackend::Memory {
+        tracing::warn!("⚠ Using in-memory storage (set STORAGE_BACKEND=sqlite or DATABASE_URL for durable state)");
+    } else {
+        tracing::info!("✓ {} storage enabled", backend.name());
+    }
+    (indexer, backend)
+}
+
+async fn shutdown_signal() {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("failed to install Ctrl+C handler");
+    tracing::info!("Shutdown signal received");
+}
+
+async fn run_backfill_cli(backfill: &BackfillManager, args: &[String]) -> i32 {
+    let restart = args.iter().any(|arg| arg == "--restart");
+    let args: Vec<String> = args.iter().filter(|arg| *arg != "--restart").cloned().collect();
+    let parsed: Option<(u64, u64, usize)> = match args.as_slice() {
+        [from, to] => from.parse().ok().zip(to.parse().ok()).map(|(f, t)| (f, t, 4)),
+        [from, to, concurrency] => from
+            .parse()
+            .ok()
+            .zip(to.parse().ok())
+            .zip(concurrency.parse().ok())
+            .map(|((f, t), c)| (f, t, c)),
+        _ => None,
+    };
+    let Some((from, to, concurrency)) = parsed else {
+        eprintln!(
+            "Usage: backend backfill <from_block> <to_block> [concurrency 1-{}] [--restart]",
+            backfill::MAX_BACKFILL_CONCURRENCY
+        );
+        return 2;
+    };
+    if let Err(e) = backfill.validate(from, to, concurrency).await {
+        eprintln!("Invalid backfill: {}", e);
+        return 2;
+    }
+
+    let job_id = match backfill.start(from, to, concurrency, restart).await {
+        Ok(status) => status.job_id,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        let Some(status) = backfill.status(&job_id).await else { return 1 };
+
+        tracing::info!(
+            "Backfill {}/{} blocks ({:.1} blocks/s, ETA {})",
+            status.blocks_done,
+            status.blocks_total,
+            status.blocks_per_second,
+            status.eta_seconds.map(|s| format!("{}s", s)).unwrap_or_else(|| "-".to_string())
+        );
+
+        match status.state {
+            backfill::BackfillState::Running => continue,
+            backfill::BackfillState::Completed => return 0,
+            _ => {
+                eprintln!("Backfill {:?}: {}", status.state, status.error.unwrap_or_default());
+                return 1;
+            }
+        }
+    }
 }
@@ -19,6 +19,7 @@ pub struct TransactionData {
     pub gas_price: String,
     pub block_number: u64,
     pub timestamp: u64,
+    pub status: Option<u64>,
 }
 
 // Smart Contract Explanation
@@ -0,0 +1,577 @@
+// Monitor decision engine - evaluates Monitor thresholds against incoming feeds and raises alerts
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::indexer::{AgentDecision, DataFeed, DataType, DecisionType, RealtimeIndexer};
+use crate::storage::{DecisionQuery, SortOrder};
+use crate::telemetry;
+use crate::webhooks::{self, WebhookDispatcher, WebhookEvent, WebhookFilter, MIN_SECRET_LEN};
+
+/// Agent id of the Alert decisions raised by the engine.
+pub const MONITOR_AGENT_ID: &str = "celo-monitor-engine";
+
+/// Stored Monitor decisions loaded at startup, newest first.
+const MAX_MONITORS: usize = 10_000;
+
+/// Stored engine alerts read at startup to restore which monitors are above their threshold.
+const MAX_RESTORED_ALERTS: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkConfig {
+    Log,
+    Webhook { url: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorConfig {
+    pub enabled: bool,
+    /// Where raised alerts are delivered, in addition to being recorded as decisions.
+    pub sinks: Vec<SinkConfig>,
+    /// Signs deliveries to webhook sinks; random when unset.
+    #[serde(skip)]
+    pub webhook_secret: Option<String>,
+}
+
+impl MonitorConfig {
+    /// `MONITOR_ALERT_SINKS` is a comma separated list of `log` and `webhook=<url>` (default `log`).
+    pub fn from_env() -> Self {
+        let sinks = std::env::var("MONITOR_ALERT_SINKS").unwrap_or_else(|_| "log".to_string());
+        let webhook_secret = std::env::var("MONITOR_ALERT_WEBHOOK_SECRET").ok().filter(|s| !s.is_empty());
+        if webhook_secret.as_ref().is_some_and(|secret| secret.len() < MIN_SECRET_LEN) {
+            panic!("MONITOR_ALERT_WEBHOOK_SECRET must be at least {} characters", MIN_SECRET_LEN);
+        }
+        Self {
+            enabled: std::env::var("MONITOR_ENGINE_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            sinks: parse_sinks(&sinks).unwrap_or_else(|e| panic!("Invalid MONITOR_ALERT_SINKS: {}", e)),
+            webhook_secret,
+        }
+    }
+
+    /// Registers each `webhook=<url>` sink with the dispatcher for the engine's alerts, so they
+    /// are signed, retried and dead-lettered like any webhook registered through the API.
+    pub async fn register_webhook_sinks(&self, dispatcher: &WebhookDispatcher) -> Result<(), String> {
+        for sink in &self.sinks {
+            let SinkConfig::Webhook { url } = sink else { continue };
+            let filter = WebhookFilter {
+                agent_id: Some(MONITOR_AGENT_ID.to_string()),
+                ..Default::default()
+            };
+            let secret = self.webhook_secret.clone().unwrap_or_else(webhooks::random_secret);
+            dispatcher.register(url.clone(), secret, vec![WebhookEvent::Alert], filter).await?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_sinks(list: &str) -> Result<Vec<SinkConfig>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            None if entry == "log" => Ok(SinkConfig::Log),
+            Some(("webhook", url)) if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(SinkConfig::Webhook { url: url.to_string() })
+            }
+            _ => Err(format!("expected log or webhook=<http(s) url>, got {}", entry)),
+        })
+        .collect()
+}
+
+/// Receives every Alert the engine raises. Webhook sinks are delivered by the
+/// `WebhookDispatcher` instead (see `MonitorConfig::register_webhook_sinks`).
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &str;
+    async fn deliver(&self, alert: &AgentDecision) -> Result<(), String>;
+}
+
+pub struct LogSink;
+
+#[async_trait]
+impl AlertSink for LogSink {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn deliver(&self, alert: &AgentDecision) -> Result<(), String> {
+        if let DecisionType::Alert { severity, message, .. } = &alert.decision_type {
+            tracing::warn!("🚨 [{}] {}", severity, message);
+        }
+        Ok(())
+    }
+}
+
+fn build_sink(config: &SinkConfig) -> Option<Arc<dyn AlertSink>> {
+    match config {
+        SinkConfig::Log => Some(Arc::new(LogSink)),
+        SinkConfig::Webhook { .. } => None,
+    }
+}
+
+/// A `Monitor` metric: `[<DataType>.]<field>[.<field>...]`, e.g. `Block.gas_used`,
+/// `Block.transaction_count` or `Price.price`. Fields are looked up in `cleaned_data`,
+/// then `raw_data`; numbers, decimal strings and `0x` hex strings are accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSelector {
+    pub data_type: Option<String>,
+    pub path: Vec<String>,
+}
+
+impl MetricSelector {
+    pub fn parse(metric: &str) -> Result<Self, String> {
+        let mut segments: Vec<String> = metric.trim().split('.').map(str::to_string).collect();
+        if segments.iter().any(|s| s.trim().is_empty()) {
+            return Err(format!("Monitor metric must be [<DataType>.]<field>[.<field>...], got {:?}", metric));
+        }
+
+        // A single segment is always a field, so `price` matches the field in any feed
+        let data_type = match segments.len() {
+            1 => None,
+            _ => DataType::parse_name(&segments[0]),
+        };
+        if data_type.is_some() {
+            segments.remove(0);
+        }
+        Ok(Self { data_type, path: segments })
+    }
+
+    /// The metric's value in `feed`, if the feed carries it.
+    pub fn value(&self, feed: &DataFeed) -> Option<f64> {
+        if self.data_type.as_ref().is_some_and(|name| *name != feed.data_type.name()) {
+            return None;
+        }
+        feed.cleaned_data
+            .as_ref()
+            .and_then(|data| self.lookup(data))
+            .or_else(|| self.lookup(&feed.raw_data))
+    }
+
+    fn lookup(&self, data: &Value) -> Option<f64> {
+        let value = self.path.iter().try_fold(data, |value, field| value.get(field))?;
+        let number = match value {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => match s.strip_prefix("0x") {
+                Some(hex) => u128::from_str_radix(hex, 16).ok().map(|n| n as f64),
+                None => s.trim().parse().ok(),
+            },
+            _ => None,
+        };
+        number.filter(|n| n.is_finite())
+    }
+}
+
+/// The monitor behind an Alert raised by the engine, and the crossing that raised it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorTrigger {
+    pub agent_id: String,
+    pub metric: String,
+    /// `timestamp` of the Monitor decision, which tells apart successive monitors on a metric.
+    pub created_at: u64,
+    pub threshold: f64,
+    pub value: f64,
+    /// `true` when the metric rose to the threshold, `false` when it fell below.
+    pub above: bool,
+}
+
+/// An active Monitor decision. Each agent has at most one monitor per metric; a newer
+/// decision replaces its threshold and scope.
+#[derive(Debug, Clone, Serialize)]
+pub struct Monitor {
+    pub agent_id: String,
+    pub metric: String,
+    pub threshold: f64,
+    /// The monitor's `data_sources`; empty watches every feed carrying the metric.
+    pub feed_ids: Vec<String>,
+    pub created_at: u64,
+    pub last_value: Option<f64>,
+    pub last_feed_id: Option<String>,
+    /// Whether the metric is at or above the threshold, per watched feed (`*` when unscoped).
+    pub above: BTreeMap<String, bool>,
+    pub alerts_raised: u64,
+    pub last_alert_at: Option<u64>,
+    #[serde(skip)]
+    selector: MetricSelector,
+}
+
+impl Monitor {
+    fn from_decision(decision: &AgentDecision) -> Option<Self> {
+        let DecisionType::Monitor { metric, threshold } = &decision.decision_type else {
+            return None;
+        };
+        let selector = MetricSelector::parse(metric).ok()?;
+        Some(Self {
+            agent_id: decision.agent_id.clone(),
+            metric: metric.clone(),
+            threshold: *threshold,
+            feed_ids: decision.data_sources.clone(),
+            created_at: decision.timestamp,
+            last_value: None,
+            last_feed_id: None,
+            above: BTreeMap::new(),
+            alerts_raised: 0,
+            last_alert_at: None,
+            selector,
+        })
+    }
+
+    /// Restores the state of one series from an alert this monitor raised before a restart.
+    /// Alerts must be applied newest first; only the newest one per series counts.
+    fn restore(&mut self, alert: &AgentDecision) {
+        let Some(trigger) = alert_trigger(alert) else {
+            return;
+        };
+        if (trigger.created_at, trigger.threshold) != (self.created_at, self.threshold) {
+            return;
+        }
+        let series = match (self.feed_ids.is_empty(), alert.data_sources.first()) {
+            (true, _) => "*",
+            (false, Some(feed_id)) => feed_id.as_str(),
+            (false, None) => return,
+        };
+        self.above.entry(series.to_string()).or_insert(trigger.above);
+        self.last_alert_at.get_or_insert(alert.timestamp);
+    }
+
+    /// Records the metric's value in `feed` and returns the alert to raise if it crossed
+    /// the threshold. A metric first seen at or above the threshold counts as a crossing.
+    fn evaluate(&mut self, feed: &DataFeed, now: u64) -> Option<AgentDecision> {
+        if !self.feed_ids.is_empty() && !self.feed_ids.contains(&feed.feed_id) {
+            return None;
+        }
+        let value = self.selector.value(feed)?;
+        self.last_value = Some(value);
+        self.last_feed_id = Some(feed.feed_id.clone());
+
+        let series = if self.feed_ids.is_empty() { "*" } else { feed.feed_id.as_str() };
+        let above = value >= self.threshold;
+        let was_above = self.above.insert(series.to_string(), above).unwrap_or(false);
+        if above == was_above {
+            return None;
+        }
+
+        self.alerts_raised += 1;
+        self.last_alert_at = Some(now);
+        let (severity, direction) = if above { ("medium", "rose to") } else { ("low", "fell below") };
+        Some(AgentDecision {
+            agent_id: MONITOR_AGENT_ID.to_string(),
+            decision_type: DecisionType::Alert {
+                severity: severity.to_string(),
+                message: format!(
+                    "{} {} {} (threshold {}) in feed {}",
+                    self.metric, direction, value, self.threshold, feed.feed_id
+                ),
+                trigger: Some(MonitorTrigger {
+                    agent_id: self.agent_id.clone(),
+                    metric: self.metric.clone(),
+                    created_at: self.created_at,
+                    threshold: self.threshold,
+                    value,
+                    above,
+                }),
+            },
+            confidence: 1.0,
+            reasoning: format!(
+                "Monitor on {} set by agent {} at {} crossed its threshold of {}",
+                self.metric, self.agent_id, self.created_at, self.threshold
+            ),
+            timestamp: now,
+            data_sources: vec![feed.feed_id.clone()],
+            provenance: None,
+            seq: None,
+        })
+    }
+}
+
+/// The trigger of an alert raised by `Monitor::evaluate`.
+fn alert_trigger(alert: &AgentDecision) -> Option<&MonitorTrigger> {
+    match &alert.decision_type {
+        DecisionType::Alert { trigger: Some(trigger), .. } if alert.agent_id == MONITOR_AGENT_ID => Some(trigger),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonitorStatus {
+    pub running: bool,
+    pub feeds_evaluated: u64,
+    pub alerts_raised: u64,
+    pub sink_failures: u64,
+    /// Feeds dropped because the engine fell behind the indexer.
+    pub feeds_skipped: u64,
+    pub last_error: Option<String>,
+}
+
+type MonitorKey = (String, String); // (agent_id, metric)
+
+/// Watches new feeds and Monitor decisions, raising an Alert decision whenever a
+/// monitored metric crosses its threshold.
+pub struct MonitorEngine {
+    config: MonitorConfig,
+    monitors: Arc<RwLock<BTreeMap<MonitorKey, Monitor>>>,
+    status: Arc<RwLock<MonitorStatus>>,
+    shutdown: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl MonitorEngine {
+    pub fn spawn(config: MonitorConfig, indexer: RealtimeIndexer) -> Self {
+        let monitors = Arc::new(RwLock::new(BTreeMap::new()));
+        let status = Arc::new(RwLock::new(MonitorStatus {
+            running: true,
+            ..Default::default()
+        }));
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let sinks: Vec<Arc<dyn AlertSink>> = config.sinks.iter().filter_map(build_sink).collect();
+
+        let task = tokio::spawn(run(indexer, sinks, monitors.clone(), status.clone(), shutdown_rx));
+
+        Self {
+            config,
+            monitors,
+            status,
+            shutdown,
+            task: Mutex::new(Some(task)),
+        }
+    }
+
+    pub fn config(&self) -> &MonitorConfig {
+        &self.config
+    }
+
+    pub async fn status(&self) -> MonitorStatus {
+        self.status.read().await.clone()
+    }
+
+    pub async fn monitors(&self) -> Vec<Monitor> {
+        self.monitors.read().await.values().cloned().collect()
+    }
+
+    /// Stops the engine and waits for the task to exit.
+    pub async fn shutdown(&self) {
+        let _ = self.shutdown.send(true);
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
+
+async fn register(monitors: &RwLock<BTreeMap<MonitorKey, Monitor>>, decision: &AgentDecision) {
+    if let Some(monitor) = Monitor::from_decision(decision) {
+        let key = (monitor.agent_id.clone(), monitor.metric.clone());
+        monitors.write().await.insert(key, monitor);
+    }
+}
+
+async fn run(
+    indexer: RealtimeIndexer,
+    sinks: Vec<Arc<dyn AlertSink>>,
+    monitors: Arc<RwLock<BTreeMap<MonitorKey, Monitor>>>,
+    status: Arc<RwLock<MonitorStatus>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    // Subscribe before loading stored monitors so none recorded in between are missed
+    let mut feeds = indexer.subscribe_feeds();
+    let mut decisions = indexer.subscribe_decisions();
+
+    let stored = DecisionQuery {
+        decision_type: Some("Monitor".to_string()),
+        order: SortOrder::Desc,
+        limit: MAX_MONITORS,
+        ..Default::default()
+    };
+    match indexer.query_decisions(&stored).await {
+        Ok(stored) => {
+            for decision in stored.iter().rev() {
+                register(&monitors, decision).await;
+            }
+            restore_alert_state(&indexer, &monitors, &status).await;
+            tracing::info!("✓ Monitor engine started with {} monitors", monitors.read().await.len());
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load stored monitors: {}", e);
+            status.write().await.last_error = Some(e);
+        }
+    }
+    let sinks = Arc::new(sinks);
+
+    loop {
+        let feed = tokio::select! {
+            _ = shutdown.changed() => break,
+            decision = decisions.recv() => {
+                match decision {
+                    Ok(decision) => register(&monitors, &decision).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Monitor engine missed {} decisions; new monitors may be inactive until restart", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+                continue;
+            }
+            feed = feeds.recv() => match feed {
+                Ok(feed) => feed,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Monitor engine fell behind; {} feeds were not evaluated", skipped);
+                    status.write().await.feeds_skipped += skipped;
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let alerts: Vec<AgentDecision> = monitors
+            .write()
+            .await
+            .values_mut()
+            .filter_map(|monitor| monitor.evaluate(&feed, now))
+            .collect();
+        status.write().await.feeds_evaluated += 1;
+
+        for alert in alerts {
+            raise(&indexer, alert, sinks.clone(), status.clone()).await;
+        }
+    }
+
+    status.write().await.running = false;
+}
+
+/// Seeds each monitor's per-series state from the newest alert it raised, so monitors that were
+/// above their threshold before a restart don't alert again on their next observation.
+async fn restore_alert_state(
+    indexer: &RealtimeIndexer,
+    monitors: &RwLock<BTreeMap<MonitorKey, Monitor>>,
+    status: &RwLock<MonitorStatus>,
+) {
+    let stored = DecisionQuery {
+        agent_id: Some(MONITOR_AGENT_ID.to_string()),
+        decision_type: Some("Alert".to_string()),
+        order: SortOrder::Desc,
+        limit: MAX_RESTORED_ALERTS,
+        ..Default::default()
+    };
+    match indexer.query_decisions(&stored).await {
+        Ok(alerts) => {
+            let mut monitors = monitors.write().await;
+            for alert in &alerts {
+                let Some(trigger) = alert_trigger(alert) else { continue };
+                if let Some(monitor) = monitors.get_mut(&(trigger.agent_id.clone(), trigger.metric.clone())) {
+                    monitor.restore(alert);
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load stored monitor alerts: {}", e);
+            status.write().await.last_error = Some(e);
+        }
+    }
+}
+
+/// Records the alert as a decision, then delivers it to every sink in the background.
+async fn raise(
+    indexer: &RealtimeIndexer,
+    alert: AgentDecision,
+    sinks: Arc<Vec<Arc<dyn AlertSink>>>,
+    status: Arc<RwLock<MonitorStatus>>,
+) {
+    let alert = match indexer.process_agent_decision(alert).await {
+        Ok(recorded) => recorded,
+        Err(e) => {
+            tracing::error!("Failed to record monitor alert: {}", e);
+            status.write().await.last_error = Some(e.to_string());
+            return;
+        }
+    };
+    telemetry::MONITOR_ALERTS.inc();
+    status.write().await.alerts_raised += 1;
+
+    tokio::spawn(async move {
+        for sink in sinks.iter() {
+            if let Err(e) = sink.deliver(&alert).await {
+                tracing::warn!("Alert delivery to {} sink failed: {}", sink.name(), e);
+                telemetry::ALERT_SINK_ERRORS.with_label_values(&[sink.name()]).inc();
+                let mut status = status.write().await;
+                status.sink_failures += 1;
+                status.last_error = Some(e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::FeedSource;
+    use serde_json::json;
+
+    fn monitor(threshold: f64) -> Monitor {
+        Monitor::from_decision(&AgentDecision {
+            agent_id: "gas_watcher".to_string(),
+            decision_type: DecisionType::Monitor { metric: "Block.gas_used".to_string(), threshold },
+            confidence: 1.0,
+            reasoning: "watch gas".to_string(),
+            timestamp: 1_704_067_200,
+            data_sources: Vec::new(),
+            provenance: None,
+            seq: None,
+        })
+        .unwrap()
+    }
+
+    fn block(gas_used: u64) -> DataFeed {
+        DataFeed {
+            feed_id: format!("celo:block:{}", gas_used),
+            source: FeedSource::OnChain("0xminer".to_string()),
+            data_type: DataType::Block,
+            timestamp: 1_704_070_800,
+            raw_data: json!({ "gas_used": gas_used }),
+            cleaned_data: None,
+            seq: None,
+        }
+    }
+
+    /// An alert as read back from a store after a restart.
+    fn stored(alert: AgentDecision) -> AgentDecision {
+        serde_json::from_value(serde_json::to_value(alert).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn restores_the_side_of_the_threshold_from_the_alert_trigger() {
+        let mut before_restart = monitor(100.0);
+        let rose = stored(before_restart.evaluate(&block(150), 10).unwrap());
+        let fell = stored(before_restart.evaluate(&block(50), 20).unwrap());
+
+        let mut above = monitor(100.0);
+        above.restore(&rose);
+        assert_eq!(above.above.get("*"), Some(&true));
+        assert!(above.evaluate(&block(160), 30).is_none());
+
+        // Newest first: only the latest crossing counts
+        let mut below = monitor(100.0);
+        below.restore(&fell);
+        below.restore(&rose);
+        assert_eq!(below.above.get("*"), Some(&false));
+        assert!(below.evaluate(&block(160), 30).is_some());
+    }
+
+    #[test]
+    fn ignores_alerts_of_a_replaced_monitor() {
+        let rose = stored(monitor(100.0).evaluate(&block(150), 10).unwrap());
+        let mut replaced = monitor(200.0);
+        replaced.restore(&rose);
+        assert!(replaced.above.is_empty());
+        assert_eq!(replaced.last_alert_at, None);
+    }
+}
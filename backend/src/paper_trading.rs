@@ -0,0 +1,894 @@
+// Paper trading - simulated per-agent portfolios executing Trade decisions at indexed prices
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::cleaning::parse_number;
+use crate::indexer::{AgentDecision, DataFeed, DataType, DecisionType, RealtimeIndexer};
+use crate::storage::{DecisionQuery, FeedCursor, FeedQuery, SortOrder};
+
+/// Fills and rejected trades kept per portfolio.
+const MAX_TRADE_HISTORY: usize = 500;
+/// Stored Price feeds scanned at startup to seed the latest prices.
+const MAX_PRICE_BOOTSTRAP: usize = 10_000;
+/// Stored Trade decisions replayed at startup to rebuild portfolios, newest first.
+const MAX_REPLAYED_TRADES: usize = 100_000;
+/// Stored Price feeds scanned while replaying them.
+const MAX_REPLAYED_PRICES: usize = 1_000_000;
+/// Quantities below this are treated as zero when closing positions.
+const DUST: f64 = 1e-12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperTradingConfig {
+    pub enabled: bool,
+    pub starting_cash: f64,
+    pub fee_bps: f64, // charged on the notional of every fill
+}
+
+impl PaperTradingConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("PAPER_TRADING_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            starting_cash: std::env::var("PAPER_TRADING_STARTING_CASH")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .expect("PAPER_TRADING_STARTING_CASH must be a valid number"),
+            fee_bps: std::env::var("PAPER_TRADING_FEE_BPS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .expect("PAPER_TRADING_FEE_BPS must be a valid number"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub price: f64,
+    pub feed_id: String,
+    pub timestamp: u64,
+}
+
+/// The latest price per asset, keyed by the uppercased `asset` (or `symbol`) of Price feeds.
+#[derive(Debug, Clone, Default)]
+pub struct PriceBook {
+    prices: HashMap<String, PricePoint>,
+}
+
+impl PriceBook {
+    /// Takes the feed's price unless a newer one is already known. Returns whether it was used.
+    pub fn update(&mut self, feed: &DataFeed) -> bool {
+        let Some((asset, price)) = price_of(feed) else { return false };
+        if self.prices.get(&asset).is_some_and(|known| known.timestamp > feed.timestamp) {
+            return false;
+        }
+        self.prices.insert(
+            asset,
+            PricePoint {
+                price,
+                feed_id: feed.feed_id.clone(),
+                timestamp: feed.timestamp,
+            },
+        );
+        true
+    }
+
+    pub fn get(&self, asset: &str) -> Option<&PricePoint> {
+        self.prices.get(&asset.to_ascii_uppercase())
+    }
+
+    /// Takes each of `other`'s prices that is newer than the one known for its asset.
+    pub fn merge(&mut self, other: PriceBook) {
+        for (asset, point) in other.prices {
+            match self.prices.get(&asset) {
+                Some(known) if known.timestamp > point.timestamp => {}
+                _ => {
+                    self.prices.insert(asset, point);
+                }
+            }
+        }
+    }
+
+    pub fn asset_count(&self) -> usize {
+        self.prices.len()
+    }
+}
+
+/// `(ASSET, price)` of a Price feed that names its asset and carries a positive price.
+pub fn price_of(feed: &DataFeed) -> Option<(String, f64)> {
+    if !matches!(feed.data_type, DataType::Price) {
+        return None;
+    }
+    let data = feed.cleaned_data.as_ref().unwrap_or(&feed.raw_data);
+    let asset = data.get("asset").or_else(|| data.get("symbol"))?.as_str()?.trim();
+    let price = data.get("price").and_then(parse_number)?;
+    (!asset.is_empty() && price.is_finite() && price > 0.0).then(|| (asset.to_ascii_uppercase(), price))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn parse(action: &str) -> Option<Self> {
+        match action.trim().to_ascii_lowercase().as_str() {
+            "buy" => Some(Side::Buy),
+            "sell" => Some(Side::Sell),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub asset: String,
+    pub quantity: f64,
+    /// Volume-weighted entry price, excluding fees.
+    pub avg_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    /// The Trade decision's timestamp.
+    pub timestamp: u64,
+    pub side: Side,
+    pub asset: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub price_feed_id: String,
+    pub notional: f64,
+    pub fee: f64,
+    /// Gross PnL closed by a sell; 0 for buys.
+    pub realized_pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedTrade {
+    pub timestamp: u64,
+    pub action: String,
+    pub asset: String,
+    pub amount: f64,
+    pub reason: String,
+}
+
+/// A cash account with long-only positions. Buys need cash for the notional plus fee and
+/// sells are limited to the quantity held.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub starting_cash: f64,
+    pub cash: f64,
+    pub fee_bps: f64,
+    pub realized_pnl: f64,
+    pub fees_paid: f64,
+    pub positions: BTreeMap<String, Position>,
+    pub trades_filled: u64,
+    pub trades_rejected: u64,
+    fills: VecDeque<Fill>,
+    rejected: VecDeque<RejectedTrade>,
+}
+
+impl Portfolio {
+    pub fn new(starting_cash: f64, fee_bps: f64) -> Self {
+        Self {
+            starting_cash,
+            cash: starting_cash,
+            fee_bps,
+            realized_pnl: 0.0,
+            fees_paid: 0.0,
+            positions: BTreeMap::new(),
+            trades_filled: 0,
+            trades_rejected: 0,
+            fills: VecDeque::new(),
+            rejected: VecDeque::new(),
+        }
+    }
+
+    /// Executes a Trade decision at the latest price of its asset. Returns `None` for
+    /// other decision types; rejected trades are recorded and returned as `Err`.
+    pub fn apply(&mut self, decision: &AgentDecision, prices: &PriceBook) -> Option<Result<Fill, String>> {
+        let DecisionType::Trade { action, asset, amount } = &decision.decision_type else {
+            return None;
+        };
+
+        let result = match (Side::parse(action), prices.get(asset)) {
+            (None, _) => Err(format!("Unsupported action {:?} (expected buy or sell)", action)),
+            (_, None) => Err(format!("No indexed Price feed for {}", asset)),
+            (Some(side), Some(price)) => self.execute(side, asset, *amount, price, decision.timestamp),
+        };
+        if let Err(reason) = &result {
+            self.trades_rejected += 1;
+            push_capped(
+                &mut self.rejected,
+                RejectedTrade {
+                    timestamp: decision.timestamp,
+                    action: action.clone(),
+                    asset: asset.clone(),
+                    amount: *amount,
+                    reason: reason.clone(),
+                },
+            );
+        }
+        Some(result)
+    }
+
+    fn execute(&mut self, side: Side, asset: &str, quantity: f64, price: &PricePoint, timestamp: u64) -> Result<Fill, String> {
+        if !quantity.is_finite() || quantity <= 0.0 {
+            return Err(format!("Trade amount must be positive, got {}", quantity));
+        }
+        let asset = asset.to_ascii_uppercase();
+        let notional = quantity * price.price;
+        let fee = notional * self.fee_bps / 10_000.0;
+        let mut realized_pnl = 0.0;
+
+        match side {
+            Side::Buy => {
+                if notional + fee > self.cash + DUST {
+                    return Err(format!(
+                        "Insufficient cash: buying {} {} costs {:.4} with fees, {:.4} available",
+                        quantity, asset, notional + fee, self.cash
+                    ));
+                }
+                let position = self.positions.entry(asset.clone()).or_insert_with(|| Position {
+                    asset: asset.clone(),
+                    quantity: 0.0,
+                    avg_cost: 0.0,
+                });
+                position.avg_cost = (position.avg_cost * position.quantity + notional) / (position.quantity + quantity);
+                position.quantity += quantity;
+                self.cash -= notional + fee;
+            }
+            Side::Sell => {
+                let held = self.positions.get(&asset).map_or(0.0, |p| p.quantity);
+                if quantity > held + DUST {
+                    return Err(format!("Insufficient position: selling {} {}, {} held", quantity, asset, held));
+                }
+                let position = self.positions.get_mut(&asset).expect("checked above");
+                realized_pnl = (price.price - position.avg_cost) * quantity;
+                position.quantity -= quantity;
+                if position.quantity <= DUST {
+                    self.positions.remove(&asset);
+                }
+                self.cash += notional - fee;
+                self.realized_pnl += realized_pnl;
+            }
+        }
+
+        self.fees_paid += fee;
+        self.trades_filled += 1;
+        let fill = Fill {
+            timestamp,
+            side,
+            asset,
+            quantity,
+            price: price.price,
+            price_feed_id: price.feed_id.clone(),
+            notional,
+            fee,
+            realized_pnl,
+        };
+        push_capped(&mut self.fills, fill.clone());
+        Ok(fill)
+    }
+
+    /// Marks every position to `prices`; positions without a known price are valued at cost.
+    pub fn valuation(&self, prices: &PriceBook) -> Valuation {
+        let positions: Vec<PositionValuation> = self
+            .positions
+            .values()
+            .map(|position| {
+                let mark = prices.get(&position.asset);
+                PositionValuation {
+                    asset: position.asset.clone(),
+                    quantity: position.quantity,
+                    avg_cost: position.avg_cost,
+                    mark_price: mark.map(|p| p.price),
+                    price_feed_id: mark.map(|p| p.feed_id.clone()),
+                    price_timestamp: mark.map(|p| p.timestamp),
+                    market_value: position.quantity * mark.map_or(position.avg_cost, |p| p.price),
+                    unrealized_pnl: mark.map_or(0.0, |p| (p.price - position.avg_cost) * position.quantity),
+                }
+            })
+            .collect();
+
+        let equity = self.cash + positions.iter().map(|p| p.market_value).sum::<f64>();
+        let total_pnl = equity - self.starting_cash;
+        Valuation {
+            cash: self.cash,
+            equity,
+            realized_pnl: self.realized_pnl,
+            unrealized_pnl: positions.iter().map(|p| p.unrealized_pnl).sum(),
+            fees_paid: self.fees_paid,
+            total_pnl,
+            return_pct: if self.starting_cash > 0.0 { total_pnl / self.starting_cash * 100.0 } else { 0.0 },
+            positions,
+        }
+    }
+
+    /// Most recent fills, newest first.
+    pub fn recent_fills(&self, limit: usize) -> Vec<Fill> {
+        self.fills.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Most recent rejected trades, newest first.
+    pub fn recent_rejections(&self, limit: usize) -> Vec<RejectedTrade> {
+        self.rejected.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+fn push_capped<T>(history: &mut VecDeque<T>, item: T) {
+    if history.len() >= MAX_TRADE_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(item);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionValuation {
+    pub asset: String,
+    pub quantity: f64,
+    pub avg_cost: f64,
+    /// `None` when no Price feed for the asset is known; the position is then valued at cost.
+    pub mark_price: Option<f64>,
+    pub price_feed_id: Option<String>,
+    pub price_timestamp: Option<u64>,
+    pub market_value: f64,
+    pub unrealized_pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Valuation {
+    pub cash: f64,
+    pub equity: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub fees_paid: f64,
+    /// `equity - starting_cash`, net of fees.
+    pub total_pnl: f64,
+    pub return_pct: f64,
+    pub positions: Vec<PositionValuation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioReport {
+    pub agent_id: String,
+    pub starting_cash: f64,
+    pub fee_bps: f64,
+    #[serde(flatten)]
+    pub valuation: Valuation,
+    pub trades_filled: u64,
+    pub trades_rejected: u64,
+    pub recent_fills: Vec<Fill>,
+    pub recent_rejections: Vec<RejectedTrade>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSummary {
+    pub agent_id: String,
+    pub equity: f64,
+    pub total_pnl: f64,
+    pub return_pct: f64,
+    pub trades_filled: u64,
+    pub trades_rejected: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaperTraderStatus {
+    pub running: bool,
+    pub portfolios: usize,
+    pub assets_priced: usize,
+    pub trades_filled: u64,
+    pub trades_rejected: u64,
+    /// Stored Trade decisions replayed at startup; included in the totals above.
+    pub trades_replayed: u64,
+    /// Feeds or decisions dropped because the simulator fell behind the indexer.
+    pub events_skipped: u64,
+}
+
+#[derive(Default)]
+struct Book {
+    prices: PriceBook,
+    portfolios: HashMap<String, Portfolio>,
+}
+
+/// Keeps a `Portfolio` per agent, filled by every Trade decision. Portfolios are held in memory
+/// and rebuilt at startup by replaying stored Trade decisions against stored Price feeds.
+pub struct PaperTrader {
+    config: PaperTradingConfig,
+    book: Arc<RwLock<Book>>,
+    status: Arc<RwLock<PaperTraderStatus>>,
+    shutdown: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl PaperTrader {
+    pub fn spawn(config: PaperTradingConfig, indexer: RealtimeIndexer) -> Self {
+        let book = Arc::new(RwLock::new(Book::default()));
+        let status = Arc::new(RwLock::new(PaperTraderStatus {
+            running: true,
+            ..Default::default()
+        }));
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let task = tokio::spawn(run(config.clone(), indexer, book.clone(), status.clone(), shutdown_rx));
+
+        Self {
+            config,
+            book,
+            status,
+            shutdown,
+            task: Mutex::new(Some(task)),
+        }
+    }
+
+    pub fn config(&self) -> &PaperTradingConfig {
+        &self.config
+    }
+
+    pub async fn status(&self) -> PaperTraderStatus {
+        let mut status = self.status.read().await.clone();
+        let book = self.book.read().await;
+        status.portfolios = book.portfolios.len();
+        status.assets_priced = book.prices.asset_count();
+        status
+    }
+
+    /// The agent's portfolio marked to the latest prices, or `None` if it has not traded.
+    pub async fn report(&self, agent_id: &str, history: usize) -> Option<PortfolioReport> {
+        let book = self.book.read().await;
+        let portfolio = book.portfolios.get(agent_id)?;
+        Some(PortfolioReport {
+            agent_id: agent_id.to_string(),
+            starting_cash: portfolio.starting_cash,
+            fee_bps: portfolio.fee_bps,
+            valuation: portfolio.valuation(&book.prices),
+            trades_filled: portfolio.trades_filled,
+            trades_rejected: portfolio.trades_rejected,
+            recent_fills: portfolio.recent_fills(history),
+            recent_rejections: portfolio.recent_rejections(history),
+        })
+    }
+
+    /// Every portfolio marked to the latest prices, best `total_pnl` first.
+    pub async fn summaries(&self) -> Vec<PortfolioSummary> {
+        let book = self.book.read().await;
+        let mut summaries: Vec<PortfolioSummary> = book
+            .portfolios
+            .iter()
+            .map(|(agent_id, portfolio)| {
+                let valuation = portfolio.valuation(&book.prices);
+                PortfolioSummary {
+                    agent_id: agent_id.clone(),
+                    equity: valuation.equity,
+                    total_pnl: valuation.total_pnl,
+                    return_pct: valuation.return_pct,
+                    trades_filled: portfolio.trades_filled,
+                    trades_rejected: portfolio.trades_rejected,
+                }
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.total_pnl.total_cmp(&a.total_pnl));
+        summaries
+    }
+
+    /// Stops the simulator and waits for the task to exit.
+    pub async fn shutdown(&self) {
+        let _ = self.shutdown.send(true);
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
+
+/// Latest stored price per asset up to `until`, scanning Price feeds newest first.
+async fn load_prices(indexer: &RealtimeIndexer, until: Option<u64>) -> Result<PriceBook, String> {
+    let mut prices = PriceBook::default();
+    let mut query = FeedQuery {
+        data_type: Some(DataType::Price.name()),
+        until,
+        order: SortOrder::Desc,
+        limit: 500,
+        ..Default::default()
+    };
+    let mut scanned = 0;
+
+    loop {
+        let page = indexer.query_feeds(query.clone()).await?;
+        scanned += page.feeds.len();
+        for feed in &page.feeds {
+            prices.update(feed);
+        }
+        if page.next_cursor.is_none() || scanned >= MAX_PRICE_BOOTSTRAP {
+            break;
+        }
+        query.cursor = page.feeds.last().map(FeedCursor::after);
+    }
+    Ok(prices)
+}
+
+/// Portfolios rebuilt from stored history, with the `seq` of every replayed trade so the
+/// same decisions arriving live during the replay are not applied twice.
+#[derive(Default)]
+struct Replayed {
+    book: Book,
+    trades: HashSet<u64>,
+    filled: u64,
+    rejected: u64,
+}
+
+/// Replays stored Trade decisions and Price feeds in timestamp order, as a backtest does:
+/// each trade fills at the latest Price feed at or before its own timestamp.
+async fn replay_history(config: &PaperTradingConfig, indexer: &RealtimeIndexer) -> Result<Replayed, String> {
+    let query = DecisionQuery {
+        decision_type: Some("Trade".to_string()),
+        order: SortOrder::Desc,
+        limit: MAX_REPLAYED_TRADES,
+        ..Default::default()
+    };
+    let mut trades = indexer.query_decisions(&query).await?;
+    if trades.len() == MAX_REPLAYED_TRADES {
+        tracing::warn!("Paper trading replays only the newest {} stored trades", MAX_REPLAYED_TRADES);
+    }
+    // Oldest first; equal timestamps back in recording order
+    trades.reverse();
+
+    let mut replayed = Replayed::default();
+    let Some(first) = trades.first() else {
+        return Ok(replayed);
+    };
+    if let Some(until) = first.timestamp.checked_sub(1) {
+        replayed.book.prices = load_prices(indexer, Some(until)).await?;
+    }
+
+    let mut prices = FeedQuery {
+        data_type: Some(DataType::Price.name()),
+        since: Some(first.timestamp),
+        order: SortOrder::Asc,
+        limit: 500,
+        ..Default::default()
+    };
+    let mut page: VecDeque<DataFeed> = VecDeque::new();
+    let mut scanned = 0;
+    let mut exhausted = false;
+
+    for trade in &trades {
+        // Prices at the trade's own timestamp are applied first, like a feed ingested just before it
+        loop {
+            if page.is_empty() && !exhausted {
+                let next = indexer.query_feeds(prices.clone()).await?;
+                scanned += next.feeds.len();
+                prices.cursor = next.feeds.last().map(FeedCursor::after);
+                exhausted = next.next_cursor.is_none() || scanned >= MAX_REPLAYED_PRICES;
+                if exhausted && next.next_cursor.is_some() {
+                    tracing::warn!("Paper trading replay stopped reading prices after {} feeds", scanned);
+                }
+                page.extend(next.feeds);
+            }
+            match page.front() {
+                Some(feed) if feed.timestamp <= trade.timestamp => {
+                    replayed.book.prices.update(feed);
+                    page.pop_front();
+                }
+                _ => break,
+            }
+        }
+
+        let portfolio = replayed
+            .book
+            .portfolios
+            .entry(trade.agent_id.clone())
+            .or_insert_with(|| Portfolio::new(config.starting_cash, config.fee_bps));
+        match portfolio.apply(trade, &replayed.book.prices) {
+            Some(Ok(_)) => replayed.filled += 1,
+            Some(Err(_)) => replayed.rejected += 1,
+            None => {}
+        }
+        replayed.trades.extend(trade.seq);
+    }
+    Ok(replayed)
+}
+
+async fn run(
+    config: PaperTradingConfig,
+    indexer: RealtimeIndexer,
+    book: Arc<RwLock<Book>>,
+    status: Arc<RwLock<PaperTraderStatus>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut feeds = indexer.subscribe_feeds();
+    let mut decisions = indexer.subscribe_decisions();
+
+    let mut replayed_trades = HashSet::new();
+    match replay_history(&config, &indexer).await {
+        Ok(replayed) => {
+            tracing::info!(
+                "✓ Paper trading rebuilt {} portfolios from {} stored trades",
+                replayed.book.portfolios.len(),
+                replayed.filled + replayed.rejected
+            );
+            *book.write().await = replayed.book;
+            let mut status = status.write().await;
+            status.trades_filled += replayed.filled;
+            status.trades_rejected += replayed.rejected;
+            status.trades_replayed = replayed.filled + replayed.rejected;
+            replayed_trades = replayed.trades;
+        }
+        Err(e) => tracing::warn!("Failed to replay stored trades for paper trading: {}", e),
+    }
+
+    match load_prices(&indexer, None).await {
+        Ok(prices) => {
+            tracing::info!("✓ Paper trading started with prices for {} assets", prices.asset_count());
+            // The replay only read prices up to the last stored trade
+            book.write().await.prices.merge(prices);
+        }
+        Err(e) => tracing::warn!("Failed to load stored prices for paper trading: {}", e),
+    }
+
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => break,
+            feed = feeds.recv() => match feed {
+                Ok(feed) => {
+                    book.write().await.prices.update(&feed);
+                }
+                Err(RecvError::Lagged(skipped)) => status.write().await.events_skipped += skipped,
+                Err(RecvError::Closed) => break,
+            },
+            decision = decisions.recv() => match decision {
+                Ok(decision) => {
+                    if !matches!(decision.decision_type, DecisionType::Trade { .. }) {
+                        continue;
+                    }
+                    // Recorded while the history was replayed, and already part of it
+                    if decision.seq.is_some_and(|seq| replayed_trades.remove(&seq)) {
+                        continue;
+                    }
+                    let mut book = book.write().await;
+                    // Apply prices ingested before the trade that are still queued
+                    loop {
+                        match feeds.try_recv() {
+                            Ok(feed) => {
+                                book.prices.update(&feed);
+                            }
+                            Err(TryRecvError::Lagged(skipped)) => status.write().await.events_skipped += skipped,
+                            Err(_) => break,
+                        }
+                    }
+
+                    let Book { prices, portfolios } = &mut *book;
+                    let portfolio = portfolios
+                        .entry(decision.agent_id.clone())
+                        .or_insert_with(|| Portfolio::new(config.starting_cash, config.fee_bps));
+                    match portfolio.apply(&decision, prices) {
+                        Some(Ok(fill)) => {
+                            tracing::debug!(
+                                "Paper trade for {}: {:?} {} {} @ {}",
+                                decision.agent_id, fill.side, fill.quantity, fill.asset, fill.price
+                            );
+                            status.write().await.trades_filled += 1;
+                        }
+                        Some(Err(reason)) => {
+                            tracing::debug!("Paper trade for {} rejected: {}", decision.agent_id, reason);
+                            status.write().await.trades_rejected += 1;
+                        }
+                        None => {}
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Paper trading fell behind; {} decisions were not simulated", skipped);
+                    status.write().await.events_skipped += skipped;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    status.write().await.running = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::FeedSource;
+    use serde_json::json;
+
+    fn point(price: f64) -> PricePoint {
+        PricePoint { price, feed_id: "price:celo".to_string(), timestamp: 100 }
+    }
+
+    fn price_feed(feed_id: &str, asset: &str, price: f64, timestamp: u64) -> DataFeed {
+        DataFeed {
+            feed_id: feed_id.to_string(),
+            source: FeedSource::Oracle("paper-tests".to_string()),
+            data_type: DataType::Price,
+            timestamp,
+            raw_data: json!({ "asset": asset, "price": price }),
+            cleaned_data: None,
+            seq: None,
+        }
+    }
+
+    fn trade(action: &str, asset: &str, amount: f64, timestamp: u64) -> AgentDecision {
+        AgentDecision {
+            agent_id: "trader".to_string(),
+            decision_type: DecisionType::Trade {
+                action: action.to_string(),
+                asset: asset.to_string(),
+                amount,
+            },
+            confidence: 0.9,
+            reasoning: "test trade".to_string(),
+            timestamp,
+            data_sources: Vec::new(),
+            provenance: None,
+            seq: None,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn buys_average_the_cost_and_pay_fees() {
+        let mut portfolio = Portfolio::new(10_000.0, 10.0);
+        let fill = portfolio.execute(Side::Buy, "celo", 100.0, &point(2.0), 1).unwrap();
+        assert_eq!((fill.asset.as_str(), fill.notional), ("CELO", 200.0));
+        assert_close(fill.fee, 0.2);
+        portfolio.execute(Side::Buy, "CELO", 100.0, &point(4.0), 2).unwrap();
+
+        let position = &portfolio.positions["CELO"];
+        assert_eq!((position.quantity, position.avg_cost), (200.0, 3.0));
+        assert_close(portfolio.cash, 10_000.0 - 600.0 - 0.6);
+        assert_close(portfolio.fees_paid, 0.6);
+        assert_eq!(portfolio.trades_filled, 2);
+    }
+
+    #[test]
+    fn buys_need_cash_for_the_notional_and_fee() {
+        let mut portfolio = Portfolio::new(1_000.0, 100.0);
+        let err = portfolio.execute(Side::Buy, "CELO", 1_000.0, &point(1.0), 1).unwrap_err();
+        assert!(err.starts_with("Insufficient cash"), "{}", err);
+        assert!(portfolio.positions.is_empty());
+        assert_eq!((portfolio.cash, portfolio.trades_filled), (1_000.0, 0));
+
+        assert!(portfolio.execute(Side::Buy, "CELO", 990.0, &point(1.0), 1).is_ok());
+        assert_close(portfolio.cash, 0.1);
+    }
+
+    #[test]
+    fn partial_sells_realize_pnl_against_the_average_cost() {
+        let mut portfolio = Portfolio::new(10_000.0, 10.0);
+        portfolio.execute(Side::Buy, "CELO", 200.0, &point(3.0), 1).unwrap();
+        let cash = portfolio.cash;
+
+        let fill = portfolio.execute(Side::Sell, "CELO", 50.0, &point(5.0), 2).unwrap();
+        assert_eq!(fill.realized_pnl, 100.0);
+        assert_close(portfolio.cash, cash + 250.0 - 0.25);
+        assert_eq!(portfolio.realized_pnl, 100.0);
+        let position = &portfolio.positions["CELO"];
+        assert_eq!((position.quantity, position.avg_cost), (150.0, 3.0));
+
+        let err = portfolio.execute(Side::Sell, "CELO", 151.0, &point(5.0), 3).unwrap_err();
+        assert!(err.starts_with("Insufficient position"), "{}", err);
+        assert!(portfolio.execute(Side::Sell, "CUSD", 1.0, &point(1.0), 3).is_err());
+    }
+
+    #[test]
+    fn selling_down_to_dust_closes_the_position() {
+        let mut portfolio = Portfolio::new(10_000.0, 0.0);
+        portfolio.execute(Side::Buy, "CELO", 0.1, &point(1.0), 1).unwrap();
+        portfolio.execute(Side::Buy, "CELO", 0.2, &point(1.0), 2).unwrap();
+        // 0.1 + 0.2 is a hair above 0.3
+        assert!(portfolio.positions["CELO"].quantity > 0.3);
+
+        portfolio.execute(Side::Sell, "CELO", 0.3, &point(1.0), 3).unwrap();
+        assert!(portfolio.positions.is_empty());
+    }
+
+    #[test]
+    fn apply_records_rejections_and_ignores_other_decisions() {
+        let mut portfolio = Portfolio::new(10_000.0, 10.0);
+        let prices = PriceBook::default();
+        let result = portfolio.apply(&trade("buy", "CELO", 1.0, 10), &prices).unwrap();
+        assert_eq!(result.unwrap_err(), "No indexed Price feed for CELO");
+        assert!(portfolio.apply(&trade("hold", "CELO", 1.0, 11), &prices).unwrap().is_err());
+
+        let mut alert = trade("buy", "CELO", 1.0, 12);
+        alert.decision_type = DecisionType::Alert {
+            severity: "low".to_string(),
+            message: "not a trade".to_string(),
+            trigger: None,
+        };
+        assert!(portfolio.apply(&alert, &prices).is_none());
+
+        assert_eq!(portfolio.trades_rejected, 2);
+        let rejections = portfolio.recent_rejections(10);
+        assert_eq!(rejections.iter().map(|r| r.timestamp).collect::<Vec<u64>>(), [11, 10]);
+    }
+
+    #[test]
+    fn valuation_marks_positions_and_values_unpriced_ones_at_cost() {
+        let mut portfolio = Portfolio::new(10_000.0, 0.0);
+        portfolio.execute(Side::Buy, "CELO", 100.0, &point(2.0), 1).unwrap();
+        portfolio.execute(Side::Buy, "CUSD", 50.0, &point(1.0), 1).unwrap();
+
+        let mut prices = PriceBook::default();
+        prices.update(&price_feed("price:celo", "CELO", 2.5, 200));
+        let valuation = portfolio.valuation(&prices);
+
+        let celo = &valuation.positions[0];
+        assert_eq!((celo.asset.as_str(), celo.mark_price, celo.price_timestamp), ("CELO", Some(2.5), Some(200)));
+        assert_eq!((celo.market_value, celo.unrealized_pnl), (250.0, 50.0));
+        let cusd = &valuation.positions[1];
+        assert_eq!((cusd.asset.as_str(), cusd.mark_price), ("CUSD", None));
+        assert_eq!((cusd.market_value, cusd.unrealized_pnl), (50.0, 0.0));
+
+        assert_eq!(valuation.cash, 9_750.0);
+        assert_eq!((valuation.equity, valuation.unrealized_pnl), (10_050.0, 50.0));
+        assert_eq!((valuation.total_pnl, valuation.return_pct), (50.0, 0.5));
+    }
+
+    #[test]
+    fn price_book_keeps_the_newest_price_per_asset() {
+        let mut prices = PriceBook::default();
+        assert!(prices.update(&price_feed("a", "celo", 1.0, 200)));
+        assert!(!prices.update(&price_feed("b", "CELO", 2.0, 100)));
+        assert!(prices.update(&price_feed("c", "Celo", 3.0, 200)));
+        assert_eq!(prices.get("cElO").map(|p| (p.price, p.feed_id.as_str())), Some((3.0, "c")));
+
+        let mut symbol = price_feed("d", "", 1.0, 100);
+        symbol.raw_data = json!({ "symbol": "cusd", "price": "1.01" });
+        assert!(prices.update(&symbol));
+        assert!(!prices.update(&price_feed("e", "BTC", 0.0, 100)));
+        let mut block = price_feed("f", "ETH", 1.0, 100);
+        block.data_type = DataType::Block;
+        assert!(!prices.update(&block));
+        assert_eq!(prices.asset_count(), 2);
+
+        let mut other = PriceBook::default();
+        other.update(&price_feed("g", "CELO", 9.0, 150));
+        other.update(&price_feed("h", "CUSD", 1.02, 300));
+        prices.merge(other);
+        assert_eq!(prices.get("CELO").unwrap().price, 3.0);
+        assert_eq!(prices.get("CUSD").unwrap().price, 1.02);
+    }
+
+    #[tokio::test]
+    async fn replay_fills_trades_in_timestamp_order_at_the_prices_of_their_time() {
+        let indexer = RealtimeIndexer::new();
+        for (feed_id, price, timestamp) in [("p1", 1.0, 100), ("p2", 2.0, 200), ("p3", 4.0, 300)] {
+            indexer.ingest_feed(price_feed(feed_id, "CELO", price, timestamp)).await.unwrap();
+        }
+        // Recorded out of timestamp order: the sell only fills if the buy is replayed first
+        for decision in [
+            trade("sell", "CELO", 10.0, 250),
+            trade("buy", "CELO", 10.0, 150),
+            trade("buy", "CELO", 10.0, 200),
+            trade("buy", "CELO", 10.0, 200),
+        ] {
+            indexer.process_agent_decision(decision).await.unwrap();
+        }
+
+        let config = PaperTradingConfig { enabled: true, starting_cash: 1_000.0, fee_bps: 0.0 };
+        let replayed = replay_history(&config, &indexer).await.unwrap();
+        assert_eq!((replayed.filled, replayed.rejected), (4, 0));
+        // Identical trades are told apart by their seq
+        assert_eq!(replayed.trades.len(), 4);
+
+        let portfolio = &replayed.book.portfolios["trader"];
+        let fills: Vec<(Side, f64)> = portfolio.recent_fills(10).iter().rev().map(|f| (f.side, f.price)).collect();
+        assert_eq!(fills, [(Side::Buy, 1.0), (Side::Buy, 2.0), (Side::Buy, 2.0), (Side::Sell, 2.0)]);
+        assert_eq!(portfolio.positions["CELO"].quantity, 20.0);
+        // Prices after the last trade are not part of the replay
+        assert_eq!(replayed.book.prices.get("CELO").unwrap().price, 2.0);
+    }
+}
@@ -0,0 +1,252 @@
+// Retention policies for indexed feeds and agent decisions, enforced by a background task
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::indexer::{DataType, FeedSource, RealtimeIndexer};
+use crate::storage::FeedQuery;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionLimits {
+    pub max_age_secs: Option<u64>, // by feed/decision `timestamp`
+    pub max_count: Option<usize>,  // newest entries kept
+}
+
+impl RetentionLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_age_secs.is_none() && self.max_count.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedRetentionRule {
+    /// `*`, a data type (`Price`, `Custom:<name>`) or a source (`Oracle`, `Oracle:<value>`).
+    pub selector: String,
+    #[serde(flatten)]
+    pub limits: RetentionLimits,
+    #[serde(skip)]
+    pub filter: FeedQuery,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPolicy {
+    pub interval_secs: u64,
+    /// Applied independently: a feed matching several rules is evicted by whichever is stricter.
+    pub feeds: Vec<FeedRetentionRule>,
+    pub decisions: RetentionLimits,
+}
+
+impl RetentionPolicy {
+    /// Feed rules come from `FEED_RETENTION_MAX_AGE_SECS` and `FEED_RETENTION_MAX_COUNT`, each a
+    /// comma separated list of `selector=value`. Decision limits apply on top of the in-memory
+    /// store's own `MAX_DECISIONS_IN_MEMORY` cap.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let number = |name: &str| -> Option<u64> {
+            std::env::var(name)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse().unwrap_or_else(|_| panic!("{} must be a valid number", name)))
+        };
+
+        Self {
+            interval_secs: number("RETENTION_INTERVAL_SECS").unwrap_or(60).max(1),
+            feeds: parse_feed_rules(&var("FEED_RETENTION_MAX_AGE_SECS"), &var("FEED_RETENTION_MAX_COUNT"))
+                .unwrap_or_else(|e| panic!("Invalid feed retention policy: {}", e)),
+            decisions: RetentionLimits {
+                max_age_secs: number("DECISION_RETENTION_MAX_AGE_SECS"),
+                max_count: number("DECISION_RETENTION_MAX_COUNT").map(|count| count as usize),
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.feeds.is_empty() && self.decisions.is_unlimited()
+    }
+}
+
+/// Merges the age and count lists into one rule per selector, in first-seen order.
+fn parse_feed_rules(max_age: &str, max_count: &str) -> Result<Vec<FeedRetentionRule>, String> {
+    let mut rules: Vec<FeedRetentionRule> = Vec::new();
+
+    for (list, is_age) in [(max_age, true), (max_count, false)] {
+        for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (selector, value) = entry
+                .rsplit_once('=')
+                .ok_or_else(|| format!("expected selector=value, got {}", entry))?;
+            let value: u64 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid number in {}", entry))?;
+            let filter = parse_selector(selector.trim())?;
+            let selector = canonical_selector(&filter);
+
+            let index = match rules.iter().position(|rule| rule.selector == selector) {
+                Some(index) => index,
+                None => {
+                    rules.push(FeedRetentionRule {
+                        selector,
+                        limits: RetentionLimits::default(),
+                        filter,
+                    });
+                    rules.len() - 1
+                }
+            };
+            let limits = &mut rules[index].limits;
+            if is_age {
+                limits.max_age_secs = Some(value);
+            } else {
+                limits.max_count = Some(value as usize);
+            }
+        }
+    }
+    Ok(rules)
+}
+
+/// `*`, a `DataType` name or `Custom:<name>`, or a source kind optionally with `:<value>`.
+fn parse_selector(selector: &str) -> Result<FeedQuery, String> {
+    if selector == "*" {
+        return Ok(FeedQuery::default());
+    }
+    if let Some(data_type) = DataType::parse_name(selector) {
+        return Ok(FeedQuery {
+            data_type: Some(data_type),
+            ..Default::default()
+        });
+    }
+
+    let (kind, value) = match selector.split_once(':') {
+        Some((kind, value)) => (kind, Some(value.to_string())),
+        None => (selector, None),
+    };
+    match FeedSource::parse_kind(kind) {
+        Some(kind) => Ok(FeedQuery {
+            source_kind: Some(kind.to_string()),
+            source_value: value,
+            ..Default::default()
+        }),
+        None => Err(format!(
+            "unknown selector {} (expected *, one of {}, Custom:<name>, or a source kind: {})",
+            selector,
+            DataType::NAMES.join(", "),
+            FeedSource::KINDS.join(", ")
+        )),
+    }
+}
+
+fn canonical_selector(filter: &FeedQuery) -> String {
+    match (&filter.data_type, &filter.source_kind, &filter.source_value) {
+        (Some(data_type), _, _) => data_type.clone(),
+        (None, Some(kind), Some(value)) => format!("{}:{}", kind, value),
+        (None, Some(kind), None) => kind.clone(),
+        _ => "*".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub ran_at: u64,
+    pub duration_ms: f64,
+    pub feeds_evicted: usize,
+    pub feeds_evicted_by_rule: BTreeMap<String, usize>,
+    pub decisions_evicted: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionStatus {
+    pub running: bool,
+    pub runs: u64,
+    pub feeds_evicted: u64,
+    pub decisions_evicted: u64,
+    pub last_run: Option<RetentionReport>,
+    pub last_error: Option<String>,
+}
+
+/// Periodically applies a `RetentionPolicy` to the indexer's stores.
+pub struct RetentionTask {
+    policy: RetentionPolicy,
+    status: Arc<RwLock<RetentionStatus>>,
+    shutdown: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl RetentionTask {
+    pub fn spawn(policy: RetentionPolicy, indexer: RealtimeIndexer) -> Self {
+        let status = Arc::new(RwLock::new(RetentionStatus {
+            running: true,
+            ..Default::default()
+        }));
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let task = tokio::spawn(run(policy.clone(), indexer, status.clone(), shutdown_rx));
+
+        Self {
+            policy,
+            status,
+            shutdown,
+            task: Mutex::new(Some(task)),
+        }
+    }
+
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    pub async fn status(&self) -> RetentionStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Stops the task and waits for an in-progress pass to finish.
+    pub async fn shutdown(&self) {
+        let _ = self.shutdown.send(true);
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
+
+async fn run(
+    policy: RetentionPolicy,
+    indexer: RealtimeIndexer,
+    status: Arc<RwLock<RetentionStatus>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(policy.interval_secs));
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.changed() => break,
+        }
+
+        match indexer.enforce_retention(&policy).await {
+            Ok(report) => {
+                if report.feeds_evicted + report.decisions_evicted > 0 {
+                    tracing::info!(
+                        "Retention evicted {} feeds and {} decisions",
+                        report.feeds_evicted,
+                        report.decisions_evicted
+                    );
+                }
+                let mut status = status.write().await;
+                status.runs += 1;
+                status.feeds_evicted += report.feeds_evicted as u64;
+                status.decisions_evicted += report.decisions_evicted as u64;
+                status.last_run = Some(report);
+                status.last_error = None;
+            }
+            Err(e) => {
+                tracing::warn!("Retention pass failed: {}", e);
+                let mut status = status.write().await;
+                status.runs += 1;
+                status.last_error = Some(e);
+            }
+        }
+    }
+
+    status.write().await.running = false;
+}
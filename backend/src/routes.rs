@@ -1,4 +1,5 @@
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
@@ -13,10 +14,55 @@ pub fn api_routes(state: AppState) -> Router {
         // ============ Real-Time Indexer ============
         .route("/indexer/metrics", get(handlers::get_indexer_metrics))
         .route("/indexer/feeds", get(handlers::get_data_feeds))
+        .route("/indexer/feeds/:feed_id", get(handlers::get_data_feed))
+        .route("/indexer/feeds/:feed_id/history", get(handlers::get_data_feed_history))
         .route("/indexer/ingest", post(handlers::ingest_data_feed))
+        .route("/indexer/ingest/batch",
+            post(handlers::ingest_data_feed_batch)
+            .layer(DefaultBodyLimit::max(handlers::INGEST_BATCH_BODY_LIMIT)))
+        .route("/indexer/ingest/stream", post(handlers::ingest_data_feed_stream))
+        .route("/indexer/schemas", get(handlers::list_feed_schemas))
+        .route("/indexer/schemas/:type_name",
+            get(handlers::get_feed_schema)
+            .put(handlers::put_feed_schema)
+            .delete(handlers::delete_feed_schema))
+        .route("/indexer/retention", get(handlers::get_retention_status))
+        .route("/indexer/follower", get(handlers::get_follower_status))
+        .route("/indexer/follower/stop", post(handlers::stop_follower))
+        .route("/indexer/backfill",
+            get(handlers::list_backfill_jobs)
+            .post(handlers::start_backfill))
+        .route("/indexer/backfill/:job_id",
+            get(handlers::get_backfill_job)
+            .delete(handlers::cancel_backfill_job))
         .route("/indexer/agents/decisions", 
             get(handlers::get_agent_decisions)
             .post(handlers::submit_agent_decision))
+        .route("/indexer/agents/stats", get(handlers::get_agent_stats))
+        .route("/indexer/monitors", get(handlers::get_monitors))
+        .route("/agents/portfolios", get(handlers::list_agent_portfolios))
+        .route("/agents/:agent_id/portfolio", get(handlers::get_agent_portfolio))
+        .route("/agents/backtests",
+            get(handlers::list_backtests)
+            .post(handlers::start_backtest))
+        .route("/agents/backtests/:backtest_id",
+            get(handlers::get_backtest)
+            .delete(handlers::cancel_backtest))
+        
+        // ============ Webhooks ============
+        .route("/webhooks",
+            get(handlers::list_webhooks)
+            .post(handlers::register_webhook))
+        .route("/webhooks/dead-letters", get(handlers::list_dead_letters))
+        .route("/webhooks/dead-letters/:delivery_id/retry", post(handlers::retry_dead_letter))
+        .route("/webhooks/:webhook_id",
+            get(handlers::get_webhook)
+            .delete(handlers::delete_webhook))
+        .route("/webhooks/:webhook_id/deliveries", get(handlers::get_webhook_deliveries))
+        
+        // ============ Live Streams (SSE / WebSocket) ============
+        .route("/stream/feeds", get(handlers::stream_feeds))
+        .route("/stream/decisions", get(handlers::stream_decisions))
         
         // ============ Celo-7B AI Engine ============
         .route("/ai/query", post(handlers::celo_llm_query))
@@ -29,6 +75,8 @@ pub fn api_routes(state: AppState) -> Router {
         .route("/blocks", get(handlers::get_blocks))
         .route("/blocks/:block_number", get(handlers::get_block))
         .route("/transactions", get(handlers::get_transactions))
+        .route("/transactions/:hash", get(handlers::get_transaction))
+        .route("/address/:address", get(handlers::get_address))
         
         // ============ Price Data ============
         .route("/price/:asset", get(handlers::get_price_data))
@@ -43,3 +91,10 @@ pub fn api_routes(state: AppState) -> Router {
         
         .with_state(state)
 }
+
+/// Served at the root so Prometheus can scrape `/metrics` directly.
+pub fn metrics_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/metrics", get(handlers::prometheus_metrics))
+        .with_state(state)
+}
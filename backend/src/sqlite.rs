@@ -0,0 +1,616 @@
+// Embedded SQLite persistence for single-binary deployments (enabled by the `sqlite` feature)
+use async_trait::async_trait;
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::types::Json;
+use sqlx::{QueryBuilder, Row};
+use std::str::FromStr;
+
+use crate::celo_client::{CeloBlock, CeloTransaction};
+use crate::indexer::{AgentDecision, DataFeed, DataType};
+use crate::storage::{DecisionGroup, DecisionQuery, DecisionStore, FeedQuery, FeedStore, FeedVersion, PutOutcome, SchemaStore, SortOrder, WriteStatus};
+
+#[derive(Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+    /// A single connection every write goes through. SQLite allows one writer at a time, and a
+    /// deferred transaction that reads before writing fails with `SQLITE_BUSY_SNAPSHOT` when
+    /// another connection commits in between, so writes queue here instead of racing.
+    writer: SqlitePool,
+}
+
+impl SqliteStore {
+    /// Opens (creating if needed) the database file at `path` and applies pending migrations.
+    /// `:memory:` opens a private in-memory database instead.
+    pub async fn open(path: &str) -> Result<Self, String> {
+        if path == ":memory:" {
+            return Self::memory().await;
+        }
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .foreign_keys(true);
+
+        Self::connect(options, 4, path).await
+    }
+
+    /// A private in-memory database that is dropped with the store.
+    pub async fn memory() -> Result<Self, String> {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")
+            .map_err(|e| format!("Invalid SQLite options: {}", e))?
+            .foreign_keys(true);
+
+        // Every connection would open its own empty database, so reads share the writer
+        Self::connect(options, 0, "sqlite::memory:").await
+    }
+
+    /// One writer connection plus a pool of `readers` (none shares the writer).
+    async fn connect(options: SqliteConnectOptions, readers: u32, name: &str) -> Result<Self, String> {
+        let open = |max_connections: u32| {
+            SqlitePoolOptions::new()
+                .max_connections(max_connections)
+                .connect_with(options.clone())
+        };
+        let writer = open(1)
+            .await
+            .map_err(|e| format!("Failed to open SQLite database {}: {}", name, e))?;
+
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&writer)
+            .await
+            .map_err(|e| format!("Failed to run migrations: {}", e))?;
+
+        let pool = if readers == 0 {
+            writer.clone()
+        } else {
+            open(readers)
+                .await
+                .map_err(|e| format!("Failed to open SQLite database {}: {}", name, e))?
+        };
+        Ok(Self { pool, writer })
+    }
+}
+
+#[async_trait]
+impl FeedStore for SqliteStore {
+    /// Feeds carrying a `CeloBlock`/`CeloTransaction` payload are also
+    /// written to `indexed_blocks`/`indexed_transactions`.
+    async fn put(&self, mut feed: DataFeed, content_hash: &str) -> Result<PutOutcome, String> {
+        let mut tx = self.writer.begin().await.map_err(|e| format!("Database error: {}", e))?;
+
+        let current: Option<(String, i64, i64)> =
+            sqlx::query_as("SELECT content_hash, version, seq FROM data_feeds WHERE feed_id = ?")
+                .bind(&feed.feed_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to save feed: {}", e))?;
+
+        let (status, version) = match current {
+            Some((hash, version, seq)) if hash == content_hash => {
+                return Ok(PutOutcome {
+                    status: WriteStatus::Duplicate,
+                    version: version as u32,
+                    seq: seq as u64,
+                });
+            }
+            Some((_, version, _)) => {
+                sqlx::query(
+                    "INSERT INTO data_feed_versions (feed_id, version, content_hash, feed, ingested_at)
+                     SELECT feed_id, version, content_hash, feed, ingested_at FROM data_feeds WHERE feed_id = ?",
+                )
+                .bind(&feed.feed_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to archive feed version: {}", e))?;
+
+                (WriteStatus::Updated, version as u32 + 1)
+            }
+            None => (WriteStatus::Created, 1),
+        };
+
+        let seq: i64 = sqlx::query_scalar(
+            "UPDATE stream_sequences SET last_seq = last_seq + 1 WHERE name = 'data_feeds' RETURNING last_seq",
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to assign feed seq: {}", e))?;
+        feed.seq = Some(seq as u64);
+        let outcome = PutOutcome { status, version, seq: seq as u64 };
+
+        sqlx::query(
+            "INSERT INTO data_feeds (feed_id, source_kind, source_value, data_type, timestamp, feed, content_hash, version, seq)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT (feed_id) DO UPDATE SET
+                source_kind = excluded.source_kind,
+                source_value = excluded.source_value,
+                data_type = excluded.data_type,
+                timestamp = excluded.timestamp,
+                feed = excluded.feed,
+                content_hash = excluded.content_hash,
+                version = excluded.version,
+                seq = excluded.seq,
+                ingested_at = CURRENT_TIMESTAMP",
+        )
+        .bind(&feed.feed_id)
+        .bind(feed.source.kind())
+        .bind(feed.source.value())
+        .bind(feed.data_type.name())
+        .bind(feed.timestamp as i64)
+        .bind(Json(&feed))
+        .bind(content_hash)
+        .bind(outcome.version as i64)
+        .bind(seq)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to save feed: {}", e))?;
+
+        match feed.data_type {
+            DataType::Block => {
+                if let Ok(block) = serde_json::from_value::<CeloBlock>(feed.raw_data.clone()) {
+                    sqlx::query(
+                        "INSERT INTO indexed_blocks
+                            (number, hash, parent_hash, timestamp, transaction_count, gas_used, miner, feed_id)
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                         ON CONFLICT (number) DO UPDATE SET
+                            hash = excluded.hash,
+                            parent_hash = excluded.parent_hash,
+                            timestamp = excluded.timestamp,
+                            transaction_count = excluded.transaction_count,
+                            gas_used = excluded.gas_used,
+                            miner = excluded.miner,
+                            feed_id = excluded.feed_id",
+                    )
+                    .bind(block.number as i64)
+                    .bind(&block.hash)
+                    .bind(&block.parent_hash)
+                    .bind(block.timestamp as i64)
+                    .bind(block.transaction_count as i64)
+                    .bind(&block.gas_used)
+                    .bind(&block.miner)
+                    .bind(&feed.feed_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Failed to save block: {}", e))?;
+                }
+            }
+            DataType::Transaction => {
+                if let Ok(transaction) = serde_json::from_value::<CeloTransaction>(feed.raw_data.clone()) {
+                    sqlx::query(
+                        "INSERT INTO indexed_transactions
+                            (hash, block_number, from_address, to_address, value, gas_price,
+                             gas_used, status, fee_currency, \"transaction\", feed_id)
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                         ON CONFLICT (hash) DO UPDATE SET
+                            block_number = excluded.block_number,
+                            gas_used = excluded.gas_used,
+                            status = excluded.status,
+                            \"transaction\" = excluded.\"transaction\",
+                            feed_id = excluded.feed_id",
+                    )
+                    .bind(&transaction.hash)
+                    .bind(transaction.block_number as i64)
+                    .bind(&transaction.from)
+                    .bind(&transaction.to)
+                    .bind(&transaction.value)
+                    .bind(&transaction.gas_price)
+                    .bind(&transaction.gas_used)
+                    .bind(transaction.status.map(|s| s as i64))
+                    .bind(&transaction.fee_currency)
+                    .bind(Json(&transaction))
+                    .bind(&feed.feed_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| format!("Failed to save transaction: {}", e))?;
+                }
+            }
+            _ => {}
+        }
+
+        tx.commit().await.map_err(|e| format!("Database error: {}", e))?;
+        Ok(outcome)
+    }
+
+    async fn get(&self, feed_id: &str) -> Result<Option<DataFeed>, String> {
+        sqlx::query("SELECT feed FROM data_feeds WHERE feed_id = ?")
+            .bind(feed_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load feed: {}", e))?
+            .map(|row| {
+                row.try_get::<Json<DataFeed>, _>("feed")
+                    .map(|feed| feed.0)
+                    .map_err(|e| format!("Corrupt feed row: {}", e))
+            })
+            .transpose()
+    }
+
+    /// Also removes the blocks/transactions derived from the feeds.
+    async fn remove(&self, feed_ids: &[String]) -> Result<usize, String> {
+        let mut tx = self.writer.begin().await.map_err(|e| format!("Database error: {}", e))?;
+        let mut removed = 0;
+
+        for feed_id in feed_ids {
+            removed += sqlx::query("DELETE FROM data_feeds WHERE feed_id = ?")
+                .bind(feed_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| format!("Failed to delete feeds: {}", e))?
+                .rows_affected() as usize;
+        }
+
+        tx.commit().await.map_err(|e| format!("Database error: {}", e))?;
+        Ok(removed)
+    }
+
+    async fn query(&self, query: &FeedQuery) -> Result<Vec<DataFeed>, String> {
+        let mut sql = QueryBuilder::<Sqlite>::new("SELECT feed FROM data_feeds WHERE 1 = 1");
+        push_feed_filters(&mut sql, query);
+        let (comparison, direction) = match query.order {
+            SortOrder::Asc => (">", "ASC"),
+            SortOrder::Desc => ("<", "DESC"),
+        };
+        if let Some(cursor) = &query.cursor {
+            sql.push(format!(" AND (timestamp, feed_id) {} (", comparison))
+                .push_bind(cursor.timestamp as i64)
+                .push(", ")
+                .push_bind(&cursor.feed_id)
+                .push(")");
+        }
+        sql.push(format!(" ORDER BY timestamp {0}, feed_id {0} LIMIT ", direction))
+            .push_bind(query.limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to query feeds: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                row.try_get::<Json<DataFeed>, _>("feed")
+                    .map(|feed| feed.0)
+                    .map_err(|e| format!("Corrupt feed row: {}", e))
+            })
+            .collect()
+    }
+
+    async fn count(&self) -> Result<usize, String> {
+        sqlx::query_scalar::<_, i64>("SELECT count(*) FROM data_feeds")
+            .fetch_one(&self.pool)
+            .await
+            .map(|count| count as usize)
+            .map_err(|e| format!("Failed to count feeds: {}", e))
+    }
+
+    async fn versions(&self, feed_id: &str) -> Result<Vec<FeedVersion>, String> {
+        let rows = sqlx::query(
+            "SELECT version, content_hash, feed, CAST(strftime('%s', ingested_at) AS INTEGER) AS ingested_at
+             FROM data_feed_versions WHERE feed_id = ?
+             UNION ALL
+             SELECT version, content_hash, feed, CAST(strftime('%s', ingested_at) AS INTEGER) AS ingested_at
+             FROM data_feeds WHERE feed_id = ?
+             ORDER BY version",
+        )
+        .bind(feed_id)
+        .bind(feed_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load feed versions: {}", e))?;
+
+        rows.iter().map(feed_version_from_row).collect()
+    }
+
+    async fn current_version(&self, feed_id: &str) -> Result<Option<FeedVersion>, String> {
+        sqlx::query(
+            "SELECT version, content_hash, feed, CAST(strftime('%s', ingested_at) AS INTEGER) AS ingested_at
+             FROM data_feeds WHERE feed_id = ?",
+        )
+        .bind(feed_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load feed: {}", e))?
+        .as_ref()
+        .map(feed_version_from_row)
+        .transpose()
+    }
+
+    async fn evict(&self, filter: &FeedQuery, keep_newest: usize) -> Result<usize, String> {
+        // SQLite only accepts OFFSET after a LIMIT; -1 means no limit
+        let mut sql = QueryBuilder::<Sqlite>::new(
+            "DELETE FROM data_feeds WHERE feed_id IN (SELECT feed_id FROM data_feeds WHERE 1 = 1",
+        );
+        push_feed_filters(&mut sql, filter);
+        sql.push(" ORDER BY timestamp DESC, feed_id DESC LIMIT -1 OFFSET ")
+            .push_bind(keep_newest as i64)
+            .push(")");
+
+        sql.build()
+            .execute(&self.writer)
+            .await
+            .map(|r| r.rows_affected() as usize)
+            .map_err(|e| format!("Failed to evict feeds: {}", e))
+    }
+
+    async fn written_after(&self, filter: &FeedQuery, after: u64, limit: usize) -> Result<Vec<DataFeed>, String> {
+        let mut sql = QueryBuilder::<Sqlite>::new("SELECT feed FROM data_feeds WHERE seq > ");
+        sql.push_bind(after as i64);
+        push_feed_filters(&mut sql, filter);
+        sql.push(" ORDER BY seq LIMIT ").push_bind(limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to query feeds: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                row.try_get::<Json<DataFeed>, _>("feed")
+                    .map(|feed| feed.0)
+                    .map_err(|e| format!("Corrupt feed row: {}", e))
+            })
+            .collect()
+    }
+}
+
+fn feed_version_from_row(row: &SqliteRow) -> Result<FeedVersion, String> {
+    let corrupt = |e: sqlx::Error| format!("Corrupt feed version: {}", e);
+    Ok(FeedVersion {
+        version: row.try_get::<i64, _>("version").map_err(corrupt)? as u32,
+        content_hash: row.try_get("content_hash").map_err(corrupt)?,
+        ingested_at: row.try_get::<i64, _>("ingested_at").map_err(corrupt)? as u64,
+        feed: row.try_get::<Json<DataFeed>, _>("feed").map_err(corrupt)?.0,
+    })
+}
+
+/// Appends the source, data type and time range conditions of `query`.
+fn push_feed_filters<'a>(sql: &mut QueryBuilder<'a, Sqlite>, query: &'a FeedQuery) {
+    if let Some(kind) = &query.source_kind {
+        sql.push(" AND source_kind = ").push_bind(kind);
+    }
+    if let Some(value) = &query.source_value {
+        sql.push(" AND source_value = ").push_bind(value);
+    }
+    if let Some(data_type) = &query.data_type {
+        sql.push(" AND data_type = ").push_bind(data_type);
+    }
+    if let Some(since) = query.since {
+        sql.push(" AND timestamp >= ").push_bind(since as i64);
+    }
+    if let Some(until) = query.until {
+        sql.push(" AND timestamp <= ").push_bind(until as i64);
+    }
+}
+
+#[async_trait]
+impl DecisionStore for SqliteStore {
+    /// The row id doubles as the decision's `seq`; `AUTOINCREMENT` never reuses one.
+    async fn append(&self, decision: AgentDecision) -> Result<u64, String> {
+        sqlx::query_scalar::<_, i64>(
+            "INSERT INTO agent_decisions (agent_id, decision_type, confidence, timestamp, decision)
+             VALUES (?, ?, ?, ?, ?) RETURNING id",
+        )
+        .bind(&decision.agent_id)
+        .bind(decision.decision_type.kind())
+        .bind(decision.confidence)
+        .bind(decision.timestamp as i64)
+        .bind(Json(&decision))
+        .fetch_one(&self.writer)
+        .await
+        .map(|id| id as u64)
+        .map_err(|e| format!("Failed to save decision: {}", e))
+    }
+
+    async fn query(&self, query: &DecisionQuery) -> Result<Vec<AgentDecision>, String> {
+        let mut sql = QueryBuilder::<Sqlite>::new("SELECT id, decision FROM agent_decisions WHERE 1 = 1");
+        push_decision_filters(&mut sql, query);
+        let direction = match query.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        sql.push(format!(" ORDER BY timestamp {0}, id {0} LIMIT ", direction))
+            .push_bind(query.limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load decisions: {}", e))?;
+
+        rows.iter().map(decision_from_row).collect()
+    }
+
+    async fn aggregate(&self, query: &DecisionQuery) -> Result<Vec<DecisionGroup>, String> {
+        let mut sql = QueryBuilder::<Sqlite>::new(
+            "SELECT agent_id, decision_type, count(*) AS count, CAST(sum(confidence) AS REAL) AS confidence_sum,
+                    min(timestamp) AS first_timestamp, max(timestamp) AS last_timestamp
+             FROM agent_decisions WHERE 1 = 1",
+        );
+        push_decision_filters(&mut sql, query);
+        sql.push(" GROUP BY agent_id, decision_type");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to aggregate decisions: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let corrupt = |e: sqlx::Error| format!("Corrupt decision aggregate: {}", e);
+                Ok(DecisionGroup {
+                    agent_id: row.try_get("agent_id").map_err(corrupt)?,
+                    decision_type: row.try_get("decision_type").map_err(corrupt)?,
+                    count: row.try_get::<i64, _>("count").map_err(corrupt)? as u64,
+                    confidence_sum: row.try_get("confidence_sum").map_err(corrupt)?,
+                    first_timestamp: row.try_get::<i64, _>("first_timestamp").map_err(corrupt)? as u64,
+                    last_timestamp: row.try_get::<i64, _>("last_timestamp").map_err(corrupt)? as u64,
+                })
+            })
+            .collect()
+    }
+
+    async fn evict(&self, older_than: Option<u64>, keep_newest: Option<usize>) -> Result<usize, String> {
+        let mut evicted = 0;
+        if let Some(cutoff) = older_than {
+            evicted += sqlx::query("DELETE FROM agent_decisions WHERE timestamp < ?")
+                .bind(cutoff as i64)
+                .execute(&self.writer)
+                .await
+                .map_err(|e| format!("Failed to evict decisions: {}", e))?
+                .rows_affected() as usize;
+        }
+        if let Some(keep) = keep_newest {
+            evicted += sqlx::query(
+                "DELETE FROM agent_decisions WHERE id IN
+                    (SELECT id FROM agent_decisions ORDER BY id DESC LIMIT -1 OFFSET ?)",
+            )
+            .bind(keep as i64)
+            .execute(&self.writer)
+            .await
+            .map_err(|e| format!("Failed to evict decisions: {}", e))?
+            .rows_affected() as usize;
+        }
+        Ok(evicted)
+    }
+
+    async fn recorded_after(&self, filter: &DecisionQuery, after: u64, limit: usize) -> Result<Vec<AgentDecision>, String> {
+        let mut sql = QueryBuilder::<Sqlite>::new("SELECT id, decision FROM agent_decisions WHERE id > ");
+        sql.push_bind(after as i64);
+        push_decision_filters(&mut sql, filter);
+        sql.push(" ORDER BY id LIMIT ").push_bind(limit as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load decisions: {}", e))?;
+
+        rows.iter().map(decision_from_row).collect()
+    }
+}
+
+fn decision_from_row(row: &SqliteRow) -> Result<AgentDecision, String> {
+    let corrupt = |e: sqlx::Error| format!("Corrupt decision row: {}", e);
+    let mut decision = row.try_get::<Json<AgentDecision>, _>("decision").map_err(corrupt)?.0;
+    decision.seq = Some(row.try_get::<i64, _>("id").map_err(corrupt)? as u64);
+    Ok(decision)
+}
+
+#[async_trait]
+impl SchemaStore for SqliteStore {
+    async fn put(&self, type_name: &str, schema: &serde_json::Value) -> Result<(), String> {
+        sqlx::query(
+            "INSERT INTO feed_schemas (type_name, schema) VALUES (?, ?)
+             ON CONFLICT (type_name) DO UPDATE SET schema = excluded.schema, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(type_name)
+        .bind(Json(schema))
+        .execute(&self.writer)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save schema: {}", e))
+    }
+
+    async fn remove(&self, type_name: &str) -> Result<bool, String> {
+        sqlx::query("DELETE FROM feed_schemas WHERE type_name = ?")
+            .bind(type_name)
+            .execute(&self.writer)
+            .await
+            .map(|r| r.rows_affected() > 0)
+            .map_err(|e| format!("Failed to delete schema: {}", e))
+    }
+
+    async fn list(&self) -> Result<Vec<(String, serde_json::Value)>, String> {
+        let rows: Vec<(String, Json<serde_json::Value>)> =
+            sqlx::query_as("SELECT type_name, schema FROM feed_schemas ORDER BY type_name")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to load schemas: {}", e))?;
+        Ok(rows.into_iter().map(|(type_name, schema)| (type_name, schema.0)).collect())
+    }
+}
+
+/// Appends the filter conditions of `query`.
+fn push_decision_filters<'a>(sql: &mut QueryBuilder<'a, Sqlite>, query: &'a DecisionQuery) {
+    if let Some(agent_id) = &query.agent_id {
+        sql.push(" AND agent_id = ").push_bind(agent_id);
+    }
+    if let Some(decision_type) = &query.decision_type {
+        sql.push(" AND decision_type = ").push_bind(decision_type);
+    }
+    if let Some(min) = query.min_confidence {
+        sql.push(" AND confidence >= ").push_bind(min);
+    }
+    if let Some(max) = query.max_confidence {
+        sql.push(" AND confidence <= ").push_bind(max);
+    }
+    if let Some(since) = query.since {
+        sql.push(" AND timestamp >= ").push_bind(since as i64);
+    }
+    if let Some(until) = query.until {
+        sql.push(" AND timestamp <= ").push_bind(until as i64);
+    }
+    if !query.data_sources.is_empty() {
+        sql.push(" AND EXISTS (SELECT 1 FROM json_each(decision, '$.data_sources') WHERE json_each.value IN (");
+        let mut sources = sql.separated(", ");
+        for source in &query.data_sources {
+            sources.push_bind(source);
+        }
+        sql.push("))");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqliteStore;
+    use crate::storage::tests;
+
+    #[tokio::test]
+    async fn put_and_versions() {
+        tests::check_put_and_versions(&SqliteStore::memory().await.unwrap()).await;
+    }
+
+    /// Needs a file: the in-memory store has a single connection and can't race.
+    #[tokio::test]
+    async fn concurrent_puts() {
+        let path = std::env::temp_dir().join(format!("sqlite-store-tests-{}.db", std::process::id()));
+        let store = SqliteStore::open(path.to_str().unwrap()).await.unwrap();
+        tests::check_concurrent_puts(&store).await;
+        store.pool.close().await;
+        store.writer.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[tokio::test]
+    async fn cursor_paging() {
+        tests::check_cursor_paging(&SqliteStore::memory().await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn feed_eviction() {
+        tests::check_feed_eviction(&SqliteStore::memory().await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn write_order() {
+        tests::check_write_order(&SqliteStore::memory().await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn recording_order() {
+        tests::check_recording_order(&SqliteStore::memory().await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn decision_eviction() {
+        tests::check_decision_eviction(&SqliteStore::memory().await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn schemas() {
+        tests::check_schemas(&SqliteStore::memory().await.unwrap()).await;
+    }
+}
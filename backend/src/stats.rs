@@ -0,0 +1,118 @@
+// Rolling-window statistics for throughput and latency reporting
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Latency samples kept per window; older samples are dropped first.
+const MAX_LATENCY_SAMPLES: usize = 4096;
+
+/// Counts events in one-second buckets over a sliding window.
+#[derive(Debug)]
+pub struct RateWindow {
+    window: Duration,
+    origin: Instant,
+    buckets: VecDeque<(u64, u64)>, // (second since origin, events)
+}
+
+impl RateWindow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window: window.max(Duration::from_secs(1)),
+            origin: Instant::now(),
+            buckets: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self) {
+        let second = self.origin.elapsed().as_secs();
+        match self.buckets.back_mut() {
+            Some((bucket, count)) if *bucket == second => *count += 1,
+            _ => self.buckets.push_back((second, 1)),
+        }
+        self.prune();
+    }
+
+    /// Events per second over the window, or over the time since start if shorter.
+    pub fn per_second(&mut self) -> f64 {
+        self.prune();
+        let events: u64 = self.buckets.iter().map(|(_, count)| count).sum();
+        let span = self.window.min(self.origin.elapsed()).as_secs_f64().max(1.0);
+        events as f64 / span
+    }
+
+    fn prune(&mut self) {
+        let now = self.origin.elapsed().as_secs();
+        let oldest = now.saturating_sub(self.window.as_secs() - 1);
+        while self.buckets.front().is_some_and(|(bucket, _)| *bucket < oldest) {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Latency samples over a sliding window.
+#[derive(Debug)]
+pub struct LatencyWindow {
+    window: Duration,
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl LatencyWindow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        self.samples.push_back((Instant::now(), latency.as_secs_f64() * 1000.0));
+        if self.samples.len() > MAX_LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.prune();
+    }
+
+    pub fn summary(&mut self) -> LatencySummary {
+        self.prune();
+        let mut values: Vec<f64> = self.samples.iter().map(|(_, ms)| *ms).collect();
+        if values.is_empty() {
+            return LatencySummary::default();
+        }
+        values.sort_by(f64::total_cmp);
+
+        LatencySummary {
+            samples: values.len(),
+            mean_ms: values.iter().sum::<f64>() / values.len() as f64,
+            p50_ms: percentile(&values, 0.50),
+            p95_ms: percentile(&values, 0.95),
+            p99_ms: percentile(&values, 0.99),
+            max_ms: values[values.len() - 1],
+        }
+    }
+
+    fn prune(&mut self) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|(at, _)| at.elapsed() > self.window)
+        {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Nearest-rank percentile of already sorted, non-empty values.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
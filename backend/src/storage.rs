@@ -0,0 +1,841 @@
+// Storage backends for the Real-Time Indexer's feeds and agent decisions
+use async_trait::async_trait;
+use ethers::utils::hex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::indexer::{AgentDecision, DataFeed};
+
+/// Decisions kept by the in-memory store; durable backends keep everything unless a
+/// retention policy says otherwise.
+pub const MAX_DECISIONS_IN_MEMORY: usize = 1000;
+
+/// Superseded versions kept per feed by the in-memory store.
+pub const MAX_FEED_VERSIONS_IN_MEMORY: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteStatus {
+    Created,
+    /// Same content hash as the stored version; nothing was written.
+    Duplicate,
+    /// New content for an existing feed id; the previous version was archived.
+    Updated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PutOutcome {
+    pub status: WriteStatus,
+    pub version: u32, // current version after the write
+    pub seq: u64,     // `DataFeed::seq` of the current version
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedVersion {
+    pub version: u32,
+    pub content_hash: String,
+    pub ingested_at: u64,
+    pub feed: DataFeed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Position after the last feed of a page. Feeds are ordered by
+/// `(timestamp, feed_id)` so pages are stable even with equal timestamps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedCursor {
+    pub timestamp: u64,
+    pub feed_id: String,
+}
+
+impl FeedCursor {
+    pub fn after(feed: &DataFeed) -> Self {
+        Self {
+            timestamp: feed.timestamp,
+            feed_id: feed.feed_id.clone(),
+        }
+    }
+
+    /// Opaque hex encoding handed to clients as `next_cursor`.
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}|{}", self.timestamp, self.feed_id))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid cursor: {}", cursor);
+        let bytes = hex::decode(cursor).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (timestamp, feed_id) = decoded.split_once('|').ok_or_else(invalid)?;
+
+        Ok(Self {
+            timestamp: timestamp.parse().map_err(|_| invalid())?,
+            feed_id: feed_id.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FeedQuery {
+    pub source_kind: Option<String>,   // OnChain, OffChain, Social, Oracle
+    pub source_value: Option<String>,
+    pub data_type: Option<String>,     // `DataType::name()`
+    pub since: Option<u64>,            // inclusive
+    pub until: Option<u64>,            // inclusive
+    pub cursor: Option<FeedCursor>,
+    pub order: SortOrder,
+    pub limit: usize,
+}
+
+impl FeedQuery {
+    pub fn matches(&self, feed: &DataFeed) -> bool {
+        self.source_kind.as_deref().is_none_or(|kind| feed.source.kind() == kind)
+            && self.source_value.as_deref().is_none_or(|value| feed.source.value() == value)
+            && self.data_type.as_deref().is_none_or(|name| feed.data_type.name() == name)
+            && self.since.is_none_or(|since| feed.timestamp >= since)
+            && self.until.is_none_or(|until| feed.timestamp <= until)
+            && self.cursor.as_ref().is_none_or(|cursor| {
+                let key = (feed.timestamp, feed.feed_id.as_str());
+                let after = (cursor.timestamp, cursor.feed_id.as_str());
+                match self.order {
+                    SortOrder::Asc => key > after,
+                    SortOrder::Desc => key < after,
+                }
+            })
+    }
+}
+
+/// Filters for agent decisions. `decision_type` is a `DecisionType::kind()`; a decision matches
+/// `data_sources` if it references any of them. Results are ordered by `timestamp`.
+#[derive(Debug, Clone, Default)]
+pub struct DecisionQuery {
+    pub agent_id: Option<String>,
+    pub decision_type: Option<String>,
+    pub min_confidence: Option<f32>,
+    pub max_confidence: Option<f32>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub data_sources: Vec<String>,
+    pub order: SortOrder,
+    pub limit: usize,
+}
+
+impl DecisionQuery {
+    /// Whether `decision` passes the filters (order and limit are not considered).
+    pub fn matches(&self, decision: &AgentDecision) -> bool {
+        self.agent_id.as_deref().is_none_or(|agent| decision.agent_id == agent)
+            && self.decision_type.as_deref().is_none_or(|kind| decision.decision_type.kind() == kind)
+            && self.min_confidence.is_none_or(|min| decision.confidence >= min)
+            && self.max_confidence.is_none_or(|max| decision.confidence <= max)
+            && self.since.is_none_or(|since| decision.timestamp >= since)
+            && self.until.is_none_or(|until| decision.timestamp <= until)
+            && (self.data_sources.is_empty()
+                || decision.data_sources.iter().any(|source| self.data_sources.contains(source)))
+    }
+}
+
+/// Aggregates for one agent and decision type, as computed by a `DecisionStore`.
+#[derive(Debug, Clone)]
+pub struct DecisionGroup {
+    pub agent_id: String,
+    pub decision_type: String,
+    pub count: u64,
+    pub confidence_sum: f64,
+    pub first_timestamp: u64,
+    pub last_timestamp: u64,
+}
+
+#[async_trait]
+pub trait FeedStore: Send + Sync {
+    /// Stores a feed unless its `content_hash` matches the current version. Replacing
+    /// different content bumps the version and archives the previous one. Every write
+    /// stamps the feed with a `seq` higher than any assigned before, never reused.
+    async fn put(&self, feed: DataFeed, content_hash: &str) -> Result<PutOutcome, String>;
+
+    async fn get(&self, feed_id: &str) -> Result<Option<DataFeed>, String>;
+
+    /// Removes feeds by id. Returns how many existed.
+    async fn remove(&self, feed_ids: &[String]) -> Result<usize, String>;
+
+    /// Up to `query.limit` matching feeds, ordered by `(timestamp, feed_id)`.
+    async fn query(&self, query: &FeedQuery) -> Result<Vec<DataFeed>, String>;
+
+    async fn count(&self) -> Result<usize, String>;
+
+    /// Every known version of a feed, oldest first; the last entry is the current one.
+    async fn versions(&self, feed_id: &str) -> Result<Vec<FeedVersion>, String>;
+
+    /// The current version of a feed with its content hash, if the feed exists.
+    async fn current_version(&self, feed_id: &str) -> Result<Option<FeedVersion>, String>;
+
+    /// Deletes feeds matching `filter` (its cursor, order and limit are ignored) except
+    /// the newest `keep_newest` by `timestamp`. Returns how many were removed.
+    async fn evict(&self, filter: &FeedQuery, keep_newest: usize) -> Result<usize, String>;
+
+    /// Up to `limit` feeds matching `filter` (its cursor, order and limit are ignored) whose
+    /// `seq` is greater than `after`, in write order.
+    async fn written_after(&self, filter: &FeedQuery, after: u64, limit: usize) -> Result<Vec<DataFeed>, String>;
+}
+
+#[async_trait]
+pub trait DecisionStore: Send + Sync {
+    /// Records the decision and returns its `seq`, higher than any assigned before.
+    async fn append(&self, decision: AgentDecision) -> Result<u64, String>;
+
+    /// Up to `query.limit` matching decisions; ties in `timestamp` keep recording order.
+    async fn query(&self, query: &DecisionQuery) -> Result<Vec<AgentDecision>, String>;
+
+    /// Per agent and decision type aggregates over all decisions matching `query`.
+    async fn aggregate(&self, query: &DecisionQuery) -> Result<Vec<DecisionGroup>, String>;
+
+    /// Deletes decisions with a `timestamp` before `older_than`, then all but the
+    /// `keep_newest` most recently recorded. Returns how many were removed.
+    async fn evict(&self, older_than: Option<u64>, keep_newest: Option<usize>) -> Result<usize, String>;
+
+    /// Up to `limit` decisions matching `filter` (its order and limit are ignored) whose
+    /// `seq` is greater than `after`, in recording order.
+    async fn recorded_after(&self, filter: &DecisionQuery, after: u64, limit: usize) -> Result<Vec<AgentDecision>, String>;
+}
+
+/// The JSON Schemas registered for `Custom` feed types, keyed by type name.
+#[async_trait]
+pub trait SchemaStore: Send + Sync {
+    /// Registers or replaces the schema for `type_name`.
+    async fn put(&self, type_name: &str, schema: &Value) -> Result<(), String>;
+
+    /// Returns whether a schema was registered.
+    async fn remove(&self, type_name: &str) -> Result<bool, String>;
+
+    async fn list(&self) -> Result<Vec<(String, Value)>, String>;
+}
+
+struct StoredFeed {
+    feed: DataFeed,
+    content_hash: String,
+    version: u32,
+    ingested_at: u64,
+}
+
+impl StoredFeed {
+    fn to_version(&self) -> FeedVersion {
+        FeedVersion {
+            version: self.version,
+            content_hash: self.content_hash.clone(),
+            ingested_at: self.ingested_at,
+            feed: self.feed.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryFeedStore {
+    feeds: RwLock<HashMap<String, StoredFeed>>,
+    history: RwLock<HashMap<String, VecDeque<FeedVersion>>>,
+    last_seq: AtomicU64,
+}
+
+#[async_trait]
+impl FeedStore for MemoryFeedStore {
+    async fn put(&self, mut feed: DataFeed, content_hash: &str) -> Result<PutOutcome, String> {
+        let mut feeds = self.feeds.write().await;
+        let ingested_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let (status, version) = match feeds.get(&feed.feed_id) {
+            None => (WriteStatus::Created, 1),
+            Some(current) if current.content_hash == content_hash => {
+                return Ok(PutOutcome {
+                    status: WriteStatus::Duplicate,
+                    version: current.version,
+                    seq: current.feed.seq.unwrap_or_default(),
+                });
+            }
+            Some(current) => {
+                let mut history = self.history.write().await;
+                let versions = history.entry(feed.feed_id.clone()).or_default();
+                versions.push_back(current.to_version());
+                while versions.len() > MAX_FEED_VERSIONS_IN_MEMORY {
+                    versions.pop_front();
+                }
+                (WriteStatus::Updated, current.version + 1)
+            }
+        };
+
+        let seq = self.last_seq.fetch_add(1, Ordering::SeqCst) + 1;
+        feed.seq = Some(seq);
+        feeds.insert(
+            feed.feed_id.clone(),
+            StoredFeed {
+                feed,
+                content_hash: content_hash.to_string(),
+                version,
+                ingested_at,
+            },
+        );
+        Ok(PutOutcome { status, version, seq })
+    }
+
+    async fn get(&self, feed_id: &str) -> Result<Option<DataFeed>, String> {
+        Ok(self.feeds.read().await.get(feed_id).map(|stored| stored.feed.clone()))
+    }
+
+    async fn remove(&self, feed_ids: &[String]) -> Result<usize, String> {
+        let mut feeds = self.feeds.write().await;
+        let mut history = self.history.write().await;
+        Ok(feed_ids
+            .iter()
+            .filter(|id| {
+                history.remove(*id);
+                feeds.remove(*id).is_some()
+            })
+            .count())
+    }
+
+    async fn query(&self, query: &FeedQuery) -> Result<Vec<DataFeed>, String> {
+        let feeds = self.feeds.read().await;
+        let mut matching: Vec<&DataFeed> = feeds
+            .values()
+            .map(|stored| &stored.feed)
+            .filter(|feed| query.matches(feed))
+            .collect();
+        matching.sort_by(|a, b| (a.timestamp, &a.feed_id).cmp(&(b.timestamp, &b.feed_id)));
+        if query.order == SortOrder::Desc {
+            matching.reverse();
+        }
+        Ok(matching.into_iter().take(query.limit).cloned().collect())
+    }
+
+    async fn count(&self) -> Result<usize, String> {
+        Ok(self.feeds.read().await.len())
+    }
+
+    async fn versions(&self, feed_id: &str) -> Result<Vec<FeedVersion>, String> {
+        let feeds = self.feeds.read().await;
+        let Some(current) = feeds.get(feed_id) else {
+            return Ok(Vec::new());
+        };
+        let mut versions: Vec<FeedVersion> = self
+            .history
+            .read()
+            .await
+            .get(feed_id)
+            .map(|history| history.iter().cloned().collect())
+            .unwrap_or_default();
+        versions.push(current.to_version());
+        Ok(versions)
+    }
+
+    async fn current_version(&self, feed_id: &str) -> Result<Option<FeedVersion>, String> {
+        Ok(self.feeds.read().await.get(feed_id).map(StoredFeed::to_version))
+    }
+
+    async fn evict(&self, filter: &FeedQuery, keep_newest: usize) -> Result<usize, String> {
+        let filter = FeedQuery { cursor: None, ..filter.clone() };
+        let mut feeds = self.feeds.write().await;
+        let mut matching: Vec<(u64, String)> = feeds
+            .values()
+            .filter(|stored| filter.matches(&stored.feed))
+            .map(|stored| (stored.feed.timestamp, stored.feed.feed_id.clone()))
+            .collect();
+        if matching.len() <= keep_newest {
+            return Ok(0);
+        }
+        matching.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut history = self.history.write().await;
+        let evicted = &matching[keep_newest..];
+        for (_, feed_id) in evicted {
+            feeds.remove(feed_id);
+            history.remove(feed_id);
+        }
+        Ok(evicted.len())
+    }
+
+    async fn written_after(&self, filter: &FeedQuery, after: u64, limit: usize) -> Result<Vec<DataFeed>, String> {
+        let filter = FeedQuery { cursor: None, ..filter.clone() };
+        let feeds = self.feeds.read().await;
+        let mut matching: Vec<&DataFeed> = feeds
+            .values()
+            .map(|stored| &stored.feed)
+            .filter(|feed| feed.seq.unwrap_or_default() > after && filter.matches(feed))
+            .collect();
+        matching.sort_by_key(|feed| feed.seq);
+        Ok(matching.into_iter().take(limit).cloned().collect())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryDecisionStore {
+    decisions: RwLock<VecDeque<AgentDecision>>,
+    last_seq: AtomicU64,
+}
+
+#[async_trait]
+impl DecisionStore for MemoryDecisionStore {
+    async fn append(&self, mut decision: AgentDecision) -> Result<u64, String> {
+        let mut decisions = self.decisions.write().await;
+        let seq = self.last_seq.fetch_add(1, Ordering::SeqCst) + 1;
+        decision.seq = Some(seq);
+        decisions.push_back(decision);
+        while decisions.len() > MAX_DECISIONS_IN_MEMORY {
+            decisions.pop_front();
+        }
+        Ok(seq)
+    }
+
+    async fn query(&self, query: &DecisionQuery) -> Result<Vec<AgentDecision>, String> {
+        let decisions = self.decisions.read().await;
+        let mut matching: Vec<&AgentDecision> = decisions.iter().filter(|d| query.matches(d)).collect();
+        // Stable, so equal timestamps stay in recording order
+        matching.sort_by_key(|d| d.timestamp);
+        if query.order == SortOrder::Desc {
+            matching.reverse();
+        }
+        Ok(matching.into_iter().take(query.limit).cloned().collect())
+    }
+
+    async fn aggregate(&self, query: &DecisionQuery) -> Result<Vec<DecisionGroup>, String> {
+        let decisions = self.decisions.read().await;
+        let mut groups: HashMap<(&str, &'static str), DecisionGroup> = HashMap::new();
+
+        for decision in decisions.iter().filter(|d| query.matches(d)) {
+            let kind = decision.decision_type.kind();
+            let group = groups
+                .entry((decision.agent_id.as_str(), kind))
+                .or_insert_with(|| DecisionGroup {
+                    agent_id: decision.agent_id.clone(),
+                    decision_type: kind.to_string(),
+                    count: 0,
+                    confidence_sum: 0.0,
+                    first_timestamp: decision.timestamp,
+                    last_timestamp: decision.timestamp,
+                });
+            group.count += 1;
+            group.confidence_sum += decision.confidence as f64;
+            group.first_timestamp = group.first_timestamp.min(decision.timestamp);
+            group.last_timestamp = group.last_timestamp.max(decision.timestamp);
+        }
+        Ok(groups.into_values().collect())
+    }
+
+    async fn evict(&self, older_than: Option<u64>, keep_newest: Option<usize>) -> Result<usize, String> {
+        let mut decisions = self.decisions.write().await;
+        let before = decisions.len();
+        if let Some(cutoff) = older_than {
+            decisions.retain(|decision| decision.timestamp >= cutoff);
+        }
+        if let Some(keep) = keep_newest {
+            let excess = decisions.len().saturating_sub(keep);
+            decisions.drain(..excess);
+        }
+        Ok(before - decisions.len())
+    }
+
+    async fn recorded_after(&self, filter: &DecisionQuery, after: u64, limit: usize) -> Result<Vec<AgentDecision>, String> {
+        // Kept in recording order already
+        Ok(self
+            .decisions
+            .read()
+            .await
+            .iter()
+            .filter(|d| d.seq.unwrap_or_default() > after && filter.matches(d))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Default)]
+pub struct MemorySchemaStore {
+    schemas: RwLock<BTreeMap<String, Value>>,
+}
+
+#[async_trait]
+impl SchemaStore for MemorySchemaStore {
+    async fn put(&self, type_name: &str, schema: &Value) -> Result<(), String> {
+        self.schemas.write().await.insert(type_name.to_string(), schema.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, type_name: &str) -> Result<bool, String> {
+        Ok(self.schemas.write().await.remove(type_name).is_some())
+    }
+
+    async fn list(&self) -> Result<Vec<(String, Value)>, String> {
+        Ok(self.schemas.read().await.iter().map(|(name, schema)| (name.clone(), schema.clone())).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageBackend {
+    Memory,
+    Sqlite { path: String },
+    Postgres { url: String },
+}
+
+impl StorageBackend {
+    /// `STORAGE_BACKEND` selects `memory`, `sqlite` (file at `SQLITE_PATH`) or
+    /// `postgres` (`DATABASE_URL`). When unset, Postgres is used if `DATABASE_URL` is set.
+    pub fn from_env() -> Self {
+        let database_url = std::env::var("DATABASE_URL").ok();
+        let sqlite_path = || std::env::var("SQLITE_PATH").unwrap_or_else(|_| "sentinel.db".to_string());
+
+        match std::env::var("STORAGE_BACKEND").map(|b| b.to_lowercase()).as_deref() {
+            Ok("memory") => StorageBackend::Memory,
+            Ok("sqlite") => StorageBackend::Sqlite { path: sqlite_path() },
+            Ok("postgres") => StorageBackend::Postgres {
+                url: database_url.expect("STORAGE_BACKEND=postgres requires DATABASE_URL"),
+            },
+            Ok(other) => panic!("Unknown STORAGE_BACKEND: {} (expected memory, sqlite or postgres)", other),
+            Err(_) => match database_url {
+                Some(url) => StorageBackend::Postgres { url },
+                None => StorageBackend::Memory,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StorageBackend::Memory => "memory",
+            StorageBackend::Sqlite { .. } => "sqlite",
+            StorageBackend::Postgres { .. } => "postgres",
+        }
+    }
+}
+
+pub struct Stores {
+    pub feeds: Arc<dyn FeedStore>,
+    pub decisions: Arc<dyn DecisionStore>,
+    pub schemas: Arc<dyn SchemaStore>,
+}
+
+impl Stores {
+    pub fn memory() -> Self {
+        Self {
+            feeds: Arc::new(MemoryFeedStore::default()),
+            decisions: Arc::new(MemoryDecisionStore::default()),
+            schemas: Arc::new(MemorySchemaStore::default()),
+        }
+    }
+
+    /// Opens the configured backend. Backends whose cargo feature is disabled are an error.
+    pub async fn open(backend: &StorageBackend) -> Result<Self, String> {
+        match backend {
+            StorageBackend::Memory => Ok(Self::memory()),
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite { path } => {
+                let store = crate::sqlite::SqliteStore::open(path).await?;
+                Ok(Self {
+                    feeds: Arc::new(store.clone()),
+                    decisions: Arc::new(store.clone()),
+                    schemas: Arc::new(store),
+                })
+            }
+            #[cfg(not(feature = "sqlite"))]
+            StorageBackend::Sqlite { .. } => Err("SQLite storage requires building with --features sqlite".to_string()),
+            #[cfg(feature = "database")]
+            StorageBackend::Postgres { url } => {
+                let store = crate::db::PgStore::connect(url).await?;
+                Ok(Self {
+                    feeds: Arc::new(store.clone()),
+                    decisions: Arc::new(store.clone()),
+                    schemas: Arc::new(store),
+                })
+            }
+            #[cfg(not(feature = "database"))]
+            StorageBackend::Postgres { .. } => Err("Postgres storage requires building with --features database".to_string()),
+        }
+    }
+}
+
+/// Behaviour every `FeedStore` and `DecisionStore` must share. Each backend runs these
+/// checks against a fresh, empty store of its own.
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::indexer::{DataType, DecisionType, FeedSource};
+    use serde_json::json;
+
+    fn feed(feed_id: &str, timestamp: u64, price: f64) -> DataFeed {
+        DataFeed {
+            feed_id: feed_id.to_string(),
+            source: FeedSource::Oracle("store-tests".to_string()),
+            data_type: DataType::Price,
+            timestamp,
+            raw_data: json!({ "asset": "CELO", "price": price }),
+            cleaned_data: None,
+            seq: None,
+        }
+    }
+
+    fn decision(agent_id: &str, timestamp: u64) -> AgentDecision {
+        AgentDecision {
+            agent_id: agent_id.to_string(),
+            decision_type: DecisionType::Alert {
+                severity: "low".to_string(),
+                message: format!("alert at {}", timestamp),
+                trigger: None,
+            },
+            confidence: 0.5,
+            reasoning: String::new(),
+            timestamp,
+            data_sources: Vec::new(),
+            provenance: None,
+            seq: None,
+        }
+    }
+
+    fn ids(feeds: &[DataFeed]) -> Vec<&str> {
+        feeds.iter().map(|f| f.feed_id.as_str()).collect()
+    }
+
+    /// Follows `next` cursors through every page of `limit` feeds.
+    async fn page_through(store: &dyn FeedStore, order: SortOrder, limit: usize) -> Vec<String> {
+        let mut query = FeedQuery { order, limit, ..Default::default() };
+        let mut seen = Vec::new();
+        loop {
+            let page = store.query(&query).await.unwrap();
+            assert!(page.len() <= limit);
+            seen.extend(page.iter().map(|f| f.feed_id.clone()));
+            match page.last() {
+                Some(last) if page.len() == limit => query.cursor = Some(FeedCursor::after(last)),
+                _ => return seen,
+            }
+        }
+    }
+
+    pub async fn check_put_and_versions(store: &dyn FeedStore) {
+        let created = store.put(feed("price:celo", 100, 0.5), "hash-1").await.unwrap();
+        assert_eq!((created.status, created.version), (WriteStatus::Created, 1));
+
+        let duplicate = store.put(feed("price:celo", 100, 0.5), "hash-1").await.unwrap();
+        assert_eq!(duplicate, PutOutcome { status: WriteStatus::Duplicate, version: 1, seq: created.seq });
+        assert_eq!(store.versions("price:celo").await.unwrap().len(), 1);
+
+        let updated = store.put(feed("price:celo", 160, 0.6), "hash-2").await.unwrap();
+        assert_eq!((updated.status, updated.version), (WriteStatus::Updated, 2));
+        assert!(updated.seq > created.seq);
+
+        let current = store.get("price:celo").await.unwrap().unwrap();
+        assert_eq!((current.timestamp, current.raw_data["price"].as_f64()), (160, Some(0.6)));
+        assert_eq!(current.seq, Some(updated.seq));
+        let current = store.current_version("price:celo").await.unwrap().unwrap();
+        assert_eq!((current.version, current.content_hash.as_str()), (2, "hash-2"));
+
+        let versions = store.versions("price:celo").await.unwrap();
+        let summary: Vec<(u32, &str, u64)> = versions
+            .iter()
+            .map(|v| (v.version, v.content_hash.as_str(), v.feed.timestamp))
+            .collect();
+        assert_eq!(summary, [(1, "hash-1", 100), (2, "hash-2", 160)]);
+        assert_eq!(store.count().await.unwrap(), 1);
+
+        assert_eq!(store.remove(&["price:celo".to_string(), "missing".to_string()]).await.unwrap(), 1);
+        assert!(store.get("price:celo").await.unwrap().is_none());
+        assert!(store.versions("price:celo").await.unwrap().is_empty());
+    }
+
+    /// Concurrent writers to one feed each get their own version, with none lost or failed.
+    pub async fn check_concurrent_puts(store: &dyn FeedStore) {
+        let hashes: Vec<String> = (0..16).map(|i| format!("race-{}", i)).collect();
+        let writes = hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| store.put(feed("price:race", 100 + i as u64, i as f64), hash));
+        let mut versions: Vec<u32> = futures_util::future::join_all(writes)
+            .await
+            .into_iter()
+            .map(|outcome| outcome.unwrap().version)
+            .collect();
+        versions.sort_unstable();
+        assert_eq!(versions, (1..=16).collect::<Vec<u32>>());
+        assert_eq!(store.versions("price:race").await.unwrap().len(), 16);
+    }
+
+    pub async fn check_cursor_paging(store: &dyn FeedStore) {
+        // Ties in timestamp are broken by feed id
+        for (feed_id, timestamp) in [("e", 10), ("b", 20), ("d", 20), ("a", 20), ("c", 30)] {
+            store.put(feed(feed_id, timestamp, 1.0), feed_id).await.unwrap();
+        }
+
+        let asc = ["e", "a", "b", "d", "c"];
+        for limit in [1, 2, 5, 10] {
+            assert_eq!(page_through(store, SortOrder::Asc, limit).await, asc);
+            let desc: Vec<&str> = asc.iter().rev().copied().collect();
+            assert_eq!(page_through(store, SortOrder::Desc, limit).await, desc);
+        }
+
+        let window = FeedQuery {
+            since: Some(20),
+            until: Some(20),
+            cursor: Some(FeedCursor { timestamp: 20, feed_id: "a".to_string() }),
+            order: SortOrder::Asc,
+            limit: 10,
+            ..Default::default()
+        };
+        assert_eq!(ids(&store.query(&window).await.unwrap()), ["b", "d"]);
+    }
+
+    pub async fn check_feed_eviction(store: &dyn FeedStore) {
+        for (feed_id, timestamp) in [("a", 10), ("b", 20), ("c", 20), ("d", 30), ("e", 40)] {
+            store.put(feed(feed_id, timestamp, 1.0), feed_id).await.unwrap();
+        }
+        store.put(feed("b", 20, 2.0), "b-2").await.unwrap();
+
+        // Older than 30, but the newest of those ("c" wins the tie on feed id) is kept
+        let older = FeedQuery { until: Some(29), ..Default::default() };
+        assert_eq!(store.evict(&older, 1).await.unwrap(), 2);
+        let remaining = store.query(&FeedQuery { order: SortOrder::Asc, limit: 10, ..Default::default() }).await.unwrap();
+        assert_eq!(ids(&remaining), ["c", "d", "e"]);
+        assert!(store.versions("b").await.unwrap().is_empty());
+
+        assert_eq!(store.evict(&FeedQuery::default(), 5).await.unwrap(), 0);
+        assert_eq!(store.evict(&FeedQuery::default(), 1).await.unwrap(), 2);
+        assert_eq!(store.count().await.unwrap(), 1);
+        assert!(store.get("e").await.unwrap().is_some());
+    }
+
+    /// `seq` follows write order, not timestamps, and is never handed out twice.
+    pub async fn check_write_order(store: &dyn FeedStore) {
+        for (feed_id, timestamp) in [("a", 30), ("b", 10), ("c", 20)] {
+            store.put(feed(feed_id, timestamp, 1.0), feed_id).await.unwrap();
+        }
+        // A new version moves the feed to the end
+        store.put(feed("a", 30, 2.0), "a-2").await.unwrap();
+
+        let everything = FeedQuery::default();
+        let written = store.written_after(&everything, 0, 10).await.unwrap();
+        assert_eq!(ids(&written), ["b", "c", "a"]);
+        let seqs: Vec<u64> = written.iter().map(|f| f.seq.unwrap()).collect();
+        assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(ids(&store.written_after(&everything, seqs[0], 10).await.unwrap()), ["c", "a"]);
+        assert_eq!(ids(&store.written_after(&everything, seqs[0], 1).await.unwrap()), ["c"]);
+        let older = FeedQuery { until: Some(20), ..Default::default() };
+        assert_eq!(ids(&store.written_after(&older, 0, 10).await.unwrap()), ["b", "c"]);
+
+        // Removing the newest feed must not free its seq for the next write
+        store.remove(&["a".to_string()]).await.unwrap();
+        let next = store.put(feed("d", 5, 1.0), "d").await.unwrap();
+        assert!(next.seq > seqs[2]);
+        assert_eq!(ids(&store.written_after(&everything, seqs[2], 10).await.unwrap()), ["d"]);
+    }
+
+    pub async fn check_recording_order(store: &dyn DecisionStore) {
+        let mut seqs = Vec::new();
+        for (agent_id, timestamp) in [("a", 300), ("b", 100), ("a", 200)] {
+            seqs.push(store.append(decision(agent_id, timestamp)).await.unwrap());
+        }
+        assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let timestamps = |decisions: Vec<AgentDecision>| -> Vec<u64> { decisions.iter().map(|d| d.timestamp).collect() };
+        let everything = DecisionQuery::default();
+        let recorded = store.recorded_after(&everything, 0, 10).await.unwrap();
+        assert_eq!(recorded.iter().map(|d| d.seq.unwrap()).collect::<Vec<u64>>(), seqs);
+        assert_eq!(timestamps(recorded), [300, 100, 200]);
+        assert_eq!(timestamps(store.recorded_after(&everything, seqs[0], 1).await.unwrap()), [100]);
+
+        let agent_a = DecisionQuery { agent_id: Some("a".to_string()), ..Default::default() };
+        assert_eq!(timestamps(store.recorded_after(&agent_a, seqs[0], 10).await.unwrap()), [200]);
+        let queried = store.query(&DecisionQuery { limit: 10, ..agent_a }).await.unwrap();
+        assert_eq!(queried.iter().map(|d| d.seq).collect::<Vec<_>>(), [Some(seqs[0]), Some(seqs[2])]);
+    }
+
+    pub async fn check_decision_eviction(store: &dyn DecisionStore) {
+        // Recorded out of timestamp order: eviction by count follows recording order
+        for timestamp in [100, 50, 300, 200, 400] {
+            store.append(decision("store-tests", timestamp)).await.unwrap();
+        }
+        let all = DecisionQuery { order: SortOrder::Asc, limit: 10, ..Default::default() };
+        let timestamps = |decisions: Vec<AgentDecision>| -> Vec<u64> { decisions.iter().map(|d| d.timestamp).collect() };
+        assert_eq!(timestamps(store.query(&all).await.unwrap()), [50, 100, 200, 300, 400]);
+
+        assert_eq!(store.evict(Some(100), None).await.unwrap(), 1);
+        assert_eq!(timestamps(store.query(&all).await.unwrap()), [100, 200, 300, 400]);
+
+        assert_eq!(store.evict(None, Some(2)).await.unwrap(), 2);
+        assert_eq!(timestamps(store.query(&all).await.unwrap()), [200, 400]);
+
+        assert_eq!(store.evict(Some(300), Some(1)).await.unwrap(), 1);
+        assert_eq!(timestamps(store.query(&all).await.unwrap()), [400]);
+        assert_eq!(store.evict(Some(300), Some(1)).await.unwrap(), 0);
+    }
+
+    pub async fn check_schemas(store: &dyn SchemaStore) {
+        let v1 = json!({ "type": "object", "required": ["a"] });
+        let v2 = json!({ "type": "object", "required": ["b"] });
+        store.put("weather", &v1).await.unwrap();
+        store.put("gas", &v1).await.unwrap();
+        store.put("weather", &v2).await.unwrap();
+        assert_eq!(
+            store.list().await.unwrap(),
+            [("gas".to_string(), v1.clone()), ("weather".to_string(), v2)]
+        );
+
+        assert!(store.remove("weather").await.unwrap());
+        assert!(!store.remove("weather").await.unwrap());
+        assert_eq!(store.list().await.unwrap(), [("gas".to_string(), v1)]);
+    }
+
+    #[tokio::test]
+    async fn memory_feed_store_put_and_versions() {
+        check_put_and_versions(&MemoryFeedStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn memory_feed_store_concurrent_puts() {
+        check_concurrent_puts(&MemoryFeedStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn memory_feed_store_cursor_paging() {
+        check_cursor_paging(&MemoryFeedStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn memory_feed_store_eviction() {
+        check_feed_eviction(&MemoryFeedStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn memory_feed_store_write_order() {
+        check_write_order(&MemoryFeedStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn memory_decision_store_recording_order() {
+        check_recording_order(&MemoryDecisionStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn memory_decision_store_eviction() {
+        check_decision_eviction(&MemoryDecisionStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn memory_decision_store_keeps_the_newest() {
+        let store = MemoryDecisionStore::default();
+        for timestamp in 0..MAX_DECISIONS_IN_MEMORY as u64 + 10 {
+            store.append(decision("store-tests", timestamp)).await.unwrap();
+        }
+        let all = DecisionQuery { order: SortOrder::Asc, limit: usize::MAX, ..Default::default() };
+        let kept = store.query(&all).await.unwrap();
+        assert_eq!(kept.len(), MAX_DECISIONS_IN_MEMORY);
+        assert_eq!(kept[0].timestamp, 10);
+    }
+
+    #[tokio::test]
+    async fn memory_schema_store() {
+        check_schemas(&MemorySchemaStore::default()).await;
+    }
+}
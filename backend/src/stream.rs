@@ -0,0 +1,276 @@
+// Live feed and agent decision events for SSE and WebSocket subscribers
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+
+use crate::indexer::{AgentDecision, DataFeed, RealtimeIndexer};
+use crate::storage::{DecisionQuery, FeedQuery};
+
+/// Stored events replayed to a subscriber that asks for `since` or resumes after an event id.
+pub const MAX_STREAM_REPLAY: usize = 10_000;
+
+const REPLAY_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamEvent {
+    /// `feed`, `decision`, `lagged` (the subscriber fell behind and events were dropped),
+    /// `truncated` (the replay hit `MAX_STREAM_REPLAY`) or `error`.
+    pub event: &'static str,
+    /// The `seq` of the feed or decision; clients resume strictly after it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    pub data: Value,
+}
+
+/// Parses an event id given as `after` or `Last-Event-ID`.
+pub fn parse_event_id(id: &str) -> Result<u64, String> {
+    id.trim().parse().map_err(|_| format!("Invalid event id: {}", id))
+}
+
+impl StreamEvent {
+    fn error(message: String) -> Self {
+        Self {
+            event: "error",
+            id: None,
+            data: json!({ "error": message }),
+        }
+    }
+}
+
+/// Feeds matching `filter`: stored feeds from `filter.since`, or written after the event id
+/// `after`, in write order, then live writes. The stream ends when the returned receiver is dropped.
+pub fn feed_events(indexer: &RealtimeIndexer, filter: FeedQuery, after: Option<u64>) -> mpsc::Receiver<StreamEvent> {
+    // Subscribe before replaying so writes made during the replay are not missed
+    let live = indexer.subscribe_feeds();
+    let (tx, rx) = mpsc::channel(256);
+    let indexer = indexer.clone();
+
+    tokio::spawn(async move {
+        let replay = if filter.since.is_some() || after.is_some() {
+            match replay_feeds(&indexer, &filter, after.unwrap_or_default()).await {
+                Ok(replay) => replay,
+                Err(e) => {
+                    let _ = tx.send(StreamEvent::error(e)).await;
+                    return;
+                }
+            }
+        } else {
+            Replay::default()
+        };
+
+        // `since` only selects the replay; live feeds may carry older timestamps (e.g. backfills)
+        let live_filter = FeedQuery { since: None, ..filter };
+        forward("feed", replay, live, move |feed| live_filter.matches(feed), |feed| feed.seq, tx).await;
+    });
+    rx
+}
+
+/// Decisions matching `filter`, replaying stored ones from `filter.since`, or recorded after
+/// the event id `after`, first.
+pub fn decision_events(
+    indexer: &RealtimeIndexer,
+    filter: DecisionQuery,
+    after: Option<u64>,
+) -> mpsc::Receiver<StreamEvent> {
+    let live = indexer.subscribe_decisions();
+    let (tx, rx) = mpsc::channel(256);
+    let indexer = indexer.clone();
+
+    tokio::spawn(async move {
+        let replay = if filter.since.is_some() || after.is_some() {
+            match replay_decisions(&indexer, &filter, after.unwrap_or_default()).await {
+                Ok(replay) => replay,
+                Err(e) => {
+                    let _ = tx.send(StreamEvent::error(e)).await;
+                    return;
+                }
+            }
+        } else {
+            Replay::default()
+        };
+
+        // Agents may report decisions with older timestamps
+        let live_filter = DecisionQuery { since: None, ..filter };
+        forward("decision", replay, live, move |d| live_filter.matches(d), |d| d.seq, tx).await;
+    });
+    rx
+}
+
+/// Stored events to send before live ones; `truncated` if more matched than `MAX_STREAM_REPLAY`.
+struct Replay<T> {
+    items: Vec<T>,
+    truncated: bool,
+}
+
+impl<T> Default for Replay<T> {
+    fn default() -> Self {
+        Self { items: Vec::new(), truncated: false }
+    }
+}
+
+impl<T> Replay<T> {
+    fn new(mut items: Vec<T>) -> Self {
+        let truncated = items.len() > MAX_STREAM_REPLAY;
+        items.truncate(MAX_STREAM_REPLAY);
+        Self { items, truncated }
+    }
+}
+
+async fn replay_feeds(indexer: &RealtimeIndexer, filter: &FeedQuery, after: u64) -> Result<Replay<DataFeed>, String> {
+    let mut feeds: Vec<DataFeed> = Vec::new();
+    loop {
+        let position = feeds.last().and_then(|feed| feed.seq).unwrap_or(after);
+        let page = indexer.feeds_written_after(filter, position, REPLAY_PAGE_SIZE).await?;
+        let last_page = page.len() < REPLAY_PAGE_SIZE;
+        feeds.extend(page);
+        if last_page || feeds.len() > MAX_STREAM_REPLAY {
+            return Ok(Replay::new(feeds));
+        }
+    }
+}
+
+async fn replay_decisions(
+    indexer: &RealtimeIndexer,
+    filter: &DecisionQuery,
+    after: u64,
+) -> Result<Replay<AgentDecision>, String> {
+    let mut decisions: Vec<AgentDecision> = Vec::new();
+    loop {
+        let position = decisions.last().and_then(|decision| decision.seq).unwrap_or(after);
+        let page = indexer.decisions_recorded_after(filter, position, REPLAY_PAGE_SIZE).await?;
+        let last_page = page.len() < REPLAY_PAGE_SIZE;
+        decisions.extend(page);
+        if last_page || decisions.len() > MAX_STREAM_REPLAY {
+            return Ok(Replay::new(decisions));
+        }
+    }
+}
+
+async fn forward<T: Clone + Serialize>(
+    event: &'static str,
+    replay: Replay<T>,
+    mut live: broadcast::Receiver<T>,
+    matches: impl Fn(&T) -> bool,
+    seq: impl Fn(&T) -> Option<u64>,
+    tx: mpsc::Sender<StreamEvent>,
+) {
+    let mut last_replayed = None;
+    for item in &replay.items {
+        let data = serde_json::to_value(item).unwrap_or_default();
+        last_replayed = seq(item).or(last_replayed);
+        if tx.send(StreamEvent { event, id: seq(item), data }).await.is_err() {
+            return;
+        }
+    }
+
+    // Live events follow, so the client has to resume from the last replayed id to fill the gap
+    if replay.truncated {
+        let truncated = StreamEvent {
+            event: "truncated",
+            id: None,
+            data: json!({ "replayed": replay.items.len(), "resume_after": last_replayed }),
+        };
+        if tx.send(truncated).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        let received = tokio::select! {
+            _ = tx.closed() => return,
+            received = live.recv() => received,
+        };
+        let item = match received {
+            Ok(item) => item,
+            Err(RecvError::Lagged(skipped)) => {
+                let lagged = StreamEvent {
+                    event: "lagged",
+                    id: None,
+                    data: json!({ "skipped": skipped }),
+                };
+                if tx.send(lagged).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        if !matches(&item) {
+            continue;
+        }
+
+        // Writes are sent in `seq` order, so anything up to the last replayed one was already
+        // stored when the replay read it
+        let id = seq(&item);
+        if last_replayed.is_some_and(|last| id.is_some_and(|id| id <= last)) {
+            continue;
+        }
+        let data = serde_json::to_value(&item).unwrap_or_default();
+        if tx.send(StreamEvent { event, id, data }).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{DataType, FeedSource};
+    use std::time::Duration;
+
+    fn event(feed_id: &str, timestamp: u64) -> DataFeed {
+        DataFeed {
+            feed_id: feed_id.to_string(),
+            source: FeedSource::OffChain("stream-tests".to_string()),
+            data_type: DataType::Event,
+            timestamp,
+            raw_data: json!({ "name": feed_id }),
+            cleaned_data: None,
+            seq: None,
+        }
+    }
+
+    async fn next(events: &mut mpsc::Receiver<StreamEvent>) -> StreamEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("no stream event within 5s")
+            .expect("stream ended")
+    }
+
+    fn feed_id(event: &StreamEvent) -> &str {
+        event.data["feed_id"].as_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn resumes_after_feeds_with_older_timestamps() {
+        let indexer = RealtimeIndexer::new();
+        indexer.ingest_feed(event("first", 200)).await.unwrap();
+        let first = indexer.get_feed("first").await.unwrap().unwrap().seq;
+
+        // Written later but with an older timestamp, like a backfilled block
+        indexer.ingest_feed(event("backfilled", 100)).await.unwrap();
+
+        let mut events = feed_events(&indexer, FeedQuery::default(), first);
+        let replayed = next(&mut events).await;
+        assert_eq!((replayed.event, feed_id(&replayed)), ("feed", "backfilled"));
+        assert!(replayed.id > first);
+
+        indexer.ingest_feed(event("live", 50)).await.unwrap();
+        let live = next(&mut events).await;
+        assert_eq!(feed_id(&live), "live");
+        assert!(live.id > replayed.id);
+    }
+
+    #[tokio::test]
+    async fn replayed_feeds_are_not_sent_again_live() {
+        let indexer = RealtimeIndexer::new();
+        let mut events = feed_events(&indexer, FeedQuery { since: Some(0), ..Default::default() }, None);
+        indexer.ingest_feed(event("a", 100)).await.unwrap();
+        indexer.ingest_feed(event("b", 100)).await.unwrap();
+
+        // Whether `a` was replayed or arrived live, it is delivered once
+        assert_eq!(feed_id(&next(&mut events).await), "a");
+        assert_eq!(feed_id(&next(&mut events).await), "b");
+    }
+}
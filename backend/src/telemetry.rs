@@ -0,0 +1,205 @@
+// Prometheus instrumentation for the indexer, Celo RPC client and AI engine
+use prometheus::{
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge, Encoder,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge, TextEncoder,
+};
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+const LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+// ============ Real-Time Indexer ============
+
+pub static FEEDS_INGESTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "sentinel_indexer_feeds_ingested_total",
+        "Feeds ingested by the indexer",
+        &["source", "data_type"]
+    )
+    .unwrap()
+});
+
+pub static INGEST_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "sentinel_indexer_ingest_errors_total",
+        "Feeds rejected during ingestion, by stage (cleaning, storage)",
+        &["stage", "data_type"]
+    )
+    .unwrap()
+});
+
+pub static INGEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "sentinel_indexer_ingest_duration_seconds",
+        "Time to clean and store a feed",
+        &["data_type"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+pub static DUPLICATE_FEEDS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!(
+        "sentinel_indexer_duplicate_feeds_total",
+        "Feeds skipped because identical content was already stored"
+    )
+    .unwrap()
+});
+
+pub static RETENTION_EVICTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "sentinel_retention_evicted_total",
+        "Feeds and decisions removed by retention policies, by store and rule selector",
+        &["store", "rule"]
+    )
+    .unwrap()
+});
+
+pub static DECISIONS_RECORDED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "sentinel_indexer_decisions_recorded_total",
+        "Agent decisions recorded by the indexer",
+        &["decision_type"]
+    )
+    .unwrap()
+});
+
+pub static DECISIONS_REJECTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "sentinel_indexer_decisions_rejected_total",
+        "Agent decisions rejected by validation or provenance checks",
+        &["decision_type"]
+    )
+    .unwrap()
+});
+
+pub static MONITOR_ALERTS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("sentinel_monitor_alerts_total", "Alerts raised by Monitor decision thresholds").unwrap()
+});
+
+pub static ALERT_SINK_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("sentinel_alert_sink_errors_total", "Failed monitor alert deliveries by sink", &["sink"])
+        .unwrap()
+});
+
+pub static WEBHOOK_ATTEMPTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "sentinel_webhook_attempts_total",
+        "Webhook delivery attempts by event and outcome (delivered, retrying, dead_lettered)",
+        &["event", "outcome"]
+    )
+    .unwrap()
+});
+
+pub static REORGS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("sentinel_indexer_reorgs_total", "Chain reorgs rolled back by the indexer").unwrap()
+});
+
+pub static ACTIVE_FEEDS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("sentinel_indexer_active_feeds", "Feeds currently held by the indexer").unwrap()
+});
+
+pub static CHAIN_HEAD: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("sentinel_chain_head_block", "Latest chain head seen by the block follower").unwrap()
+});
+
+pub static LAST_INDEXED_BLOCK: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("sentinel_indexer_last_indexed_block", "Last block indexed by the block follower").unwrap()
+});
+
+pub static CHAIN_HEAD_LAG: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "sentinel_indexer_chain_head_lag_blocks",
+        "Blocks between the chain head and the last indexed block"
+    )
+    .unwrap()
+});
+
+// ============ Celo RPC ============
+
+pub static RPC_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("sentinel_rpc_requests_total", "Celo RPC calls by method", &["method"]).unwrap()
+});
+
+pub static RPC_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("sentinel_rpc_errors_total", "Failed Celo RPC calls by method", &["method"]).unwrap()
+});
+
+pub static RPC_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "sentinel_rpc_request_duration_seconds",
+        "Celo RPC call latency by method",
+        &["method"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+// ============ AI Engine ============
+
+pub static AI_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("sentinel_ai_requests_total", "LLM provider calls", &["provider"]).unwrap()
+});
+
+pub static AI_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("sentinel_ai_errors_total", "Failed LLM provider calls", &["provider"]).unwrap()
+});
+
+pub static AI_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "sentinel_ai_request_duration_seconds",
+        "LLM provider call latency",
+        &["provider"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+pub static AI_CACHE_HITS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("sentinel_ai_cache_hits_total", "AI engine responses served from cache").unwrap()
+});
+
+pub static AI_CACHE_MISSES: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("sentinel_ai_cache_misses_total", "AI engine requests that missed the cache").unwrap()
+});
+
+/// Runs an RPC call, recording its count, latency and failure under `method`.
+pub async fn time_rpc<T, E>(method: &str, call: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    let start = Instant::now();
+    let result = call.await;
+
+    RPC_REQUESTS.with_label_values(&[method]).inc();
+    RPC_DURATION.with_label_values(&[method]).observe(start.elapsed().as_secs_f64());
+    if result.is_err() {
+        RPC_ERRORS.with_label_values(&[method]).inc();
+    }
+    result
+}
+
+/// Runs an LLM provider call, recording its count, latency and failure under `provider`.
+pub async fn time_ai<T, E>(provider: &str, call: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    let start = Instant::now();
+    let result = call.await;
+
+    AI_REQUESTS.with_label_values(&[provider]).inc();
+    AI_DURATION.with_label_values(&[provider]).observe(start.elapsed().as_secs_f64());
+    if result.is_err() {
+        AI_ERRORS.with_label_values(&[provider]).inc();
+    }
+    result
+}
+
+/// All registered metrics in the Prometheus text exposition format.
+pub fn render() -> String {
+    // Unlabelled metrics are registered on first use; force them so every scrape lists them
+    for counter in [&REORGS, &DUPLICATE_FEEDS, &MONITOR_ALERTS, &AI_CACHE_HITS, &AI_CACHE_MISSES] {
+        LazyLock::force(counter);
+    }
+
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+        tracing::error!("Failed to encode metrics: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
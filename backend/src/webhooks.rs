@@ -0,0 +1,977 @@
+// Webhook endpoints notified of alerts, new feeds and chain reorgs, with signed and retried deliveries
+use ethers::core::rand::{self, Rng};
+use ethers::utils::hex;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::{watch, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+
+use crate::indexer::{AgentDecision, DataFeed, DecisionType, RealtimeIndexer, ReorgNotice, ALERT_SEVERITIES};
+use crate::storage::FeedQuery;
+use crate::telemetry;
+
+pub const EVENT_HEADER: &str = "X-Sentinel-Event";
+pub const DELIVERY_HEADER: &str = "X-Sentinel-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-Sentinel-Timestamp";
+/// `sha256=<hex HMAC-SHA256 of "<timestamp>.<body>" keyed by the webhook secret>`.
+pub const SIGNATURE_HEADER: &str = "X-Sentinel-Signature";
+
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// Attempts kept per webhook for the delivery log.
+const MAX_DELIVERY_LOG: usize = 200;
+/// Dead letters kept before the oldest is dropped.
+const MAX_DEAD_LETTERS: usize = 1_000;
+/// Deliveries (including ones waiting out a backoff) running at once for a single webhook.
+const MAX_IN_FLIGHT_PER_WEBHOOK: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    /// An `Alert` agent decision (including monitor and reorg alerts).
+    Alert,
+    /// A new or updated feed; duplicates are not delivered.
+    Feed,
+    /// A chain reorg rolled back by the block follower.
+    Reorg,
+}
+
+impl WebhookEvent {
+    pub const NAMES: [&'static str; 3] = ["alert", "feed", "reorg"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "alert" => Some(WebhookEvent::Alert),
+            "feed" => Some(WebhookEvent::Feed),
+            "reorg" => Some(WebhookEvent::Reorg),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::Alert => "alert",
+            WebhookEvent::Feed => "feed",
+            WebhookEvent::Reorg => "reorg",
+        }
+    }
+}
+
+/// Narrows which alerts and feeds a webhook receives; reorgs are not filtered.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WebhookFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub severity: Vec<String>,
+    /// `source` and `data_type` as a feed filter.
+    #[serde(skip)]
+    pub feeds: FeedQuery,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    #[serde(skip)]
+    secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub filter: WebhookFilter,
+    pub created_at: u64,
+}
+
+impl Webhook {
+    fn wants(&self, event: &Event) -> bool {
+        if !self.events.contains(&event.kind()) {
+            return false;
+        }
+        match event {
+            Event::Feed(feed) => self.filter.feeds.matches(feed),
+            Event::Alert(decision) => {
+                let DecisionType::Alert { severity, .. } = &decision.decision_type else {
+                    return false;
+                };
+                self.filter.agent_id.as_ref().is_none_or(|agent| *agent == decision.agent_id)
+                    && (self.filter.severity.is_empty() || self.filter.severity.contains(severity))
+            }
+            Event::Reorg(_) => true,
+        }
+    }
+}
+
+enum Event {
+    Feed(DataFeed),
+    Alert(AgentDecision),
+    Reorg(ReorgNotice),
+}
+
+impl Event {
+    fn kind(&self) -> WebhookEvent {
+        match self {
+            Event::Feed(_) => WebhookEvent::Feed,
+            Event::Alert(_) => WebhookEvent::Alert,
+            Event::Reorg(_) => WebhookEvent::Reorg,
+        }
+    }
+
+    fn data(&self) -> Value {
+        match self {
+            Event::Feed(feed) => serde_json::to_value(feed),
+            Event::Alert(decision) => serde_json::to_value(decision),
+            Event::Reorg(notice) => serde_json::to_value(notice),
+        }
+        .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub max_attempts: u32,
+    pub backoff_base_ms: u64, // doubled after every failed attempt
+    pub backoff_max_ms: u64,
+    pub timeout_ms: u64,
+    pub max_concurrency: usize, // requests in flight across all webhooks
+    pub queue_size: usize,      // deliveries waiting per webhook before new ones are dead-lettered
+    /// Accept webhook URLs on loopback, private and link-local addresses (see `check_outbound_url`).
+    pub allow_private_urls: bool,
+}
+
+impl WebhookConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_attempts: std::env::var("WEBHOOK_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse::<u32>()
+                .expect("WEBHOOK_MAX_ATTEMPTS must be a valid number")
+                .max(1),
+            backoff_base_ms: std::env::var("WEBHOOK_BACKOFF_BASE_MS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .expect("WEBHOOK_BACKOFF_BASE_MS must be a valid number"),
+            backoff_max_ms: std::env::var("WEBHOOK_BACKOFF_MAX_MS")
+                .unwrap_or_else(|_| "300000".to_string())
+                .parse()
+                .expect("WEBHOOK_BACKOFF_MAX_MS must be a valid number"),
+            timeout_ms: std::env::var("WEBHOOK_TIMEOUT_MS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .expect("WEBHOOK_TIMEOUT_MS must be a valid number"),
+            max_concurrency: std::env::var("WEBHOOK_MAX_CONCURRENCY")
+                .unwrap_or_else(|_| "32".to_string())
+                .parse::<usize>()
+                .expect("WEBHOOK_MAX_CONCURRENCY must be a valid number")
+                .max(1),
+            queue_size: std::env::var("WEBHOOK_QUEUE_SIZE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse::<usize>()
+                .expect("WEBHOOK_QUEUE_SIZE must be a valid number")
+                .max(1),
+            allow_private_urls: allow_private_urls_from_env(),
+        }
+    }
+
+    /// Wait before attempt `attempt + 1`, after `attempt` failures.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self.backoff_base_ms.saturating_mul(1 << (attempt - 1).min(20));
+        Duration::from_millis(delay.min(self.backoff_max_ms))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptOutcome {
+    Delivered,
+    Retrying,
+    DeadLettered,
+}
+
+/// One HTTP attempt at delivering an event, as shown in the delivery log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    pub delivery_id: String,
+    pub event: WebhookEvent,
+    pub attempt: u32,
+    pub attempted_at: u64,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: f64,
+    pub outcome: AttemptOutcome,
+}
+
+/// A delivery that exhausted its attempts or was refused by the endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub delivery_id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub payload: Value,
+    pub attempts: u32,
+    pub last_error: String,
+    pub failed_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookStatus {
+    pub running: bool,
+    pub webhooks: usize,
+    pub in_flight: usize,
+    /// Deliveries waiting in the per-webhook queues.
+    pub queued: usize,
+    pub delivered: u64,
+    pub failed_attempts: u64,
+    pub dead_lettered: u64,
+    /// Events dropped because the dispatcher fell behind the indexer.
+    pub events_skipped: u64,
+    /// Deliveries dead-lettered without an attempt because their webhook's queue was full.
+    pub queue_overflows: u64,
+}
+
+#[derive(Clone)]
+struct Delivery {
+    id: String,
+    webhook_id: String,
+    event: WebhookEvent,
+    payload: Value,
+}
+
+struct Shared {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    permits: Semaphore,
+    webhooks: RwLock<BTreeMap<String, Webhook>>,
+    /// Per-webhook delivery queues, each drained by its own worker task.
+    queues: RwLock<HashMap<String, mpsc::Sender<Delivery>>>,
+    log: RwLock<HashMap<String, VecDeque<DeliveryAttempt>>>,
+    dead_letters: RwLock<VecDeque<DeadLetter>>,
+    status: RwLock<WebhookStatus>,
+}
+
+/// Registered webhooks and the task fanning indexer events out to them. Registrations,
+/// the delivery log and dead letters are held in memory.
+pub struct WebhookDispatcher {
+    shared: Arc<Shared>,
+    shutdown: watch::Sender<bool>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl WebhookDispatcher {
+    pub fn spawn(config: WebhookConfig, indexer: RealtimeIndexer) -> Self {
+        let shared = Arc::new(Shared {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_millis(config.timeout_ms))
+                // A redirect could lead a delivery past `check_outbound_url`
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap_or_default(),
+            permits: Semaphore::new(config.max_concurrency),
+            config,
+            webhooks: RwLock::new(BTreeMap::new()),
+            queues: RwLock::new(HashMap::new()),
+            log: RwLock::new(HashMap::new()),
+            dead_letters: RwLock::new(VecDeque::new()),
+            status: RwLock::new(WebhookStatus {
+                running: true,
+                ..Default::default()
+            }),
+        });
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        // Subscribe before spawning so events recorded right after `spawn` returns are delivered
+        let events = (indexer.subscribe_feeds(), indexer.subscribe_decisions(), indexer.subscribe_reorgs());
+        let task = tokio::spawn(run(events, shared.clone(), shutdown_rx));
+
+        Self {
+            shared,
+            shutdown,
+            task: Mutex::new(Some(task)),
+        }
+    }
+
+    pub fn config(&self) -> &WebhookConfig {
+        &self.shared.config
+    }
+
+    pub async fn status(&self) -> WebhookStatus {
+        let mut status = self.shared.status.read().await.clone();
+        status.webhooks = self.shared.webhooks.read().await.len();
+        status.in_flight = self.shared.config.max_concurrency - self.shared.permits.available_permits();
+        status.queued = self
+            .shared
+            .queues
+            .read()
+            .await
+            .values()
+            .map(|queue| queue.max_capacity() - queue.capacity())
+            .sum();
+        status
+    }
+
+    pub async fn register(
+        &self,
+        url: String,
+        secret: String,
+        events: Vec<WebhookEvent>,
+        filter: WebhookFilter,
+    ) -> Result<Webhook, String> {
+        check_outbound_url(&url, self.shared.config.allow_private_urls).await?;
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(format!("secret must be at least {} characters", MIN_SECRET_LEN));
+        }
+        if events.is_empty() {
+            return Err(format!("events must list at least one of {}", WebhookEvent::NAMES.join(", ")));
+        }
+        if let Some(severity) = filter.severity.iter().find(|s| !ALERT_SEVERITIES.contains(&s.as_str())) {
+            return Err(format!("Invalid severity: {} (expected one of {})", severity, ALERT_SEVERITIES.join(", ")));
+        }
+
+        let webhook = Webhook {
+            id: format!("wh_{}", random_id()),
+            url,
+            secret,
+            events,
+            filter,
+            created_at: unix_now(),
+        };
+        let (queue, deliveries) = mpsc::channel(self.shared.config.queue_size);
+        self.shared.queues.write().await.insert(webhook.id.clone(), queue);
+        self.shared.webhooks.write().await.insert(webhook.id.clone(), webhook.clone());
+        tokio::spawn(work(self.shared.clone(), deliveries));
+        tracing::info!("Registered webhook {} for {}", webhook.id, webhook.url);
+        Ok(webhook)
+    }
+
+    pub async fn list(&self) -> Vec<Webhook> {
+        self.shared.webhooks.read().await.values().cloned().collect()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Webhook> {
+        self.shared.webhooks.read().await.get(id).cloned()
+    }
+
+    /// Unregisters a webhook; its queued deliveries and pending retries are abandoned.
+    pub async fn remove(&self, id: &str) -> bool {
+        let removed = self.shared.webhooks.write().await.remove(id).is_some();
+        if removed {
+            // Closing the queue stops the worker once it has drained what is left
+            self.shared.queues.write().await.remove(id);
+            self.shared.log.write().await.remove(id);
+        }
+        removed
+    }
+
+    /// The most recent delivery attempts for a webhook, newest first.
+    pub async fn deliveries(&self, id: &str, limit: usize) -> Option<Vec<DeliveryAttempt>> {
+        self.get(id).await?;
+        let log = self.shared.log.read().await;
+        Some(
+            log.get(id)
+                .map(|attempts| attempts.iter().rev().take(limit).cloned().collect())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Dead letters, newest first, optionally for one webhook.
+    pub async fn dead_letters(&self, webhook_id: Option<&str>) -> Vec<DeadLetter> {
+        self.shared
+            .dead_letters
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|letter| webhook_id.is_none_or(|id| letter.webhook_id == id))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes a dead letter and queues it again with a fresh set of attempts. If the webhook's
+    /// queue is full, it goes straight back to the dead-letter queue.
+    pub async fn retry_dead_letter(&self, delivery_id: &str) -> Result<(), String> {
+        let letter = {
+            let mut letters = self.shared.dead_letters.write().await;
+            let index = letters
+                .iter()
+                .position(|letter| letter.delivery_id == delivery_id)
+                .ok_or_else(|| format!("Dead letter {} not found", delivery_id))?;
+            if self.get(&letters[index].webhook_id).await.is_none() {
+                return Err(format!("Webhook {} no longer exists", letters[index].webhook_id));
+            }
+            letters.remove(index).unwrap()
+        };
+
+        enqueue(
+            &self.shared,
+            Delivery {
+                id: letter.delivery_id,
+                webhook_id: letter.webhook_id,
+                event: letter.event,
+                payload: letter.payload,
+            },
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Stops fanning out new events; deliveries already in progress are dropped with the runtime.
+    pub async fn shutdown(&self) {
+        let _ = self.shutdown.send(true);
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
+
+type Subscriptions = (
+    broadcast::Receiver<DataFeed>,
+    broadcast::Receiver<AgentDecision>,
+    broadcast::Receiver<ReorgNotice>,
+);
+
+async fn run(events: Subscriptions, shared: Arc<Shared>, mut shutdown: watch::Receiver<bool>) {
+    let (mut feeds, mut decisions, mut reorgs) = events;
+
+    loop {
+        let received = tokio::select! {
+            _ = shutdown.changed() => break,
+            feed = feeds.recv() => feed.map(Event::Feed),
+            decision = decisions.recv() => decision.map(Event::Alert),
+            notice = reorgs.recv() => notice.map(Event::Reorg),
+        };
+        let event = match received {
+            Ok(Event::Alert(decision)) if !matches!(decision.decision_type, DecisionType::Alert { .. }) => continue,
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Webhook dispatcher fell behind; {} events were not delivered", skipped);
+                shared.status.write().await.events_skipped += skipped;
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        let targets: Vec<String> = shared
+            .webhooks
+            .read()
+            .await
+            .values()
+            .filter(|webhook| webhook.wants(&event))
+            .map(|webhook| webhook.id.clone())
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
+
+        let kind = event.kind();
+        let data = event.data();
+        for webhook_id in targets {
+            let id = format!("dlv_{}", random_id());
+            let payload = json!({
+                "id": id,
+                "event": kind,
+                "webhook_id": webhook_id,
+                "created_at": unix_now(),
+                "data": data,
+            });
+            enqueue(&shared, Delivery { id, webhook_id, event: kind, payload }).await;
+        }
+    }
+
+    shared.status.write().await.running = false;
+}
+
+/// Queues a delivery for its webhook's worker. When the queue is full the delivery is
+/// dead-lettered instead, so a slow or failing endpoint can't build an unbounded backlog.
+async fn enqueue(shared: &Shared, delivery: Delivery) {
+    let Some(queue) = shared.queues.read().await.get(&delivery.webhook_id).cloned() else {
+        return;
+    };
+    match queue.try_send(delivery) {
+        Ok(()) => {}
+        Err(TrySendError::Full(delivery)) => {
+            shared.status.write().await.queue_overflows += 1;
+            let error = format!("Delivery queue full ({} waiting)", shared.config.queue_size);
+            dead_letter(shared, delivery, 0, error).await;
+        }
+        // The webhook was removed in the meantime
+        Err(TrySendError::Closed(_)) => {}
+    }
+}
+
+/// Drains one webhook's queue, running up to `MAX_IN_FLIGHT_PER_WEBHOOK` deliveries at a time.
+async fn work(shared: Arc<Shared>, mut deliveries: mpsc::Receiver<Delivery>) {
+    let slots = Arc::new(Semaphore::new(MAX_IN_FLIGHT_PER_WEBHOOK));
+    loop {
+        let Ok(slot) = slots.clone().acquire_owned().await else {
+            return;
+        };
+        let Some(delivery) = deliveries.recv().await else {
+            return;
+        };
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            deliver(shared, delivery).await;
+            drop(slot);
+        });
+    }
+}
+
+/// Attempts a delivery until it succeeds, the endpoint refuses it, or attempts run out.
+async fn deliver(shared: Arc<Shared>, delivery: Delivery) {
+    let body = delivery.payload.to_string();
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        // Stop retrying for webhooks removed in the meantime
+        let Some(webhook) = shared.webhooks.read().await.get(&delivery.webhook_id).cloned() else {
+            return;
+        };
+
+        let start = Instant::now();
+        let result = {
+            let _permit = shared.permits.acquire().await;
+            send(&shared.client, &webhook, &delivery, &body).await
+        };
+        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let (status_code, error, retryable) = match result {
+            Ok(status) if status.is_success() => (Some(status.as_u16()), None, false),
+            Ok(status) => {
+                let retryable = status.is_server_error() || status.as_u16() == 408 || status.as_u16() == 429;
+                (Some(status.as_u16()), Some(format!("Endpoint returned {}", status)), retryable)
+            }
+            Err(e) => (None, Some(e), true),
+        };
+        let outcome = match &error {
+            None => AttemptOutcome::Delivered,
+            Some(_) if retryable && attempt < shared.config.max_attempts => AttemptOutcome::Retrying,
+            Some(_) => AttemptOutcome::DeadLettered,
+        };
+        telemetry::WEBHOOK_ATTEMPTS
+            .with_label_values(&[delivery.event.name(), outcome_label(outcome)])
+            .inc();
+
+        record_attempt(
+            &shared,
+            &delivery.webhook_id,
+            DeliveryAttempt {
+                delivery_id: delivery.id.clone(),
+                event: delivery.event,
+                attempt,
+                attempted_at: unix_now(),
+                status_code,
+                error: error.clone(),
+                duration_ms,
+                outcome,
+            },
+        )
+        .await;
+
+        match outcome {
+            AttemptOutcome::Delivered => {
+                shared.status.write().await.delivered += 1;
+                return;
+            }
+            AttemptOutcome::Retrying => {
+                shared.status.write().await.failed_attempts += 1;
+                tokio::time::sleep(shared.config.backoff(attempt)).await;
+            }
+            AttemptOutcome::DeadLettered => {
+                shared.status.write().await.failed_attempts += 1;
+                dead_letter(&shared, delivery, attempt, error.unwrap_or_default()).await;
+                return;
+            }
+        }
+    }
+}
+
+async fn dead_letter(shared: &Shared, delivery: Delivery, attempts: u32, last_error: String) {
+    tracing::warn!(
+        "Webhook delivery {} to {} dead-lettered after {} attempt(s): {}",
+        delivery.id,
+        delivery.webhook_id,
+        attempts,
+        last_error
+    );
+    shared.status.write().await.dead_lettered += 1;
+
+    let mut letters = shared.dead_letters.write().await;
+    if letters.len() >= MAX_DEAD_LETTERS {
+        letters.pop_front();
+    }
+    letters.push_back(DeadLetter {
+        delivery_id: delivery.id,
+        webhook_id: delivery.webhook_id,
+        event: delivery.event,
+        payload: delivery.payload,
+        attempts,
+        last_error,
+        failed_at: unix_now(),
+    });
+}
+
+async fn send(
+    client: &reqwest::Client,
+    webhook: &Webhook,
+    delivery: &Delivery,
+    body: &str,
+) -> Result<reqwest::StatusCode, String> {
+    // Signed per attempt so receivers can reject stale timestamps
+    let timestamp = unix_now().to_string();
+    let response = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, delivery.event.name())
+        .header(DELIVERY_HEADER, &delivery.id)
+        .header(TIMESTAMP_HEADER, &timestamp)
+        .header(SIGNATURE_HEADER, format!("sha256={}", sign(&webhook.secret, &timestamp, body)))
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    Ok(response.status())
+}
+
+/// Hex HMAC-SHA256 of `<timestamp>.<body>`.
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+async fn record_attempt(shared: &Shared, webhook_id: &str, attempt: DeliveryAttempt) {
+    let mut log = shared.log.write().await;
+    let attempts = log.entry(webhook_id.to_string()).or_default();
+    if attempts.len() >= MAX_DELIVERY_LOG {
+        attempts.pop_front();
+    }
+    attempts.push_back(attempt);
+}
+
+fn outcome_label(outcome: AttemptOutcome) -> &'static str {
+    match outcome {
+        AttemptOutcome::Delivered => "delivered",
+        AttemptOutcome::Retrying => "retrying",
+        AttemptOutcome::DeadLettered => "dead_lettered",
+    }
+}
+
+fn random_id() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 8]>())
+}
+
+/// A secret for webhooks registered by the server itself rather than through the API.
+/// `ALLOW_PRIVATE_CALLBACK_URLS`: whether webhooks and backtest callback agents may target
+/// internal addresses. Off by default; turn it on for local development.
+pub fn allow_private_urls_from_env() -> bool {
+    std::env::var("ALLOW_PRIVATE_CALLBACK_URLS")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+/// Checks a URL the server will POST to on a client's behalf. Unless `allow_private` is set,
+/// the host must not be, or resolve to, a loopback, private, link-local (such as cloud metadata
+/// endpoints), shared, unique-local, multicast or unspecified address. Host names are resolved
+/// once, here, so one that later resolves elsewhere is not caught.
+pub async fn check_outbound_url(url: &str, allow_private: bool) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| format!("url must be an http(s) URL, got {}", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("url must be an http(s) URL, got {}", url));
+    }
+    let host = parsed.host_str().ok_or_else(|| format!("url has no host: {}", url))?;
+    if allow_private {
+        return Ok(());
+    }
+
+    // IPv6 literals come back bracketed
+    let literal = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>();
+    let addresses: Vec<IpAddr> = match literal {
+        Ok(ip) => vec![ip],
+        Err(_) => tokio::net::lookup_host((host, parsed.port_or_known_default().unwrap_or(80)))
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+            .map(|address| address.ip())
+            .collect(),
+    };
+    match addresses.into_iter().find(|ip| is_internal(*ip)) {
+        Some(ip) => Err(format!(
+            "url {} points to the internal address {}; set ALLOW_PRIVATE_CALLBACK_URLS=true to allow it",
+            url, ip
+        )),
+        None => Ok(()),
+    }
+}
+
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || (a == 100 && (64..128).contains(&b)) // shared address space (RFC 6598)
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link local
+                || ip.to_ipv4_mapped().is_some_and(|v4| is_internal(IpAddr::V4(v4)))
+        }
+    }
+}
+
+pub fn random_secret() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{DataType, FeedSource};
+    use axum::body::Bytes;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::Router;
+    use std::collections::HashSet;
+
+    const SECRET: &str = "webhook-test-secret";
+
+    struct Received {
+        headers: HeaderMap,
+        body: String,
+        at: Instant,
+    }
+
+    /// Answers with the scripted statuses in order, then `200`, after `delay`.
+    #[derive(Clone)]
+    struct Receiver {
+        script: Arc<Mutex<VecDeque<u16>>>,
+        received: Arc<Mutex<Vec<Received>>>,
+        delay: Duration,
+    }
+
+    impl Receiver {
+        async fn start(script: &[u16], delay: Duration) -> (Self, String) {
+            let receiver = Self {
+                script: Arc::new(Mutex::new(script.iter().copied().collect())),
+                received: Arc::new(Mutex::new(Vec::new())),
+                delay,
+            };
+            let app = Router::new().route("/hook", post(receive)).with_state(receiver.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            (receiver, url)
+        }
+
+        fn count(&self) -> usize {
+            self.received.lock().unwrap().len()
+        }
+    }
+
+    async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> StatusCode {
+        receiver.received.lock().unwrap().push(Received {
+            headers,
+            body: String::from_utf8(body.to_vec()).unwrap(),
+            at: Instant::now(),
+        });
+        tokio::time::sleep(receiver.delay).await;
+        let status = receiver.script.lock().unwrap().pop_front().unwrap_or(200);
+        StatusCode::from_u16(status).unwrap()
+    }
+
+    fn config(max_attempts: u32, queue_size: usize) -> WebhookConfig {
+        WebhookConfig {
+            max_attempts,
+            backoff_base_ms: 20,
+            backoff_max_ms: 1_000,
+            timeout_ms: 5_000,
+            max_concurrency: 32,
+            queue_size,
+            allow_private_urls: true,
+        }
+    }
+
+    async fn dispatcher(config: WebhookConfig, url: String) -> (WebhookDispatcher, RealtimeIndexer, Webhook) {
+        let indexer = RealtimeIndexer::new();
+        let dispatcher = WebhookDispatcher::spawn(config, indexer.clone());
+        let webhook = dispatcher
+            .register(url, SECRET.to_string(), vec![WebhookEvent::Feed], WebhookFilter::default())
+            .await
+            .unwrap();
+        (dispatcher, indexer, webhook)
+    }
+
+    async fn ingest(indexer: &RealtimeIndexer, feed_id: &str) {
+        let feed = DataFeed {
+            feed_id: feed_id.to_string(),
+            source: FeedSource::OffChain("webhook-tests".to_string()),
+            data_type: DataType::Custom("test".to_string()),
+            timestamp: unix_now(),
+            raw_data: json!({ "feed": feed_id }),
+            cleaned_data: None,
+            seq: None,
+        };
+        indexer.ingest_feed(feed).await.unwrap();
+    }
+
+    async fn eventually(what: &str, condition: impl AsyncFn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition().await {
+            assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn outcomes(attempts: &[DeliveryAttempt]) -> Vec<AttemptOutcome> {
+        attempts.iter().rev().map(|a| a.outcome).collect()
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_signed() {
+        let (receiver, url) = Receiver::start(&[], Duration::ZERO).await;
+        let (dispatcher, indexer, webhook) = dispatcher(config(3, 10), url).await;
+        ingest(&indexer, "signed").await;
+        eventually("the delivery", async || dispatcher.status().await.delivered == 1).await;
+
+        let received = receiver.received.lock().unwrap();
+        let request = &received[0];
+        let header = |name: &str| request.headers.get(name).unwrap().to_str().unwrap().to_string();
+        let timestamp = header(TIMESTAMP_HEADER);
+        assert_eq!(header(SIGNATURE_HEADER), format!("sha256={}", sign(SECRET, &timestamp, &request.body)));
+        assert_ne!(header(SIGNATURE_HEADER), format!("sha256={}", sign("another-secret-value", &timestamp, &request.body)));
+        assert_eq!(header(EVENT_HEADER), "feed");
+
+        let payload: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(payload["id"], header(DELIVERY_HEADER));
+        assert_eq!(payload["webhook_id"], webhook.id);
+        assert_eq!(payload["data"]["feed_id"], "signed");
+    }
+
+    #[tokio::test]
+    async fn retryable_statuses_back_off_until_delivered() {
+        let (receiver, url) = Receiver::start(&[503, 408, 429], Duration::ZERO).await;
+        let config = config(5, 10);
+        let (dispatcher, indexer, webhook) = dispatcher(config.clone(), url).await;
+        ingest(&indexer, "retried").await;
+        eventually("the delivery", async || dispatcher.status().await.delivered == 1).await;
+
+        let attempts = dispatcher.deliveries(&webhook.id, 10).await.unwrap();
+        use AttemptOutcome::*;
+        assert_eq!(outcomes(&attempts), [Retrying, Retrying, Retrying, Delivered]);
+        assert!(dispatcher.dead_letters(None).await.is_empty());
+
+        let received = receiver.received.lock().unwrap();
+        let delivery_ids: HashSet<_> = received.iter().map(|r| r.headers[DELIVERY_HEADER].clone()).collect();
+        assert_eq!(delivery_ids.len(), 1);
+        for (attempt, pair) in received.windows(2).enumerate() {
+            assert!(pair[1].at - pair[0].at >= config.backoff(attempt as u32 + 1));
+        }
+    }
+
+    #[tokio::test]
+    async fn client_errors_dead_letter_without_retrying() {
+        let (receiver, url) = Receiver::start(&[400], Duration::ZERO).await;
+        let (dispatcher, indexer, webhook) = dispatcher(config(5, 10), url).await;
+        ingest(&indexer, "refused").await;
+        eventually("the dead letter", async || dispatcher.status().await.dead_lettered == 1).await;
+
+        let letters = dispatcher.dead_letters(Some(&webhook.id)).await;
+        assert_eq!(letters[0].attempts, 1);
+        assert_eq!(receiver.count(), 1);
+        let attempts = dispatcher.deliveries(&webhook.id, 10).await.unwrap();
+        assert_eq!(outcomes(&attempts), [AttemptOutcome::DeadLettered]);
+    }
+
+    #[tokio::test]
+    async fn exhausted_attempts_dead_letter_and_can_be_retried() {
+        let (receiver, url) = Receiver::start(&[500, 500, 500], Duration::ZERO).await;
+        let (dispatcher, indexer, webhook) = dispatcher(config(3, 10), url).await;
+        ingest(&indexer, "exhausted").await;
+        eventually("the dead letter", async || dispatcher.status().await.dead_lettered == 1).await;
+
+        let letter = dispatcher.dead_letters(None).await.remove(0);
+        assert_eq!(letter.attempts, 3);
+        assert_eq!(letter.webhook_id, webhook.id);
+        assert_eq!(receiver.count(), 3);
+
+        // The receiver answers 200 from now on
+        dispatcher.retry_dead_letter(&letter.delivery_id).await.unwrap();
+        eventually("the redelivery", async || dispatcher.status().await.delivered == 1).await;
+        assert!(dispatcher.dead_letters(None).await.is_empty());
+        assert!(dispatcher.retry_dead_letter(&letter.delivery_id).await.is_err());
+        let received = receiver.received.lock().unwrap();
+        assert_eq!(received.len(), 4);
+        assert_eq!(received[3].headers[DELIVERY_HEADER], letter.delivery_id.as_str());
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_dead_letters_new_deliveries() {
+        let (receiver, url) = Receiver::start(&[], Duration::from_millis(300)).await;
+        let (dispatcher, indexer, webhook) = dispatcher(config(1, 2), url).await;
+        for i in 0..20 {
+            ingest(&indexer, &format!("burst-{}", i)).await;
+        }
+        eventually("the burst to settle", async || {
+            let status = dispatcher.status().await;
+            status.delivered + status.dead_lettered == 20
+        })
+        .await;
+
+        let status = dispatcher.status().await;
+        assert!(status.queue_overflows > 0);
+        assert_eq!(status.queue_overflows, status.dead_lettered);
+        assert!(status.delivered as usize <= MAX_IN_FLIGHT_PER_WEBHOOK + 2);
+        assert_eq!(receiver.count() as u64, status.delivered);
+        let letter = &dispatcher.dead_letters(Some(&webhook.id)).await[0];
+        assert_eq!(letter.attempts, 0);
+        assert!(letter.last_error.contains("queue full"));
+    }
+
+    #[tokio::test]
+    async fn outbound_urls_to_internal_addresses_are_rejected() {
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://localhost/hook",
+            "http://10.1.2.3/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:192.168.0.1]/hook",
+        ] {
+            let error = check_outbound_url(url, false).await.unwrap_err();
+            assert!(error.contains("internal address"), "{}: {}", url, error);
+        }
+        check_outbound_url("https://93.184.216.34/hook", false).await.unwrap();
+        check_outbound_url("http://127.0.0.1:8080/hook", true).await.unwrap();
+        assert!(check_outbound_url("ftp://93.184.216.34/hook", true).await.is_err());
+    }
+}